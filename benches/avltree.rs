@@ -2,7 +2,9 @@ mod util;
 
 use std::time::Duration;
 
-use cds::avltree::SeqLockAVLTree;
+use cds::avltree::{RcuAVLTree, SeqLockAVLTree};
+use cds::bst::LockFreeBST;
+use cds::map::ConcurrentMap;
 use criterion::{criterion_group, Criterion};
 use criterion::{criterion_main, SamplingMode, Throughput};
 
@@ -20,11 +22,14 @@ const OPS_RATE: [(u64, u64, u64); 7] = [
     (50, 0, 50),
 ];
 
-fn bench_mixed_per_seqlockavltree(c: &mut Criterion) {
+fn bench_mixed_per_map<M>(name: &str, c: &mut Criterion)
+where
+    M: Sync + ConcurrentMap<u64, u64>,
+{
     for (insert, lookup, remove) in OPS_RATE {
         let mut group = c.benchmark_group(format!(
-            "SeqLockAVLTree/{:+e} pre-inserted, Ops(I: {}%, L: {}%, R: {}%, per: scaled by iters)",
-            MAP_ALREADY_INSERTED, insert, lookup, remove
+            "{}/{:+e} pre-inserted, Ops(I: {}%, L: {}%, R: {}%, per: scaled by iters)",
+            name, MAP_ALREADY_INSERTED, insert, lookup, remove
         ));
         group.sample_size(20);
         group.measurement_time(Duration::from_secs(15));
@@ -32,7 +37,7 @@ fn bench_mixed_per_seqlockavltree(c: &mut Criterion) {
 
         for num in get_test_thread_nums() {
             group.throughput(Throughput::Elements((100 * num) as u64));
-            criterion_linear_bench_mixed_concurrent_map::<SeqLockAVLTree<_, _>>(
+            criterion_linear_bench_mixed_concurrent_map::<M>(
                 MAP_ALREADY_INSERTED,
                 insert,
                 lookup,
@@ -45,7 +50,24 @@ fn bench_mixed_per_seqlockavltree(c: &mut Criterion) {
     }
 }
 
-criterion_group!(bench, bench_mixed_per_seqlockavltree,);
+fn bench_mixed_per_seqlockavltree(c: &mut Criterion) {
+    bench_mixed_per_map::<SeqLockAVLTree<_, _>>("SeqLockAVLTree", c);
+}
+
+fn bench_mixed_per_rcuavltree(c: &mut Criterion) {
+    bench_mixed_per_map::<RcuAVLTree<_, _>>("RcuAVLTree", c);
+}
+
+fn bench_mixed_per_lockfreebst(c: &mut Criterion) {
+    bench_mixed_per_map::<LockFreeBST<_, _>>("LockFreeBST", c);
+}
+
+criterion_group!(
+    bench,
+    bench_mixed_per_seqlockavltree,
+    bench_mixed_per_rcuavltree,
+    bench_mixed_per_lockfreebst,
+);
 criterion_main! {
     bench,
 }
@@ -1,16 +1,22 @@
 mod util;
 
+use std::collections::BTreeMap;
 use std::time::Duration;
 
 use criterion::{criterion_group, Criterion};
 use criterion::{criterion_main, SamplingMode, Throughput};
 
-use cds::{avltree::AVLTree, btree::BTree};
+use cds::avltree::{AVLTree, RwLockAVLTree, SeqLockAVLTree};
+use cds::btree::{BLinkTree, BTree};
+use cds::lock::RawSpinLock;
+use cds::map::{ConcurrentMap, FCMap, Locked, RwLocked, SequentialMap};
 
+use util::concurrent::*;
 use util::sequential::{bench_logs_btreemap, bench_logs_sequential_map, fuzz_sequential_logs};
 
 const MAP_ALREADY_INSERTED: u64 = 500_000;
 const MAP_TOTAL_OPS: usize = 192_000;
+const BULK_LOAD_SIZE: u64 = 1_000_000;
 
 const OPS_RATE: [(usize, usize, usize); 7] = [
     (100, 0, 0),
@@ -47,7 +53,95 @@ fn bench_vs_btreemap(c: &mut Criterion) {
     }
 }
 
-criterion_group!(bench, bench_vs_btreemap);
+fn bench_bulk_load(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("Bulk load {:+e} sorted keys", BULK_LOAD_SIZE));
+    group.sample_size(20);
+    group.throughput(Throughput::Elements(BULK_LOAD_SIZE));
+
+    group.bench_function("BTreeMap from_iter", |b| {
+        b.iter(|| {
+            (0..BULK_LOAD_SIZE)
+                .map(|i| (i, i))
+                .collect::<BTreeMap<_, _>>()
+        })
+    });
+    group.bench_function("BTree insert", |b| {
+        b.iter(|| {
+            let mut tree = BTree::new();
+
+            for i in 0..BULK_LOAD_SIZE {
+                let _ = tree.insert(i, i);
+            }
+
+            tree
+        })
+    });
+    group.bench_function("BTree from_sorted_iter", |b| {
+        b.iter(|| BTree::from_sorted_iter((0..BULK_LOAD_SIZE).map(|i| (i, i))))
+    });
+    group.finish();
+}
+
+fn bench_mixed_per_map<M>(name: &str, c: &mut Criterion)
+where
+    M: Sync + ConcurrentMap<u64, u64>,
+{
+    for (insert, lookup, remove) in OPS_RATE {
+        let mut group = c.benchmark_group(format!(
+            "{}/{:+e} pre-inserted, Ops(I: {}%, L: {}%, R: {}%, per: scaled by iters)",
+            name, MAP_ALREADY_INSERTED, insert, lookup, remove
+        ));
+        group.sample_size(20);
+        group.measurement_time(Duration::from_secs(15));
+        group.sampling_mode(SamplingMode::Linear);
+
+        for num in get_test_thread_nums() {
+            group.throughput(Throughput::Elements((100 * num) as u64));
+            criterion_linear_bench_mixed_concurrent_map::<M>(
+                MAP_ALREADY_INSERTED,
+                insert as u64,
+                lookup as u64,
+                remove as u64,
+                num,
+                &mut group,
+            );
+        }
+        group.finish();
+    }
+}
+
+fn bench_mixed_per_blinktree(c: &mut Criterion) {
+    bench_mixed_per_map::<BLinkTree<_, _>>("BLinkTree", c);
+}
+
+fn bench_mixed_per_seqlockavltree(c: &mut Criterion) {
+    bench_mixed_per_map::<SeqLockAVLTree<_, _>>("SeqLockAVLTree", c);
+}
+
+fn bench_mixed_per_rwlockavltree(c: &mut Criterion) {
+    bench_mixed_per_map::<RwLockAVLTree<_, _>>("RwLockAVLTree", c);
+}
+
+fn bench_mixed_per_fcmap(c: &mut Criterion) {
+    bench_mixed_per_map::<FCMap<_, _, RawSpinLock, BTree<_, _>>>("FCMap<BTree>", c);
+    bench_mixed_per_map::<FCMap<_, _, RawSpinLock, AVLTree<_, _>>>("FCMap<AVLTree>", c);
+}
+
+fn bench_mixed_per_locked(c: &mut Criterion) {
+    bench_mixed_per_map::<Locked<RawSpinLock, BTree<_, _>>>("Locked<BTree>", c);
+    bench_mixed_per_map::<RwLocked<AVLTree<_, _>>>("RwLocked<AVLTree>", c);
+}
+
+criterion_group!(
+    bench,
+    bench_vs_btreemap,
+    bench_bulk_load,
+    bench_mixed_per_blinktree,
+    bench_mixed_per_seqlockavltree,
+    bench_mixed_per_rwlockavltree,
+    bench_mixed_per_fcmap,
+    bench_mixed_per_locked,
+);
 criterion_main! {
     bench,
 }
@@ -0,0 +1,102 @@
+mod util;
+
+use std::time::Duration;
+
+use cds::hashtable::{CuckooHashMap, RobinHoodHashMap, SplitOrderedHashMap, StripedHashMap};
+use cds::lock::RawSpinLock;
+use cds::map::ConcurrentMap;
+use criterion::{criterion_group, Criterion};
+use criterion::{criterion_main, SamplingMode, Throughput};
+
+use util::concurrent::*;
+use util::sequential::{bench_logs_hashmap, bench_logs_sequential_map, fuzz_sequential_logs};
+
+const MAP_ALREADY_INSERTED: u64 = 500_000;
+const MAP_TOTAL_OPS: usize = 192_000;
+
+const OPS_RATE: [(u64, u64, u64); 7] = [
+    (100, 0, 0),
+    (0, 100, 0),
+    (0, 0, 100),
+    (5, 90, 5),
+    (30, 50, 20),
+    (40, 20, 40),
+    (50, 0, 50),
+];
+
+fn bench_vs_hashmap(c: &mut Criterion) {
+    for (insert, lookup, remove) in OPS_RATE {
+        let (insert, lookup, remove) = (insert as usize, lookup as usize, remove as usize);
+
+        let logs = fuzz_sequential_logs(
+            200,
+            MAP_ALREADY_INSERTED,
+            MAP_TOTAL_OPS * insert / 100,
+            MAP_TOTAL_OPS * lookup / 100,
+            MAP_TOTAL_OPS * remove / 100,
+        );
+
+        let mut group = c.benchmark_group(format!(
+            "Inserted {:+e}, Ops (I: {}%, L: {}%, R: {}%, total: {:+e})",
+            MAP_ALREADY_INSERTED, insert, lookup, remove, MAP_TOTAL_OPS
+        ));
+        group.measurement_time(Duration::from_secs(15)); // Note: make almost same the measurement_time to iters * avg_op_time
+        group.sampling_mode(SamplingMode::Flat);
+        group.sample_size(20);
+        group.throughput(Throughput::Elements(MAP_TOTAL_OPS as u64));
+
+        bench_logs_hashmap(logs.clone(), &mut group);
+        bench_logs_sequential_map::<RobinHoodHashMap<_, _>>("RobinHoodHashMap", logs, &mut group);
+    }
+}
+
+fn bench_mixed_per_map<M>(name: &str, c: &mut Criterion)
+where
+    M: Sync + ConcurrentMap<u64, u64>,
+{
+    for (insert, lookup, remove) in OPS_RATE {
+        let mut group = c.benchmark_group(format!(
+            "{}/{:+e} pre-inserted, Ops(I: {}%, L: {}%, R: {}%, per: scaled by iters)",
+            name, MAP_ALREADY_INSERTED, insert, lookup, remove
+        ));
+        group.sample_size(20);
+        group.measurement_time(Duration::from_secs(15));
+        group.sampling_mode(SamplingMode::Linear);
+
+        for num in get_test_thread_nums() {
+            group.throughput(Throughput::Elements((100 * num) as u64));
+            criterion_linear_bench_mixed_concurrent_map::<M>(
+                MAP_ALREADY_INSERTED,
+                insert,
+                lookup,
+                remove,
+                num,
+                &mut group,
+            );
+        }
+        group.finish();
+    }
+}
+
+fn bench_mixed_per_splitorderedhashmap(c: &mut Criterion) {
+    bench_mixed_per_map::<SplitOrderedHashMap<_, _>>("SplitOrderedHashMap", c);
+}
+
+fn bench_mixed_per_stripedhashmap(c: &mut Criterion) {
+    bench_mixed_per_map::<StripedHashMap<_, _, RawSpinLock>>("StripedHashMap", c);
+}
+
+fn bench_mixed_per_cuckoohashmap(c: &mut Criterion) {
+    bench_mixed_per_map::<CuckooHashMap<_, _>>("CuckooHashMap", c);
+}
+
+criterion_group!(
+    bench,
+    bench_vs_hashmap,
+    bench_mixed_per_splitorderedhashmap,
+    bench_mixed_per_stripedhashmap,
+    bench_mixed_per_cuckoohashmap,
+);
+criterion_main! {
+    bench,
+}
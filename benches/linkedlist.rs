@@ -0,0 +1,73 @@
+mod util;
+
+use std::time::Duration;
+
+use cds::linkedlist::{HandOverHandList, HarrisList, LazyList};
+use cds::lock::RawSpinLock;
+use cds::map::ConcurrentMap;
+use criterion::{criterion_group, Criterion};
+use criterion::{criterion_main, SamplingMode, Throughput};
+
+use util::concurrent::*;
+
+const MAP_ALREADY_INSERTED: u64 = 1_000;
+
+const OPS_RATE: [(u64, u64, u64); 7] = [
+    (100, 0, 0),
+    (0, 100, 0),
+    (0, 0, 100),
+    (5, 90, 5),
+    (30, 50, 20),
+    (40, 20, 40),
+    (50, 0, 50),
+];
+
+fn bench_mixed_per_list<M>(name: &str, c: &mut Criterion)
+where
+    M: Sync + ConcurrentMap<u64, u64>,
+{
+    for (insert, lookup, remove) in OPS_RATE {
+        let mut group = c.benchmark_group(format!(
+            "{}/{:+e} pre-inserted, Ops(I: {}%, L: {}%, R: {}%, per: scaled by iters)",
+            name, MAP_ALREADY_INSERTED, insert, lookup, remove
+        ));
+        group.sample_size(20);
+        group.measurement_time(Duration::from_secs(15));
+        group.sampling_mode(SamplingMode::Linear);
+
+        for num in get_test_thread_nums() {
+            group.throughput(Throughput::Elements((100 * num) as u64));
+            criterion_linear_bench_mixed_concurrent_map::<M>(
+                MAP_ALREADY_INSERTED,
+                insert,
+                lookup,
+                remove,
+                num,
+                &mut group,
+            );
+        }
+        group.finish();
+    }
+}
+
+fn bench_mixed_per_harrislist(c: &mut Criterion) {
+    bench_mixed_per_list::<HarrisList<_, _>>("HarrisList", c);
+}
+
+fn bench_mixed_per_lazylist(c: &mut Criterion) {
+    bench_mixed_per_list::<LazyList<_, _, RawSpinLock>>("LazyList", c);
+}
+
+fn bench_mixed_per_handoverhandlist(c: &mut Criterion) {
+    bench_mixed_per_list::<HandOverHandList<_, _, RawSpinLock>>("HandOverHandList", c);
+}
+
+criterion_group!(
+    bench,
+    bench_mixed_per_harrislist,
+    bench_mixed_per_lazylist,
+    bench_mixed_per_handoverhandlist,
+);
+criterion_main! {
+    bench,
+}
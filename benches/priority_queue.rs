@@ -0,0 +1,154 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use cds::lock::{RawMutex, RawSpinLock};
+use cds::priority_queue::{
+    ConcurrentPriorityQueue, FCPriorityQueue, PairingHeap, SkipListPriorityQueue,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, SamplingMode, Throughput};
+use crossbeam_utils::thread;
+use rand::{thread_rng, Rng};
+
+mod util;
+
+use util::concurrent::get_test_thread_nums;
+
+const PQ_PER_OPS: usize = 10_000;
+const PQ_PUSH_RATE: usize = 50;
+const PQ_POP_RATE: usize = 50;
+
+/// the baseline of the mutex-wrapped binary heap
+struct MutexBinaryHeap<K, V> {
+    heap: Mutex<BinaryHeap<Reverse<(K, V)>>>,
+}
+
+impl<K: Ord, V: Ord> ConcurrentPriorityQueue<K, V> for MutexBinaryHeap<K, V> {
+    fn new() -> Self {
+        Self {
+            heap: Mutex::new(BinaryHeap::new()),
+        }
+    }
+
+    fn push(&self, key: K, value: V) {
+        self.heap.lock().unwrap().push(Reverse((key, value)));
+    }
+
+    fn try_pop_min(&self) -> Option<(K, V)> {
+        self.heap.lock().unwrap().pop().map(|Reverse(entry)| entry)
+    }
+
+    fn pop_min(&self) -> (K, V) {
+        loop {
+            if let Some(entry) = self.try_pop_min() {
+                return entry;
+            }
+        }
+    }
+}
+
+fn bench_mixed_concurrent_priority_queue<Q>(name: &str, c: &mut Criterion)
+where
+    Q: Sync + ConcurrentPriorityQueue<u64, u64>,
+{
+    let mut group = c.benchmark_group(format!(
+        "{}/Ops(push: {}%, pop: {}%, per: {:+e})",
+        name, PQ_PUSH_RATE, PQ_POP_RATE, PQ_PER_OPS
+    ));
+    group.sampling_mode(SamplingMode::Flat);
+
+    for num in get_test_thread_nums() {
+        group.measurement_time(Duration::from_secs(num as u64));
+        group.throughput(Throughput::Elements((PQ_PER_OPS * num) as u64));
+        group.bench_function(format!("{} threads", num), |b| {
+            b.iter_custom(|iters| {
+                let queue = Q::new();
+
+                let mut duration = Duration::ZERO;
+                for _ in 0..iters {
+                    let batched_time = thread::scope(|s| {
+                        let mut threads = Vec::new();
+
+                        for _ in 0..num {
+                            let t = s.spawn(|_| {
+                                let mut rng = thread_rng();
+                                let mut duration = Duration::ZERO;
+
+                                for _ in 0..PQ_PER_OPS {
+                                    let op_idx = rng.gen_range(0..PQ_PER_OPS);
+
+                                    if op_idx < PQ_PUSH_RATE * PQ_PER_OPS / 100 {
+                                        let key: u64 = rng.gen();
+
+                                        let start = Instant::now();
+                                        queue.push(black_box(key), key);
+                                        duration += start.elapsed();
+                                    } else {
+                                        let start = Instant::now();
+                                        let _ = black_box(queue.try_pop_min());
+                                        duration += start.elapsed();
+                                    }
+                                }
+
+                                duration
+                            });
+
+                            threads.push(t);
+                        }
+
+                        threads
+                            .into_iter()
+                            .map(|h| h.join().unwrap())
+                            .sum::<Duration>()
+                    })
+                    .unwrap();
+
+                    duration += batched_time
+                }
+
+                // avg thread time
+                duration / (num as u32)
+            });
+        });
+    }
+}
+
+fn bench_mixed_mutex_binary_heap(c: &mut Criterion) {
+    bench_mixed_concurrent_priority_queue::<MutexBinaryHeap<_, _>>("Mutex<BinaryHeap>", c);
+}
+
+fn bench_mixed_skiplist_priority_queue(c: &mut Criterion) {
+    bench_mixed_concurrent_priority_queue::<SkipListPriorityQueue<_, _>>(
+        "SkipListPriorityQueue",
+        c,
+    );
+}
+
+fn bench_mixed_flat_combining_spinlock_pairing_heap(c: &mut Criterion) {
+    bench_mixed_concurrent_priority_queue::<FCPriorityQueue<_, _, RawSpinLock, PairingHeap<_, _>>>(
+        "FCPriorityQueue<RawSpinLock, PairingHeap>",
+        c,
+    );
+}
+
+fn bench_mixed_flat_combining_mutex_pairing_heap(c: &mut Criterion) {
+    bench_mixed_concurrent_priority_queue::<FCPriorityQueue<_, _, RawMutex, PairingHeap<_, _>>>(
+        "FCPriorityQueue<RawMutex, PairingHeap>",
+        c,
+    );
+}
+
+criterion_group!(
+    bench,
+    bench_mixed_mutex_binary_heap,
+    bench_mixed_skiplist_priority_queue,
+    bench_mixed_flat_combining_spinlock_pairing_heap,
+    bench_mixed_flat_combining_mutex_pairing_heap
+);
+
+criterion_main! {
+    bench,
+}
@@ -228,7 +228,7 @@ where
 
     // pre-insert
     for i in range {
-        let _ = map.insert(&i, i);
+        let _ = map.insert(i, i);
     }
 
     let duration = thread::scope(|s| {
@@ -246,7 +246,7 @@ where
                         let key: u64 = rng.gen_range(already_inserted..u64::MAX);
 
                         let start = Instant::now();
-                        let _ = black_box(map.insert(&key, key));
+                        let _ = black_box(map.insert(key, key));
                         duration += start.elapsed();
                     } else if op_idx < insert + lookup {
                         let key: u64 = rng.gen_range(0..already_inserted);
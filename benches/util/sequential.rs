@@ -1,5 +1,5 @@
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashMap},
     time::{Duration, Instant},
 };
 
@@ -120,6 +120,42 @@ pub fn bench_logs_btreemap(mut logs: Vec<(Vec<u64>, Vec<Op>)>, c: &mut Benchmark
     });
 }
 
+pub fn bench_logs_hashmap(mut logs: Vec<(Vec<u64>, Vec<Op>)>, c: &mut BenchmarkGroup<WallTime>) {
+    c.bench_function("std::HashMap", |b| {
+        b.iter_custom(|iters| {
+            let mut duration = Duration::ZERO;
+
+            for _ in 0..iters {
+                let (pre_inserted, logs) = logs.pop().unwrap();
+                let mut map = HashMap::new();
+
+                // pre-insert
+                for key in pre_inserted {
+                    let _ = map.insert(key, key);
+                }
+
+                let start = Instant::now();
+                for op in logs {
+                    match op {
+                        Op::Insert(key) => {
+                            let _ = black_box(map.insert(key, key));
+                        }
+                        Op::Lookup(key) => {
+                            let _ = black_box(map.get(&key));
+                        }
+                        Op::Remove(key) => {
+                            let _ = black_box(map.remove(&key));
+                        }
+                    }
+                }
+                duration += start.elapsed();
+            }
+
+            duration
+        });
+    });
+}
+
 pub fn bench_logs_sequential_map<M>(
     name: &str,
     mut logs: Vec<(Vec<u64>, Vec<Op>)>,
@@ -137,14 +173,14 @@ pub fn bench_logs_sequential_map<M>(
 
                 // pre-insert
                 for key in pre_inserted {
-                    let _ = map.insert(&key, key);
+                    let _ = map.insert(key, key);
                 }
 
                 let start = Instant::now();
                 for op in logs {
                     match op {
                         Op::Insert(key) => {
-                            let _ = black_box(map.insert(&key, key));
+                            let _ = black_box(map.insert(key, key));
                         }
                         Op::Lookup(key) => {
                             let _ = black_box(map.lookup(&key));
@@ -0,0 +1,140 @@
+mod seqlock;
+
+pub use seqlock::SeqLockART;
+
+/// the key of the radix tree, which is indexed by its bytes
+///
+/// The bytes should be prefix-free: the different keys write the different bytes, and no bytes of a key are the
+/// prefix of the bytes of another key. The entries are scanned in the order of the bytes, so the implementations
+/// here write the bytes in the order of the keys: the integers in big endian with the sign bit flipped,
+/// and the strings and the byte slices with their zero bytes escaped and a terminator.
+/// The borrowed forms of the key, like `str` for `String`, should write the same bytes.
+pub trait ArtKey {
+    /// write the bytes of the key
+    fn write_bytes(&self, bytes: &mut Vec<u8>);
+
+    /// write the bytes which the bytes of the keys starting with this key start with
+    ///
+    /// It is the bytes without the terminator for the strings, and the same bytes for the fixed-size keys.
+    fn write_prefix_bytes(&self, bytes: &mut Vec<u8>) {
+        self.write_bytes(bytes);
+    }
+}
+
+macro_rules! impl_art_key_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl ArtKey for $t {
+                fn write_bytes(&self, bytes: &mut Vec<u8>) {
+                    bytes.extend_from_slice(&self.to_be_bytes());
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_art_key_signed {
+    ($($t:ty),*) => {
+        $(
+            impl ArtKey for $t {
+                fn write_bytes(&self, bytes: &mut Vec<u8>) {
+                    // flip the sign bit so that the negative numbers are before the positive ones
+                    let mut be = self.to_be_bytes();
+                    be[0] ^= 0x80;
+                    bytes.extend_from_slice(&be);
+                }
+            }
+        )*
+    };
+}
+
+impl_art_key_unsigned!(u8, u16, u32, u64, u128, usize);
+impl_art_key_signed!(i8, i16, i32, i64, i128, isize);
+
+impl ArtKey for char {
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        (*self as u32).write_bytes(bytes);
+    }
+}
+
+impl ArtKey for bool {
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        bytes.push(*self as u8);
+    }
+}
+
+/// The unit key writes no bytes, so the tree has at most one entry of it.
+impl ArtKey for () {
+    fn write_bytes(&self, _: &mut Vec<u8>) {}
+}
+
+impl ArtKey for [u8] {
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        self.write_prefix_bytes(bytes);
+        bytes.extend_from_slice(&[0, 0]);
+    }
+
+    /// The zero byte is escaped to [0, 0xff], so the terminator [0, 0] is never on the bytes and is less than
+    /// any byte after it.
+    fn write_prefix_bytes(&self, bytes: &mut Vec<u8>) {
+        for byte in self {
+            match byte {
+                0 => bytes.extend_from_slice(&[0, 0xff]),
+                byte => bytes.push(*byte),
+            }
+        }
+    }
+}
+
+impl ArtKey for Vec<u8> {
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        self.as_slice().write_bytes(bytes);
+    }
+
+    fn write_prefix_bytes(&self, bytes: &mut Vec<u8>) {
+        self.as_slice().write_prefix_bytes(bytes);
+    }
+}
+
+impl ArtKey for str {
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        self.as_bytes().write_bytes(bytes);
+    }
+
+    fn write_prefix_bytes(&self, bytes: &mut Vec<u8>) {
+        self.as_bytes().write_prefix_bytes(bytes);
+    }
+}
+
+impl ArtKey for String {
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        self.as_str().write_bytes(bytes);
+    }
+
+    fn write_prefix_bytes(&self, bytes: &mut Vec<u8>) {
+        self.as_str().write_prefix_bytes(bytes);
+    }
+}
+
+impl<T: ?Sized + ArtKey> ArtKey for &T {
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        (**self).write_bytes(bytes);
+    }
+
+    fn write_prefix_bytes(&self, bytes: &mut Vec<u8>) {
+        (**self).write_prefix_bytes(bytes);
+    }
+}
+
+/// The tuple writes the bytes of its elements in order, so the keys with the same first elements are adjacent.
+impl<A: ArtKey, B: ArtKey> ArtKey for (A, B) {
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        self.0.write_bytes(bytes);
+        self.1.write_bytes(bytes);
+    }
+
+    fn write_prefix_bytes(&self, bytes: &mut Vec<u8>) {
+        self.0.write_bytes(bytes);
+        self.1.write_prefix_bytes(bytes);
+    }
+}
@@ -0,0 +1,988 @@
+/*
+ Refer to
+ Leis et al., The Adaptive Radix Tree: ARTful Indexing for Main-Memory Databases, ICDE 2013
+ Leis et al., The ART of Practical Synchronization, DaMoN 2016
+*/
+
+use std::{
+    borrow::Borrow,
+    mem::ManuallyDrop,
+    sync::atomic::{AtomicU8, AtomicUsize, Ordering},
+};
+
+use crossbeam_epoch::{pin, unprotected, Atomic, Guard, Owned, Shared};
+
+use super::ArtKey;
+use crate::lock::seqlock::{ReadGuard, SeqLock, WriteGuard};
+use crate::map::{ConcurrentLookup, ConcurrentMap};
+use crate::some_or;
+
+/// get the bytes of the key which are the path on the tree
+fn key_bytes<Q: ?Sized + ArtKey>(key: &Q) -> Box<[u8]> {
+    let mut bytes = Vec::new();
+    key.write_bytes(&mut bytes);
+
+    bytes.into_boxed_slice()
+}
+
+/// the length of the common prefix of both bytes
+fn common_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+type InnerReadGuard<'g, K, V> = ManuallyDrop<ReadGuard<'g, Inner<K, V>>>;
+
+/// lock the node by the read guard, which fails if the node is changed after the read
+fn upgrade<K, V>(read: InnerReadGuard<'_, K, V>) -> Option<WriteGuard<'_, Inner<K, V>>> {
+    ManuallyDrop::into_inner(read).upgrade().ok()
+}
+
+enum Node<K, V> {
+    Inner(SeqLock<Inner<K, V>>),
+    Leaf(Leaf<K, V>),
+}
+
+impl<K, V> Node<K, V> {
+    fn inner(prefix: &[u8], children: Children<K, V>) -> Self {
+        Node::Inner(SeqLock::new(Inner {
+            prefix: prefix.into(),
+            children,
+        }))
+    }
+
+    fn as_leaf(&self) -> Option<&Leaf<K, V>> {
+        match self {
+            Node::Leaf(leaf) => Some(leaf),
+            Node::Inner(_) => None,
+        }
+    }
+}
+
+/// The leaf has the whole bytes of the key, so the inner nodes above it may skip some bytes.
+///
+/// The value is dropped with the leaf, so the readers pinned before the leaf is unlinked can still read it.
+struct Leaf<K, V> {
+    key: K,
+    bytes: Box<[u8]>,
+    value: V,
+}
+
+/// The prefix is never changed, so the node is copied with the new prefix to split it.
+struct Inner<K, V> {
+    prefix: Box<[u8]>,
+    children: Children<K, V>,
+}
+
+/// the children of the inner node, which are read optimistically so all of them are atomic
+///
+/// The kind of the node is never changed, so the node is copied to the bigger one to grow it.
+/// They are boxed since the sizes of the kinds are very different.
+enum Children<K, V> {
+    Node4(Box<Sorted<K, V, 4>>),
+    Node16(Box<Sorted<K, V, 16>>),
+    Node48(Box<Indexed<K, V>>),
+    Node256(Box<Direct<K, V>>),
+}
+
+/// the children sorted by the bytes for Node4 and Node16
+struct Sorted<K, V, const N: usize> {
+    len: AtomicUsize,
+    bytes: [AtomicU8; N],
+    children: [Atomic<Node<K, V>>; N],
+}
+
+/// the children indexed by the bytes for Node48, where the index 0 means empty
+struct Indexed<K, V> {
+    len: AtomicUsize,
+    index: [AtomicU8; 256],
+    children: [Atomic<Node<K, V>>; 48],
+}
+
+/// the children on their bytes for Node256
+struct Direct<K, V> {
+    len: AtomicUsize,
+    children: [Atomic<Node<K, V>>; 256],
+}
+
+impl<K, V, const N: usize> Sorted<K, V, N> {
+    fn new() -> Self {
+        Self {
+            len: AtomicUsize::new(0),
+            bytes: [(); N].map(|_| AtomicU8::new(0)),
+            children: [(); N].map(|_| Atomic::null()),
+        }
+    }
+
+    /// find the index of the byte, or the index where it should be inserted
+    fn search(&self, byte: u8) -> Result<usize, usize> {
+        let len = self.len.load(Ordering::Relaxed).min(N);
+
+        for i in 0..len {
+            let current = self.bytes[i].load(Ordering::Relaxed);
+
+            if current == byte {
+                return Ok(i);
+            }
+
+            if current > byte {
+                return Err(i);
+            }
+        }
+
+        Err(len)
+    }
+
+    /// move the child on the index to the other index
+    fn move_child(&self, from: usize, to: usize, guard: &Guard) {
+        self.bytes[to].store(self.bytes[from].load(Ordering::Relaxed), Ordering::Relaxed);
+        self.children[to].store(
+            self.children[from].load(Ordering::Relaxed, guard),
+            Ordering::Release,
+        );
+    }
+}
+
+impl<K, V> Children<K, V> {
+    fn new(capacity: usize) -> Self {
+        match capacity {
+            4 => Children::Node4(Box::new(Sorted::new())),
+            16 => Children::Node16(Box::new(Sorted::new())),
+            48 => Children::Node48(Box::new(Indexed {
+                len: AtomicUsize::new(0),
+                index: [(); 256].map(|_| AtomicU8::new(0)),
+                children: [(); 48].map(|_| Atomic::null()),
+            })),
+            _ => Children::Node256(Box::new(Direct {
+                len: AtomicUsize::new(0),
+                children: [(); 256].map(|_| Atomic::null()),
+            })),
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        match self {
+            Children::Node4(_) => 4,
+            Children::Node16(_) => 16,
+            Children::Node48(_) => 48,
+            Children::Node256(_) => 256,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Children::Node4(node) => node.len.load(Ordering::Relaxed),
+            Children::Node16(node) => node.len.load(Ordering::Relaxed),
+            Children::Node48(node) => node.len.load(Ordering::Relaxed),
+            Children::Node256(node) => node.len.load(Ordering::Relaxed),
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.len() >= self.capacity()
+    }
+
+    /// get the child of the byte, which is null if not exists
+    fn get<'g>(&self, byte: u8, guard: &'g Guard) -> Shared<'g, Node<K, V>> {
+        let child = match self {
+            Children::Node4(node) => node.search(byte).ok().map(|i| &node.children[i]),
+            Children::Node16(node) => node.search(byte).ok().map(|i| &node.children[i]),
+            Children::Node48(node) => match node.index[byte as usize].load(Ordering::Relaxed) {
+                0 => None,
+                slot => Some(&node.children[slot as usize - 1]),
+            },
+            Children::Node256(node) => Some(&node.children[byte as usize]),
+        };
+
+        child.map_or(Shared::null(), |child| child.load(Ordering::Acquire, guard))
+    }
+
+    /// get all children in ascending order of the bytes
+    fn entries<'g>(&self, guard: &'g Guard) -> Vec<(u8, Shared<'g, Node<K, V>>)> {
+        let entries = match self {
+            Children::Node4(node) => (0..node.len.load(Ordering::Relaxed).min(4))
+                .map(|i| (node.bytes[i].load(Ordering::Relaxed), &node.children[i]))
+                .collect::<Vec<_>>(),
+            Children::Node16(node) => (0..node.len.load(Ordering::Relaxed).min(16))
+                .map(|i| (node.bytes[i].load(Ordering::Relaxed), &node.children[i]))
+                .collect(),
+            Children::Node48(node) => (0..=u8::MAX)
+                .filter_map(
+                    |byte| match node.index[byte as usize].load(Ordering::Relaxed) {
+                        0 => None,
+                        slot => Some((byte, &node.children[slot as usize - 1])),
+                    },
+                )
+                .collect(),
+            Children::Node256(node) => (0..=u8::MAX)
+                .map(|byte| (byte, &node.children[byte as usize]))
+                .collect(),
+        };
+
+        entries
+            .into_iter()
+            .map(|(byte, child)| (byte, child.load(Ordering::Acquire, guard)))
+            .filter(|(_, child)| !child.is_null())
+            .collect()
+    }
+
+    /// add the child of the byte, where the node should be locked and not be full
+    fn add(&self, byte: u8, child: Shared<'_, Node<K, V>>, guard: &Guard) {
+        match self {
+            Children::Node4(node) => Self::add_sorted(node, byte, child, guard),
+            Children::Node16(node) => Self::add_sorted(node, byte, child, guard),
+            Children::Node48(node) => {
+                let slot = (0..48)
+                    .find(|i| node.children[*i].load(Ordering::Relaxed, guard).is_null())
+                    .unwrap();
+
+                node.children[slot].store(child, Ordering::Release);
+                node.index[byte as usize].store(slot as u8 + 1, Ordering::Relaxed);
+                node.len.fetch_add(1, Ordering::Relaxed);
+            }
+            Children::Node256(node) => {
+                node.children[byte as usize].store(child, Ordering::Release);
+                node.len.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn add_sorted<const N: usize>(
+        node: &Sorted<K, V, N>,
+        byte: u8,
+        child: Shared<'_, Node<K, V>>,
+        guard: &Guard,
+    ) {
+        let len = node.len.load(Ordering::Relaxed);
+        let index = node.search(byte).unwrap_err();
+
+        for i in (index..len).rev() {
+            node.move_child(i, i + 1, guard);
+        }
+
+        node.bytes[index].store(byte, Ordering::Relaxed);
+        node.children[index].store(child, Ordering::Release);
+        node.len.store(len + 1, Ordering::Relaxed);
+    }
+
+    /// replace the child of the byte, where the node should be locked
+    fn replace(&self, byte: u8, child: Shared<'_, Node<K, V>>) {
+        let target = match self {
+            Children::Node4(node) => &node.children[node.search(byte).unwrap()],
+            Children::Node16(node) => &node.children[node.search(byte).unwrap()],
+            Children::Node48(node) => {
+                &node.children[node.index[byte as usize].load(Ordering::Relaxed) as usize - 1]
+            }
+            Children::Node256(node) => &node.children[byte as usize],
+        };
+
+        target.store(child, Ordering::Release);
+    }
+
+    /// remove the child of the byte, where the node should be locked
+    fn remove(&self, byte: u8, guard: &Guard) {
+        match self {
+            Children::Node4(node) => Self::remove_sorted(node, byte, guard),
+            Children::Node16(node) => Self::remove_sorted(node, byte, guard),
+            Children::Node48(node) => {
+                let slot = node.index[byte as usize].swap(0, Ordering::Relaxed) as usize;
+                node.children[slot - 1].store(Shared::null(), Ordering::Release);
+                node.len.fetch_sub(1, Ordering::Relaxed);
+            }
+            Children::Node256(node) => {
+                node.children[byte as usize].store(Shared::null(), Ordering::Release);
+                node.len.fetch_sub(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn remove_sorted<const N: usize>(node: &Sorted<K, V, N>, byte: u8, guard: &Guard) {
+        let len = node.len.load(Ordering::Relaxed);
+        let index = node.search(byte).unwrap();
+
+        for i in index + 1..len {
+            node.move_child(i, i - 1, guard);
+        }
+
+        node.children[len - 1].store(Shared::null(), Ordering::Release);
+        node.len.store(len - 1, Ordering::Relaxed);
+    }
+
+    /// copy the children to the new node of the capacity
+    fn copy(&self, capacity: usize, guard: &Guard) -> Self {
+        let copy = Self::new(capacity);
+
+        for (byte, child) in self.entries(guard) {
+            copy.add(byte, child, guard);
+        }
+
+        copy
+    }
+
+    /// copy the children to the bigger node
+    fn grow(&self, guard: &Guard) -> Self {
+        let capacity = match self.capacity() {
+            4 => 16,
+            16 => 48,
+            _ => 256,
+        };
+
+        self.copy(capacity, guard)
+    }
+}
+
+/// the place where the search stops on the node
+enum Position<'g, K, V> {
+    /// the bytes mismatch the prefix of the node at the offset, or end there
+    Mismatch(usize),
+    /// the child of the byte after the prefix, which is null or a leaf
+    Child(u8, Shared<'g, Node<K, V>>),
+}
+
+/// the result of searching the bytes with the read guards of the last two nodes
+struct Cursor<'g, K, V> {
+    /// the parent of the node with its read guard and the byte of the node
+    parent: Option<(InnerReadGuard<'g, K, V>, u8)>,
+    /// the pointer of the node, which is null on the root
+    node: Shared<'g, Node<K, V>>,
+    read: InnerReadGuard<'g, K, V>,
+    /// the offset of the prefix of the node on the bytes
+    depth: usize,
+    position: Position<'g, K, V>,
+}
+
+/// Concurrent adaptive radix tree by optimistic lock coupling
+///
+/// The readers never write on the nodes but validate the versions of them by SeqLock,
+/// and the writers lock only the nodes to change.
+///
+/// The keys are indexed by the bytes that ArtKey writes, and the entries are scanned in the order of the bytes.
+pub struct SeqLockART<K, V> {
+    root: SeqLock<Inner<K, V>>, // Node256 without prefix, which is never replaced
+    empty: Atomic<Node<K, V>>,  // the leaf of the key without bytes, which has no byte on the root
+}
+
+impl<K, V> Default for SeqLockART<K, V> {
+    fn default() -> Self {
+        Self {
+            root: SeqLock::new(Inner {
+                prefix: Box::new([]),
+                children: Children::new(256),
+            }),
+            empty: Atomic::null(),
+        }
+    }
+}
+
+impl<K, V> SeqLockART<K, V> {
+    /// take the entry of the leaf which is never shared
+    fn into_entry(leaf: Node<K, V>) -> (K, V) {
+        match leaf {
+            Node::Leaf(Leaf { key, value, .. }) => (key, value),
+            Node::Inner(_) => unreachable!(),
+        }
+    }
+
+    /// find the node which has the bytes or should have them
+    ///
+    /// The parent is validated after reading the version of the child, so the nodes on the cursor are not replaced
+    /// until the read. If any validation fails, return None.
+    fn search<'g>(&'g self, bytes: &[u8], guard: &'g Guard) -> Option<Cursor<'g, K, V>> {
+        let mut parent = None;
+        let mut node = Shared::null();
+        let mut read = ManuallyDrop::new(unsafe { self.root.read_lock() });
+        let mut depth = 0;
+
+        loop {
+            let rest = &bytes[depth.min(bytes.len())..];
+            let prefix_len = read.prefix.len();
+            let matched = common_len(&read.prefix, rest);
+
+            if matched < prefix_len || matched == rest.len() {
+                if !read.validate() {
+                    return None;
+                }
+
+                return Some(Cursor {
+                    parent,
+                    node,
+                    read,
+                    depth,
+                    position: Position::Mismatch(matched),
+                });
+            }
+
+            let byte = rest[matched];
+            let child = read.children.get(byte, guard);
+
+            if !read.validate() {
+                return None;
+            }
+
+            let child_lock = match unsafe { child.as_ref() } {
+                Some(Node::Inner(lock)) => lock,
+                _ => {
+                    return Some(Cursor {
+                        parent,
+                        node,
+                        read,
+                        depth,
+                        position: Position::Child(byte, child),
+                    })
+                }
+            };
+
+            let child_read = ManuallyDrop::new(unsafe { child_lock.read_lock() });
+
+            if !read.validate() {
+                return None;
+            }
+
+            parent = Some((read, byte));
+            node = child;
+            read = child_read;
+            depth += prefix_len + 1;
+        }
+    }
+
+    /// get the byte and the pointer of the leaf on the cursor whose bytes are the same with the bytes
+    fn found<'g>(cursor: &Cursor<'g, K, V>, bytes: &[u8]) -> Option<(u8, Shared<'g, Node<K, V>>)> {
+        match cursor.position {
+            Position::Child(byte, child) => {
+                let leaf = unsafe { child.as_ref() }?.as_leaf().unwrap();
+
+                if *leaf.bytes == *bytes {
+                    Some((byte, child))
+                } else {
+                    None
+                }
+            }
+            Position::Mismatch(_) => None,
+        }
+    }
+
+    /// link the new leaf on the cursor of its bytes, where no leaf of the same bytes is on the cursor
+    ///
+    /// If the nodes on the cursor are changed after the search, return false and the caller should retry.
+    fn link_leaf<'g>(
+        &'g self,
+        cursor: Cursor<'g, K, V>,
+        leaf: Shared<'g, Node<K, V>>,
+        guard: &'g Guard,
+    ) -> bool {
+        let bytes = &unsafe { leaf.deref() }.as_leaf().unwrap().bytes;
+        let depth = cursor.depth;
+
+        match cursor.position {
+            Position::Mismatch(matched) => {
+                assert!(
+                    matched < cursor.read.prefix.len() && depth + matched < bytes.len(),
+                    "The bytes of the keys should be prefix-free."
+                );
+
+                // split the prefix of the node by the new node
+                let (parent_read, parent_byte) = cursor.parent.unwrap();
+                let parent = some_or!(upgrade(parent_read), return false);
+                let node = some_or!(upgrade(cursor.read), return false);
+
+                let prefix = &node.prefix;
+                let copy = Node::inner(
+                    &prefix[matched + 1..],
+                    node.children.copy(node.children.capacity(), guard),
+                );
+
+                let children = Children::new(4);
+                children.add(prefix[matched], Owned::new(copy).into_shared(guard), guard);
+                children.add(bytes[depth + matched], leaf, guard);
+
+                let split = Owned::new(Node::inner(&prefix[..matched], children));
+                parent
+                    .children
+                    .replace(parent_byte, split.into_shared(guard));
+
+                drop(node);
+                unsafe { guard.defer_destroy(cursor.node) };
+
+                true
+            }
+            Position::Child(byte, child) => {
+                if child.is_null() && !cursor.read.children.is_full() {
+                    let node = some_or!(upgrade(cursor.read), return false);
+                    node.children.add(byte, leaf, guard);
+
+                    return true;
+                }
+
+                if child.is_null() {
+                    // grow the node with the leaf, where the root is never full
+                    let (parent_read, parent_byte) = cursor.parent.unwrap();
+                    let parent = some_or!(upgrade(parent_read), return false);
+                    let node = some_or!(upgrade(cursor.read), return false);
+
+                    let children = node.children.grow(guard);
+                    children.add(byte, leaf, guard);
+
+                    let grown = Owned::new(Node::inner(&node.prefix, children));
+                    parent
+                        .children
+                        .replace(parent_byte, grown.into_shared(guard));
+
+                    drop(node);
+                    unsafe { guard.defer_destroy(cursor.node) };
+
+                    return true;
+                }
+
+                let old = unsafe { child.deref() }.as_leaf().unwrap();
+
+                // split the old leaf by the new node
+                let depth = depth + cursor.read.prefix.len() + 1;
+                let node = some_or!(upgrade(cursor.read), return false);
+
+                let matched = common_len(&old.bytes[depth..], &bytes[depth..]);
+                assert!(
+                    matched < old.bytes.len().min(bytes.len()) - depth,
+                    "The bytes of the keys should be prefix-free."
+                );
+
+                let children = Children::new(4);
+                children.add(old.bytes[depth + matched], child, guard);
+                children.add(bytes[depth + matched], leaf, guard);
+
+                let split = Owned::new(Node::inner(&bytes[depth..depth + matched], children));
+                node.children.replace(byte, split.into_shared(guard));
+
+                true
+            }
+        }
+    }
+
+    /// unlink the leaf of the byte from the node on the cursor, and the node too if it is left with one leaf or nothing
+    ///
+    /// If the nodes on the cursor are changed after the search, return false and the caller should retry.
+    fn unlink_leaf<'g>(&'g self, cursor: Cursor<'g, K, V>, byte: u8, guard: &'g Guard) -> bool {
+        match cursor.parent {
+            Some((parent_read, parent_byte)) if cursor.read.children.len() <= 2 => {
+                // the node may be left with one leaf or nothing, so remove it from the parent
+                let parent = some_or!(upgrade(parent_read), return false);
+                let node = some_or!(upgrade(cursor.read), return false);
+
+                node.children.remove(byte, guard);
+                let entries = node.children.entries(guard);
+
+                let replacement = match entries.as_slice() {
+                    [] => Some(Shared::null()),
+                    [(_, last)] if unsafe { last.deref() }.as_leaf().is_some() => Some(*last),
+                    _ => None,
+                };
+
+                if let Some(replacement) = replacement {
+                    if replacement.is_null() {
+                        parent.children.remove(parent_byte, guard);
+                    } else {
+                        parent.children.replace(parent_byte, replacement);
+                    }
+
+                    drop(node);
+                    unsafe { guard.defer_destroy(cursor.node) };
+                }
+            }
+            _ => {
+                let node = some_or!(upgrade(cursor.read), return false);
+                node.children.remove(byte, guard);
+            }
+        }
+
+        true
+    }
+
+    /// get the entries whose keys start with the prefix in ascending order of the bytes of the keys
+    ///
+    /// The prefix writes the bytes by ArtKey, like the starting string of the string keys or the first elements of
+    /// the tuple keys, and `()` writes no bytes to scan all entries.
+    /// It is weakly consistent: each node is read atomically, but the nodes are read at the different times.
+    /// The node changed while reading is read again alone, so the scan does not restart under steady writes.
+    /// The replaced node keeps its children as they were on replacing, so the entries which exist during the whole
+    /// scan are always returned, but the entries inserted or removed during the scan may or may not be returned.
+    pub fn scan_prefix<P>(&self, prefix: &P) -> Vec<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+        P: ?Sized + ArtKey,
+    {
+        let mut bytes = Vec::new();
+        prefix.write_prefix_bytes(&mut bytes);
+        let prefix = bytes.as_slice();
+
+        let guard = pin();
+
+        'retry: loop {
+            let mut nodes = Vec::new();
+            let mut lock = &self.root;
+            let mut depth = 0;
+
+            // find the subtree whose keys all start with the prefix
+            loop {
+                let read = ManuallyDrop::new(unsafe { lock.read_lock() });
+                let rest = &prefix[depth..];
+                let matched = common_len(&read.prefix, rest);
+
+                if matched == rest.len() {
+                    let entries = read.children.entries(&guard);
+
+                    if !read.validate() {
+                        continue 'retry;
+                    }
+
+                    nodes.extend(entries.into_iter().rev().map(|(_, child)| child));
+                    break;
+                }
+
+                if matched < read.prefix.len() {
+                    if !read.validate() {
+                        continue 'retry;
+                    }
+
+                    return Vec::new();
+                }
+
+                depth += matched;
+                let child = read.children.get(prefix[depth], &guard);
+
+                if !read.validate() {
+                    continue 'retry;
+                }
+
+                match unsafe { child.as_ref() } {
+                    Some(Node::Inner(child_lock)) => {
+                        lock = child_lock;
+                        depth += 1;
+                    }
+                    Some(Node::Leaf(leaf)) if leaf.bytes.starts_with(prefix) => {
+                        nodes.push(child);
+                        break;
+                    }
+                    _ => return Vec::new(),
+                }
+            }
+
+            // traverse the subtree in order, after the key without bytes which is before any other key
+            let mut result = Vec::new();
+
+            if prefix.is_empty() {
+                if let Some(Node::Leaf(leaf)) =
+                    unsafe { self.empty.load(Ordering::Acquire, &guard).as_ref() }
+                {
+                    result.push((leaf.key.clone(), leaf.value.clone()));
+                }
+            }
+
+            while let Some(node) = nodes.pop() {
+                match unsafe { node.deref() } {
+                    // the leaf is not destroyed until the guard is dropped, and its key and value are never changed
+                    Node::Leaf(leaf) => result.push((leaf.key.clone(), leaf.value.clone())),
+                    Node::Inner(lock) => {
+                        // read only this node again if it is changed, not to restart the whole scan
+                        let entries = loop {
+                            let read = ManuallyDrop::new(unsafe { lock.read_lock() });
+                            let entries = read.children.entries(&guard);
+
+                            if read.validate() {
+                                break entries;
+                            }
+                        };
+
+                        nodes.extend(entries.into_iter().rev().map(|(_, child)| child));
+                    }
+                }
+            }
+
+            return result;
+        }
+    }
+}
+
+impl<K, V> ConcurrentMap<K, V> for SeqLockART<K, V>
+where
+    K: Eq + ArtKey,
+    V: Clone,
+{
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
+        let guard = pin();
+
+        let bytes = key_bytes(&key);
+        let leaf = Owned::new(Node::Leaf(Leaf { key, bytes, value })).into_shared(&guard);
+        let new = unsafe { leaf.deref() }.as_leaf().unwrap();
+        let bytes = &new.bytes;
+
+        if bytes.is_empty() {
+            return match self.empty.compare_exchange(
+                Shared::null(),
+                leaf,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard,
+            ) {
+                Ok(_) => Ok(()),
+                Err(e) => {
+                    let old = unsafe { e.current.deref() }.as_leaf().unwrap();
+                    assert!(
+                        old.key == new.key,
+                        "The bytes of the keys should be different."
+                    );
+
+                    Err(Self::into_entry(*unsafe { e.new.into_owned() }.into_box()))
+                }
+            };
+        }
+
+        loop {
+            let cursor = some_or!(self.search(bytes, &guard), continue);
+
+            if let Some((_, child)) = Self::found(&cursor, bytes) {
+                let old = unsafe { child.deref() }.as_leaf().unwrap();
+
+                assert!(
+                    old.key == new.key,
+                    "The bytes of the keys should be different."
+                );
+
+                return Err(Self::into_entry(*unsafe { leaf.into_owned() }.into_box()));
+            }
+
+            if self.link_leaf(cursor, leaf, &guard) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<K, V, Q> ConcurrentLookup<K, V, Q> for SeqLockART<K, V>
+where
+    K: Eq + ArtKey + Borrow<Q>,
+    V: Clone,
+    Q: ?Sized + Eq + ArtKey,
+{
+    fn lookup<F, R>(&self, key: &Q, f: F) -> R
+    where
+        F: FnOnce(Option<&V>) -> R,
+    {
+        let guard = pin();
+        let bytes = key_bytes(key);
+
+        loop {
+            let leaf = if bytes.is_empty() {
+                unsafe { self.empty.load(Ordering::Acquire, &guard).as_ref() }
+            } else {
+                let cursor = some_or!(self.search(&bytes, &guard), continue);
+
+                match cursor.position {
+                    Position::Child(_, child) => unsafe { child.as_ref() },
+                    Position::Mismatch(_) => None,
+                }
+            };
+
+            // the leaf is not destroyed until the guard is dropped even if it is removed after the validation
+            let leaf = leaf
+                .and_then(Node::as_leaf)
+                .filter(|leaf| leaf.bytes == bytes && leaf.key.borrow() == key);
+
+            return f(leaf.map(|leaf| &leaf.value));
+        }
+    }
+
+    fn get(&self, key: &Q) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.lookup(key, |value| value.cloned())
+    }
+
+    fn remove(&self, key: &Q) -> Result<V, ()> {
+        let guard = pin();
+        let bytes = key_bytes(key);
+
+        if bytes.is_empty() {
+            loop {
+                let empty = self.empty.load(Ordering::Acquire, &guard);
+                let leaf = some_or!(unsafe { empty.as_ref() }, return Err(()))
+                    .as_leaf()
+                    .unwrap();
+
+                if leaf.key.borrow() != key {
+                    return Err(());
+                }
+
+                if self
+                    .empty
+                    .compare_exchange(
+                        empty,
+                        Shared::null(),
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                        &guard,
+                    )
+                    .is_ok()
+                {
+                    let value = leaf.value.clone();
+                    unsafe { guard.defer_destroy(empty) };
+
+                    return Ok(value);
+                }
+            }
+        }
+
+        loop {
+            let cursor = some_or!(self.search(&bytes, &guard), continue);
+
+            let (byte, child) = some_or!(Self::found(&cursor, &bytes), return Err(()));
+            let leaf = unsafe { child.deref() }.as_leaf().unwrap();
+
+            if leaf.key.borrow() != key {
+                return Err(());
+            }
+
+            if !self.unlink_leaf(cursor, byte, &guard) {
+                continue;
+            }
+
+            // the readers may still read the value of the unlinked leaf, so it is dropped with the leaf
+            let value = leaf.value.clone();
+            unsafe { guard.defer_destroy(child) };
+
+            return Ok(value);
+        }
+    }
+
+    fn upsert(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+    {
+        // the function may be called again if the node is changed, so it clones the value
+        self.compute(key, |_| Some(value.clone()))
+    }
+
+    fn compute<F>(&self, key: &Q, mut f: F) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        let guard = pin();
+        let bytes = key_bytes(key);
+
+        let new_leaf = |value| {
+            Node::Leaf(Leaf {
+                key: key.to_owned(),
+                bytes: bytes.clone(),
+                value,
+            })
+        };
+
+        if bytes.is_empty() {
+            loop {
+                let empty = self.empty.load(Ordering::Acquire, &guard);
+                let old = unsafe { empty.as_ref() }.map(|old| old.as_leaf().unwrap());
+
+                if let Some(old) = old {
+                    assert!(
+                        old.key.borrow() == key,
+                        "The bytes of the keys should be different."
+                    );
+                }
+
+                let new = match f(old.map(|old| &old.value)) {
+                    Some(value) => Owned::new(new_leaf(value)).into_shared(&guard),
+                    None if old.is_none() => return None,
+                    None => Shared::null(),
+                };
+
+                if self
+                    .empty
+                    .compare_exchange(empty, new, Ordering::AcqRel, Ordering::Acquire, &guard)
+                    .is_ok()
+                {
+                    let value = old.map(|old| old.value.clone());
+
+                    if !empty.is_null() {
+                        unsafe { guard.defer_destroy(empty) };
+                    }
+
+                    return value;
+                }
+
+                if !new.is_null() {
+                    drop(unsafe { new.into_owned() });
+                }
+            }
+        }
+
+        loop {
+            let cursor = some_or!(self.search(&bytes, &guard), continue);
+
+            let (byte, child) = match Self::found(&cursor, &bytes) {
+                Some(found) => found,
+                None => {
+                    let leaf = Owned::new(new_leaf(f(None)?)).into_shared(&guard);
+
+                    if self.link_leaf(cursor, leaf, &guard) {
+                        return None;
+                    }
+
+                    drop(unsafe { leaf.into_owned() });
+                    continue;
+                }
+            };
+
+            let old = unsafe { child.deref() }.as_leaf().unwrap();
+            assert!(
+                old.key.borrow() == key,
+                "The bytes of the keys should be different."
+            );
+
+            // compute without any lock, and compute again if the node is changed until locking it
+            match f(Some(&old.value)) {
+                Some(value) => {
+                    let leaf = Owned::new(new_leaf(value));
+                    let node = some_or!(upgrade(cursor.read), continue);
+                    node.children.replace(byte, leaf.into_shared(&guard));
+                }
+                None => {
+                    if !self.unlink_leaf(cursor, byte, &guard) {
+                        continue;
+                    }
+                }
+            }
+
+            // the readers may still read the value of the old leaf, so it is dropped with the leaf
+            let value = old.value.clone();
+            unsafe { guard.defer_destroy(child) };
+
+            return Some(value);
+        }
+    }
+}
+
+impl<K, V> Drop for SeqLockART<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = unprotected();
+            let mut nodes = self.root.write_lock().children.entries(guard);
+            let empty = self.empty.load(Ordering::Relaxed, guard);
+
+            if !empty.is_null() {
+                drop(empty.into_owned());
+            }
+
+            while let Some((_, node)) = nodes.pop() {
+                let mut node = node.into_owned();
+
+                if let Node::Inner(lock) = &mut *node {
+                    nodes.extend(lock.write_lock().children.entries(guard));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use std::ops::RangeBounds;
+
+use super::{is_after_start, is_before_end, AVLTree, Node};
+
+/// in-order iterator, whose stack has the nodes not visited yet whose left subtrees are already visited
+pub struct Iter<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left(&mut self, mut node: Option<&'a Node<K, V>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+
+        Some((node.key(), node.value()))
+    }
+}
+
+/// the split borrows of the node not visited yet: (key, value, right subtree)
+type Unvisited<'a, K, V> = (&'a K, &'a mut V, Option<&'a mut Node<K, V>>);
+
+pub struct IterMut<'a, K, V> {
+    stack: Vec<Unvisited<'a, K, V>>,
+}
+
+impl<'a, K, V> IterMut<'a, K, V> {
+    fn push_left(&mut self, mut node: Option<&'a mut Node<K, V>>) {
+        while let Some(current) = node {
+            let Node {
+                key,
+                value,
+                left,
+                right,
+                ..
+            } = current;
+
+            let (key, value) = unsafe { (&*key.as_ptr(), &mut *value.as_mut_ptr()) };
+            self.stack.push((key, value, right.as_deref_mut()));
+            node = left.as_deref_mut();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value, right) = self.stack.pop()?;
+        self.push_left(right);
+
+        Some((key, value))
+    }
+}
+
+pub struct IntoIter<K, V> {
+    stack: Vec<Box<Node<K, V>>>,
+}
+
+impl<K, V> IntoIter<K, V> {
+    fn push_left(&mut self, mut node: Option<Box<Node<K, V>>>) {
+        while let Some(mut current) = node {
+            node = current.left.take();
+            self.stack.push(current);
+        }
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = self.stack.pop()?;
+        self.push_left(node.right.take());
+
+        Some(node.into_entry())
+    }
+}
+
+pub struct Keys<'a, K, V> {
+    iter: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(key, _)| key)
+    }
+}
+
+pub struct Values<'a, K, V> {
+    iter: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, value)| value)
+    }
+}
+
+/// in-order iterator on the range, which starts from the lower bound found by descending
+pub struct Range<'a, K, V, R> {
+    iter: Iter<'a, K, V>,
+    range: R,
+}
+
+impl<'a, K: Ord, V, R: RangeBounds<K>> Iterator for Range<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.iter.next()?;
+
+        if !is_before_end(key, self.range.end_bound()) {
+            self.iter.stack.clear();
+            return None;
+        }
+
+        Some((key, value))
+    }
+}
+
+impl<K, V> AVLTree<K, V> {
+    fn real_root(&self) -> Option<&Node<K, V>> {
+        unsafe { self.root.as_ref().right.as_deref() }
+    }
+
+    /// iterate (key, value) in the ascending order of the key
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left(self.real_root());
+
+        iter
+    }
+
+    /// iterate (key, mutable value) in the ascending order of the key
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let mut iter = IterMut { stack: Vec::new() };
+        iter.push_left(unsafe { self.root.as_mut().right.as_deref_mut() });
+
+        iter
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { iter: self.iter() }
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { iter: self.iter() }
+    }
+
+    /// iterate (key, value) whose key is in the range in the ascending order of the key
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, R>
+    where
+        K: Ord,
+    {
+        let mut iter = Iter { stack: Vec::new() };
+        let mut node = self.real_root();
+
+        // push the nodes satisfying the lower bound, whose left subtrees may have more satisfying nodes
+        while let Some(current) = node {
+            if is_after_start(current.key(), range.start_bound()) {
+                iter.stack.push(current);
+                node = current.left.as_deref();
+            } else {
+                node = current.right.as_deref();
+            }
+        }
+
+        Range { iter, range }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a AVLTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut AVLTree<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> IntoIterator for AVLTree<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let mut iter = IntoIter { stack: Vec::new() };
+        iter.push_left(unsafe { self.root.as_mut().right.take() });
+
+        iter
+    }
+}
@@ -1,28 +1,55 @@
+mod iter;
+mod persistent;
 mod rwlock;
 mod seqlock;
 
-pub use rwlock::RwLockAVLTree;
-pub use seqlock::SeqLockAVLTree;
+pub use iter::{IntoIter, Iter, IterMut, Keys, Range, Values};
+pub use persistent::{Iter as PersistentIter, PersistentAVLTree, RcuAVLTree};
+pub use rwlock::{RwLockAVLTree, RwLockRange};
+pub use seqlock::{SeqLockAVLTree, SeqLockRange};
 
-use crate::map::SequentialMap;
+use crate::map::{OrderedMap, SequentialLookup, SequentialMap};
 use std::{
-    cmp::max,
+    borrow::Borrow,
+    cmp::{max, Ordering},
     fmt::Debug,
-    mem,
-    ops::DerefMut,
+    mem::{self, ManuallyDrop, MaybeUninit},
+    ops::{Bound, DerefMut, RangeBounds},
     ptr::{drop_in_place, NonNull},
     usize,
 };
 
+/// whether the key is on the range by the start bound
+fn is_after_start<K: ?Sized + Ord>(key: &K, start: Bound<&K>) -> bool {
+    match start {
+        Bound::Included(start) => key >= start,
+        Bound::Excluded(start) => key > start,
+        Bound::Unbounded => true,
+    }
+}
+
+/// whether the key is on the range by the end bound
+fn is_before_end<K: ?Sized + Ord>(key: &K, end: Bound<&K>) -> bool {
+    match end {
+        Bound::Included(end) => key <= end,
+        Bound::Excluded(end) => key < end,
+        Bound::Unbounded => true,
+    }
+}
+
 pub struct AVLTree<K, V> {
-    root: NonNull<Node<K, V>>, // root node is dummy for simplicity
+    root: NonNull<Node<K, V>>, // root node is the sentinel for simplicity, and the tree is on its right
 }
 
+// The tree owns its nodes, and lookups never mutate them.
+unsafe impl<K: Send, V: Send> Send for AVLTree<K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for AVLTree<K, V> {}
+
 impl<K: Debug, V: Debug> Debug for AVLTree<K, V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         unsafe {
             f.debug_struct("AVLTree")
-                .field("root", self.root.as_ref())
+                .field("root", &self.root.as_ref().right)
                 .finish()
         }
     }
@@ -35,32 +62,81 @@ enum Dir {
     Right,
 }
 
-#[derive(Debug)]
+/// The key and value are always initialized except the sentinel root, which is never dropped as a node.
+///
+/// The size is the number of nodes on the subtree for the order statistics, and it is renewed with the height.
 struct Node<K, V> {
-    key: K,
-    value: V,
+    key: MaybeUninit<K>,
+    value: MaybeUninit<V>,
     height: isize,
+    size: usize,
     left: Option<Box<Node<K, V>>>,
     right: Option<Box<Node<K, V>>>,
 }
 
-impl<K: Default, V: Default> Default for Node<K, V> {
-    fn default() -> Self {
-        Self::new(K::default(), V::default())
+impl<K: Debug, V: Debug> Debug for Node<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Node")
+            .field("key", self.key())
+            .field("value", self.value())
+            .field("height", &self.height)
+            .field("size", &self.size)
+            .field("left", &self.left)
+            .field("right", &self.right)
+            .finish()
+    }
+}
+
+impl<K, V> Drop for Node<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            self.key.as_mut_ptr().drop_in_place();
+            self.value.as_mut_ptr().drop_in_place();
+        }
     }
 }
 
 impl<K, V> Node<K, V> {
     fn new(key: K, value: V) -> Node<K, V> {
         Node {
-            key,
-            value,
+            key: MaybeUninit::new(key),
+            value: MaybeUninit::new(value),
+            height: 1,
+            size: 1,
+            left: None,
+            right: None,
+        }
+    }
+
+    /// the root without key and value, whose right child is the real root
+    fn sentinel() -> Node<K, V> {
+        Node {
+            key: MaybeUninit::uninit(),
+            value: MaybeUninit::uninit(),
             height: 1,
+            size: 0,
             left: None,
             right: None,
         }
     }
 
+    fn key(&self) -> &K {
+        unsafe { &*self.key.as_ptr() }
+    }
+
+    fn value(&self) -> &V {
+        unsafe { &*self.value.as_ptr() }
+    }
+
+    /// move out the key and value of the node
+    ///
+    /// The children should be taken before, otherwise they are leaked.
+    fn into_entry(self) -> (K, V) {
+        let node = ManuallyDrop::new(self);
+
+        unsafe { (node.key.as_ptr().read(), node.value.as_ptr().read()) }
+    }
+
     /// get the mutable reference of the child of the node by dir
     fn child_mut(&mut self, dir: Dir) -> &mut Option<Box<Node<K, V>>> {
         match dir {
@@ -87,6 +163,16 @@ impl<K, V> Node<K, V> {
         self.height = max(left_height, right_height) + 1;
     }
 
+    /// renew the size of the node from the childs
+    fn renew_size(&mut self) {
+        self.size = Self::size_of(&self.left) + Self::size_of(&self.right) + 1;
+    }
+
+    /// get the size of the subtree, which is 0 if empty
+    fn size_of(node: &Option<Box<Node<K, V>>>) -> usize {
+        node.as_ref().map_or(0, |node| node.size)
+    }
+
     /// get difference of the heights from the childs
     fn get_factor(&self) -> isize {
         let left_height = if let Some(node) = &self.left {
@@ -107,10 +193,13 @@ impl<K, V> Node<K, V> {
     /// rotate left the node
     ///
     /// Change Parent-Right Child to Left Child-Parent, then return new parent(old right child).
+    /// Since the size does not depend on the balance, it is renewed here unlike the height.
     fn rotate_left(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
         let mut new_parent = node.right.take().unwrap();
-        let _ = mem::replace(&mut node.right, new_parent.left);
+        let _ = mem::replace(&mut node.right, new_parent.left.take());
+        node.renew_size();
         new_parent.left = Some(node);
+        new_parent.renew_size();
 
         new_parent
     }
@@ -118,10 +207,13 @@ impl<K, V> Node<K, V> {
     /// rotate right the node
     ///
     /// Change Left Child-Parent to Parent-Right Child, then return new parent(old left child).
+    /// Since the size does not depend on the balance, it is renewed here unlike the height.
     fn rotate_right(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
         let mut new_parent = node.left.take().unwrap();
-        let _ = mem::replace(&mut node.left, new_parent.right);
+        let _ = mem::replace(&mut node.left, new_parent.right.take());
+        node.renew_size();
         new_parent.right = Some(node);
+        new_parent.renew_size();
 
         new_parent
     }
@@ -138,11 +230,7 @@ struct Cursor<K, V> {
     dir: Dir,
 }
 
-impl<'c, K, V> Cursor<K, V>
-where
-    K: Default + Ord + Clone,
-    V: Default,
-{
+impl<'c, K: Ord, V> Cursor<K, V> {
     fn new(tree: &AVLTree<K, V>) -> Cursor<K, V> {
         let cursor = Cursor {
             ancestors: Vec::with_capacity(tree.get_height() + 1),
@@ -215,6 +303,8 @@ where
     }
 
     /// rebalance the nodes by the rule of AVL using the cursor's ancestors
+    ///
+    /// The sizes of all nodes on the path are also renewed, since one node is inserted or removed below them.
     fn rebalance(&mut self) {
         let parent_rotate_left = |mut node: Box<Node<K, V>>| -> Box<Node<K, V>> {
             let child_factor = node.right.as_ref().unwrap().get_factor();
@@ -261,7 +351,11 @@ where
                     new_target.renew_height();
                     *target = Some(new_target);
                 }
-                -1..=1 => target.as_mut().unwrap().renew_height(),
+                -1..=1 => {
+                    let target = target.as_mut().unwrap();
+                    target.renew_height();
+                    target.renew_size();
+                }
                 2 => {
                     let mut new_target = parent_rotate_right(target.take().unwrap());
                     new_target.right.as_mut().unwrap().renew_height();
@@ -274,17 +368,17 @@ where
     }
 }
 
-impl<K, V> AVLTree<K, V>
-where
-    K: Default + Ord + Clone,
-    V: Default,
-{
+impl<K: Ord, V> AVLTree<K, V> {
     /// find the last state of the cursor by the key
     ///
     /// If there exists the key on the tree, the cursor's current is the node and the dir is Eq.
     /// If there does not exist the key on the tree, the cursor's current is leaf node and the dir is
     /// Left if the key is greater than the key of the node, or Right if the key is less than.
-    fn find(&self, key: &K) -> Cursor<K, V> {
+    fn find<Q>(&self, key: &Q) -> Cursor<K, V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
         let mut cursor = Cursor::new(self);
 
         loop {
@@ -295,10 +389,12 @@ where
             cursor.move_next();
 
             unsafe {
-                if *key == cursor.current.as_ref().key {
+                let current_key = cursor.current.as_ref().key().borrow();
+
+                if key == current_key {
                     cursor.dir = Dir::Eq;
                     return cursor;
-                } else if *key < cursor.current.as_ref().key {
+                } else if key < current_key {
                     cursor.dir = Dir::Left;
                 } else {
                     // *key > next.key
@@ -316,15 +412,97 @@ where
             0
         }
     }
+
+    /// the number of keys on the tree
+    pub fn len(&self) -> usize {
+        Node::size_of(unsafe { &self.root.as_ref().right })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// the number of keys less than the key, or less than or equal to the key if inclusive
+    fn count_less<Q>(&self, key: &Q, inclusive: bool) -> usize
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut node = unsafe { self.root.as_ref().right.as_ref() };
+        let mut count = 0;
+
+        while let Some(current) = node {
+            let current_key = current.key().borrow();
+
+            if key < current_key || (key == current_key && !inclusive) {
+                node = current.left.as_ref();
+            } else {
+                count += Node::size_of(&current.left) + 1;
+                node = current.right.as_ref();
+            }
+        }
+
+        count
+    }
+
+    /// get the rank of the key, which is the number of keys less than the key
+    ///
+    /// If the key exists, it is the index of the key in the ascending order.
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.count_less(key, false)
+    }
+
+    /// get (key, value) whose rank is the index, which is the index-th smallest key from 0
+    pub fn select(&self, index: usize) -> Option<(&K, &V)> {
+        let mut node = unsafe { self.root.as_ref().right.as_ref() };
+        let mut index = index;
+
+        while let Some(current) = node {
+            let left_size = Node::size_of(&current.left);
+
+            match index.cmp(&left_size) {
+                Ordering::Less => node = current.left.as_ref(),
+                Ordering::Equal => return Some((current.key(), current.value())),
+                Ordering::Greater => {
+                    index -= left_size + 1;
+                    node = current.right.as_ref();
+                }
+            }
+        }
+
+        None
+    }
+
+    /// get the number of keys in the range
+    pub fn count_range<Q, R>(&self, range: R) -> usize
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self.count_less(start, false),
+            Bound::Excluded(start) => self.count_less(start, true),
+            Bound::Unbounded => 0,
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.count_less(end, true),
+            Bound::Excluded(end) => self.count_less(end, false),
+            Bound::Unbounded => self.len(),
+        };
+
+        end.saturating_sub(start)
+    }
 }
 
-impl<K, V> SequentialMap<K, V> for AVLTree<K, V>
-where
-    K: Default + Ord + Clone,
-    V: Default,
-{
+impl<K: Ord, V> SequentialMap<K, V> for AVLTree<K, V> {
     fn new() -> Self {
-        let root = Box::new(Node::default());
+        let root = Box::new(Node::sentinel());
 
         let tree = AVLTree {
             root: Box::leak(root).into(),
@@ -333,15 +511,15 @@ where
         tree
     }
 
-    fn insert(&mut self, key: &K, value: V) -> Result<(), V> {
-        let node = Box::new(Node::new(key.clone(), value));
-
-        let mut cursor = self.find(key);
+    fn insert(&mut self, key: K, value: V) -> Result<(), (K, V)> {
+        let mut cursor = self.find(&key);
 
         if cursor.dir == Dir::Eq {
-            return Err(node.value);
+            return Err((key, value));
         }
 
+        let node = Box::new(Node::new(key, value));
+
         *(cursor.next_node_mut()) = Some(node);
 
         unsafe {
@@ -351,20 +529,37 @@ where
 
         Ok(())
     }
+}
 
-    fn lookup(&self, key: &K) -> Option<&V> {
+impl<K: Ord, V, Q> SequentialLookup<K, V, Q> for AVLTree<K, V>
+where
+    K: Borrow<Q>,
+    Q: ?Sized + Ord,
+{
+    fn lookup(&self, key: &Q) -> Option<&V> {
         let cursor = self.find(key);
 
         unsafe {
             if cursor.dir == Dir::Eq {
-                return Some(&cursor.current.as_ref().value);
+                return Some(cursor.current.as_ref().value());
             } else {
                 return None;
             }
         }
     }
 
-    fn remove(&mut self, key: &K) -> Result<V, ()> {
+    fn remove(&mut self, key: &Q) -> Result<V, ()> {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+}
+
+impl<K: Ord, V> AVLTree<K, V> {
+    /// remove (key, value) from the tree with the key
+    fn remove_entry<Q>(&mut self, key: &Q) -> Result<(K, V), ()>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
         let mut cursor = self.find(key);
 
         if cursor.dir != Dir::Eq {
@@ -389,35 +584,125 @@ where
             mem::swap(&mut child.key, &mut swap_node.key);
             mem::swap(&mut child.value, &mut swap_node.value);
 
-            let swap_node = swap_node_ptr.take().unwrap();
+            let mut swap_node = swap_node_ptr.take().unwrap();
             if swap_node.left.is_some() {
-                *swap_node_ptr = swap_node.left;
+                *swap_node_ptr = swap_node.left.take();
             }
 
             cursor.rebalance();
 
-            return Ok(swap_node.value);
+            return Ok(swap_node.into_entry());
         }
 
         let (mut parent, dir) = cursor.ancestors.pop().unwrap();
         let child = unsafe { parent.as_mut().child_mut(dir) };
-        let node = child.take().unwrap();
+        let mut node = child.take().unwrap();
 
         if left {
-            *child = node.left;
+            *child = node.left.take();
         } else if right {
-            *child = node.right;
+            *child = node.right.take();
         }
 
         cursor.rebalance();
-        Ok(node.value)
+        Ok(node.into_entry())
+    }
+
+    /// get the node with the least key if dir is Left, or the greatest key if Right
+    fn end_node(&self, dir: Dir) -> Option<&Node<K, V>> {
+        let mut node = unsafe { self.root.as_ref().right.as_deref()? };
+
+        while let Some(next) = match dir {
+            Dir::Left => node.left.as_deref(),
+            Dir::Right => node.right.as_deref(),
+            Dir::Eq => unreachable!(),
+        } {
+            node = next;
+        }
+
+        Some(node)
+    }
+
+    /// remove the node with the least key if dir is Left, or the greatest key if Right
+    fn pop_end(&mut self, dir: Dir) -> Option<(K, V)> {
+        let key: *const K = self.end_node(dir)?.key();
+
+        // The end node has no child on the dir, so removing it never swaps its key with another node.
+        self.remove_entry(unsafe { &*key }).ok()
+    }
+}
+
+impl<K: Ord, V> OrderedMap<K, V> for AVLTree<K, V> {
+    fn first(&self) -> Option<(&K, &V)> {
+        self.end_node(Dir::Left)
+            .map(|node| (node.key(), node.value()))
+    }
+
+    fn last(&self) -> Option<(&K, &V)> {
+        self.end_node(Dir::Right)
+            .map(|node| (node.key(), node.value()))
+    }
+
+    fn pop_first(&mut self) -> Option<(K, V)> {
+        self.pop_end(Dir::Left)
+    }
+
+    fn pop_last(&mut self) -> Option<(K, V)> {
+        self.pop_end(Dir::Right)
+    }
+
+    fn floor<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut node = unsafe { self.root.as_ref().right.as_deref() };
+        let mut floor = None;
+
+        while let Some(current) = node {
+            match key.cmp(current.key().borrow()) {
+                Ordering::Less => node = current.left.as_deref(),
+                Ordering::Equal => return Some((current.key(), current.value())),
+                Ordering::Greater => {
+                    floor = Some(current);
+                    node = current.right.as_deref();
+                }
+            }
+        }
+
+        floor.map(|node| (node.key(), node.value()))
+    }
+
+    fn ceiling<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut node = unsafe { self.root.as_ref().right.as_deref() };
+        let mut ceiling = None;
+
+        while let Some(current) = node {
+            match key.cmp(current.key().borrow()) {
+                Ordering::Less => {
+                    ceiling = Some(current);
+                    node = current.left.as_deref();
+                }
+                Ordering::Equal => return Some((current.key(), current.value())),
+                Ordering::Greater => node = current.right.as_deref(),
+            }
+        }
+
+        ceiling.map(|node| (node.key(), node.value()))
     }
 }
 
 impl<K, V> Drop for AVLTree<K, V> {
     fn drop(&mut self) {
         // since the struct had 'pointer' instead of 'ownership' of the root,
-        // manually drop the root. Then, the childs are dropped recursively.
-        unsafe { drop_in_place(self.root.as_mut()) };
+        // manually drop the childs of the root recursively. Then, deallocate the sentinel root without dropping it.
+        unsafe {
+            let mut root = Box::from_raw(self.root.as_ptr() as *mut ManuallyDrop<Node<K, V>>);
+            drop_in_place(&mut root.right);
+        }
     }
 }
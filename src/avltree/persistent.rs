@@ -0,0 +1,487 @@
+use std::{
+    borrow::Borrow,
+    cmp::{max, Ordering},
+    mem,
+    sync::{atomic, Arc},
+};
+
+use crossbeam_epoch::{pin, unprotected, Atomic, Owned};
+use parking_lot::Mutex;
+
+use crate::map::{
+    compute_on, get_or_insert_on, ConcurrentLookup, ConcurrentMap, SequentialLookup, SequentialMap,
+};
+
+type Link<K, V> = Option<Arc<Node<K, V>>>;
+
+#[derive(Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    height: isize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(key: K, value: V) -> Self {
+        Self {
+            key,
+            value,
+            height: 1,
+            left: None,
+            right: None,
+        }
+    }
+
+    /// get the height of the subtree, which is 0 if empty
+    fn height_of(node: &Link<K, V>) -> isize {
+        node.as_ref().map_or(0, |node| node.height)
+    }
+
+    /// renew the height of the node from the childs
+    fn renew_height(&mut self) {
+        self.height = max(Self::height_of(&self.left), Self::height_of(&self.right)) + 1;
+    }
+
+    /// get difference of the heights from the childs
+    fn get_factor(&self) -> isize {
+        Self::height_of(&self.left) - Self::height_of(&self.right)
+    }
+}
+
+impl<K: Clone, V: Clone> Node<K, V> {
+    /// get the mutable reference of the node, which copies it if it is shared by other versions
+    fn make_mut(link: &mut Link<K, V>) -> &mut Self {
+        Arc::make_mut(link.as_mut().unwrap())
+    }
+
+    /// take the node out of the Arc, which copies it if it is shared by other versions
+    fn unwrap(node: Arc<Self>) -> Self {
+        Arc::try_unwrap(node).unwrap_or_else(|node| (*node).clone())
+    }
+
+    /// rotate left the node
+    ///
+    /// Change Parent-Right Child to Left Child-Parent on the link.
+    fn rotate_left(link: &mut Link<K, V>) {
+        let mut node = link.take().unwrap();
+        let node_mut = Arc::make_mut(&mut node);
+        let mut new_parent = node_mut.right.take().unwrap();
+        let new_parent_mut = Arc::make_mut(&mut new_parent);
+
+        node_mut.right = new_parent_mut.left.take();
+        node_mut.renew_height();
+        new_parent_mut.left = Some(node);
+        new_parent_mut.renew_height();
+
+        *link = Some(new_parent);
+    }
+
+    /// rotate right the node
+    ///
+    /// Change Left Child-Parent to Parent-Right Child on the link.
+    fn rotate_right(link: &mut Link<K, V>) {
+        let mut node = link.take().unwrap();
+        let node_mut = Arc::make_mut(&mut node);
+        let mut new_parent = node_mut.left.take().unwrap();
+        let new_parent_mut = Arc::make_mut(&mut new_parent);
+
+        node_mut.left = new_parent_mut.right.take();
+        node_mut.renew_height();
+        new_parent_mut.right = Some(node);
+        new_parent_mut.renew_height();
+
+        *link = Some(new_parent);
+    }
+
+    /// renew the height of the node on the link, then rebalance it by the rule of AVL
+    fn rebalance(link: &mut Link<K, V>) {
+        let node = Self::make_mut(link);
+        node.renew_height();
+
+        let factor = node.get_factor();
+
+        if factor > 1 {
+            if node.left.as_ref().unwrap().get_factor() < 0 {
+                Self::rotate_left(&mut node.left);
+            }
+
+            Self::rotate_right(link);
+        } else if factor < -1 {
+            if node.right.as_ref().unwrap().get_factor() > 0 {
+                Self::rotate_right(&mut node.right);
+            }
+
+            Self::rotate_left(link);
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Node<K, V> {
+    /// insert the entry below the link, where the key should not be on the subtree
+    fn insert(link: &mut Link<K, V>, key: K, value: V) {
+        if link.is_none() {
+            *link = Some(Arc::new(Self::new(key, value)));
+            return;
+        }
+
+        let node = Self::make_mut(link);
+
+        if key < node.key {
+            Self::insert(&mut node.left, key, value);
+        } else {
+            Self::insert(&mut node.right, key, value);
+        }
+
+        Self::rebalance(link);
+    }
+
+    /// remove the entry of the key below the link, where the key should be on the subtree
+    fn remove<Q>(link: &mut Link<K, V>, key: &Q) -> (K, V)
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let node = Self::make_mut(link);
+
+        let entry = match key.cmp(node.key.borrow()) {
+            Ordering::Less => Self::remove(&mut node.left, key),
+            Ordering::Greater => Self::remove(&mut node.right, key),
+            Ordering::Equal => {
+                if node.left.is_none() || node.right.is_none() {
+                    let child = node.left.take().or_else(|| node.right.take());
+                    let node = Self::unwrap(mem::replace(link, child).unwrap());
+
+                    return (node.key, node.value);
+                }
+
+                // replace the entry by the one of the least key on the right subtree
+                let (key, value) = Self::remove_least(&mut node.right);
+
+                (
+                    mem::replace(&mut node.key, key),
+                    mem::replace(&mut node.value, value),
+                )
+            }
+        };
+
+        Self::rebalance(link);
+        entry
+    }
+
+    /// remove the entry of the least key below the link, where the subtree should not be empty
+    fn remove_least(link: &mut Link<K, V>) -> (K, V) {
+        let node = Self::make_mut(link);
+
+        if node.left.is_none() {
+            let right = node.right.take();
+            let node = Self::unwrap(mem::replace(link, right).unwrap());
+
+            return (node.key, node.value);
+        }
+
+        let entry = Self::remove_least(&mut node.left);
+        Self::rebalance(link);
+        entry
+    }
+}
+
+/// Persistent AVL tree which shares the nodes between the versions
+///
+/// Cloning the tree is O(1), and updating the tree copies only the nodes on the path, which is O(log n).
+/// The other versions never see the update, so any version is a snapshot.
+pub struct PersistentAVLTree<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+impl<K, V> Clone for PersistentAVLTree<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K, V> Default for PersistentAVLTree<K, V> {
+    fn default() -> Self {
+        Self { root: None, len: 0 }
+    }
+}
+
+impl<K, V> PersistentAVLTree<K, V> {
+    /// the number of keys on the tree
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get_height(&self) -> usize {
+        Node::height_of(&self.root) as usize
+    }
+
+    /// whether both trees are the same version, so no update is between them
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.root, &other.root) {
+            (Some(root), Some(other_root)) => Arc::ptr_eq(root, other_root),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    fn find<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut current = self.root.as_ref();
+
+        while let Some(node) = current {
+            current = match key.cmp(node.key.borrow()) {
+                Ordering::Less => node.left.as_ref(),
+                Ordering::Greater => node.right.as_ref(),
+                Ordering::Equal => return Some(&node.value),
+            };
+        }
+
+        None
+    }
+
+    /// iterate all entries in ascending order of the keys
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left(&self.root);
+
+        iter
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> PersistentAVLTree<K, V> {
+    /// get the new version with the entry, or the entry back if the key already exists
+    pub fn inserted(&self, key: K, value: V) -> Result<Self, (K, V)> {
+        let mut tree = self.clone();
+        tree.insert(key, value)?;
+
+        Ok(tree)
+    }
+
+    /// get the new version without the entry of the key, or None if the key does not exist
+    pub fn removed<Q>(&self, key: &Q) -> Option<Self>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut tree = self.clone();
+        tree.remove(key).ok()?;
+
+        Some(tree)
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> SequentialMap<K, V> for PersistentAVLTree<K, V> {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Result<(), (K, V)> {
+        // check it first not to copy the path on failure
+        if self.find(&key).is_some() {
+            return Err((key, value));
+        }
+
+        Node::insert(&mut self.root, key, value);
+        self.len += 1;
+
+        Ok(())
+    }
+}
+
+impl<K: Ord + Clone, V: Clone, Q> SequentialLookup<K, V, Q> for PersistentAVLTree<K, V>
+where
+    K: Borrow<Q>,
+    Q: ?Sized + Ord,
+{
+    fn lookup(&self, key: &Q) -> Option<&V> {
+        self.find(key)
+    }
+
+    fn remove(&mut self, key: &Q) -> Result<V, ()> {
+        if self.find(key).is_none() {
+            return Err(());
+        }
+
+        let (_, value) = Node::remove(&mut self.root, key);
+        self.len -= 1;
+
+        Ok(value)
+    }
+}
+
+/// the iterator on the version of PersistentAVLTree
+pub struct Iter<'t, K, V> {
+    stack: Vec<&'t Node<K, V>>, // the nodes whose left subtrees are visited
+}
+
+impl<'t, K, V> Iter<'t, K, V> {
+    fn push_left(&mut self, mut link: &'t Link<K, V>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'t, K, V> Iterator for Iter<'t, K, V> {
+    type Item = (&'t K, &'t V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(&node.right);
+
+        Some((&node.key, &node.value))
+    }
+}
+
+/// Concurrent map which swaps the version of PersistentAVLTree atomically like RCU
+///
+/// The readers only load the current version, so they are wait-free and never blocked by the writers.
+/// The writers are serialized by the lock, and each of them publishes the new version by path copying.
+pub struct RcuAVLTree<K, V> {
+    current: Atomic<PersistentAVLTree<K, V>>,
+    lock: Mutex<()>,
+}
+
+impl<K, V> RcuAVLTree<K, V> {
+    /// the number of keys on the current version
+    pub fn len(&self) -> usize {
+        let guard = pin();
+
+        unsafe { self.current.load(atomic::Ordering::Acquire, &guard).deref() }.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// get the current version in O(1), which is never changed by the later updates
+    pub fn snapshot(&self) -> PersistentAVLTree<K, V> {
+        let guard = pin();
+
+        unsafe { self.current.load(atomic::Ordering::Acquire, &guard).deref() }.clone()
+    }
+
+    /// update the copy of the current version, then publish it if it is changed
+    fn write<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut PersistentAVLTree<K, V>) -> R,
+    {
+        let _lock = self.lock.lock();
+        let guard = pin();
+
+        let current = self.current.load(atomic::Ordering::Acquire, &guard);
+        let current_ref = unsafe { current.deref() };
+
+        let mut tree = current_ref.clone();
+        let result = f(&mut tree);
+
+        if !tree.ptr_eq(current_ref) {
+            self.current
+                .store(Owned::new(tree), atomic::Ordering::Release);
+
+            // the readers may still be on the old version
+            unsafe { guard.defer_destroy(current) };
+        }
+
+        result
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> ConcurrentMap<K, V> for RcuAVLTree<K, V> {
+    fn new() -> Self {
+        Self {
+            current: Atomic::new(PersistentAVLTree::new()),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
+        self.write(|tree| tree.insert(key, value))
+    }
+}
+
+impl<K: Ord + Clone, V: Clone, Q> ConcurrentLookup<K, V, Q> for RcuAVLTree<K, V>
+where
+    K: Borrow<Q>,
+    Q: ?Sized + Ord,
+{
+    fn lookup<F, R>(&self, key: &Q, f: F) -> R
+    where
+        F: FnOnce(Option<&V>) -> R,
+    {
+        let guard = pin();
+        let tree = unsafe { self.current.load(atomic::Ordering::Acquire, &guard).deref() };
+
+        f(tree.lookup(key))
+    }
+
+    fn get(&self, key: &Q) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.lookup(key, |value| value.cloned())
+    }
+
+    fn remove(&self, key: &Q) -> Result<V, ()> {
+        self.write(|tree| tree.remove(key))
+    }
+
+    fn upsert(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+    {
+        let mut value = Some(value);
+
+        // the function is called only once by the writer
+        self.compute(key, |_| value.take())
+    }
+
+    fn compute<F>(&self, key: &Q, f: F) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        self.write(|tree| compute_on(tree, key, f).0)
+    }
+
+    fn get_or_insert_with<F>(&self, key: &Q, f: F) -> V
+    where
+        Q: ToOwned<Owned = K>,
+        V: Clone,
+        F: FnOnce() -> V,
+    {
+        // the value is usually on the map, so try it without the lock first
+        if let Some(value) = self.get(key) {
+            return value;
+        }
+
+        self.write(|tree| get_or_insert_on(tree, key, f).0)
+    }
+}
+
+impl<K, V> Drop for RcuAVLTree<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = unprotected();
+            drop(
+                self.current
+                    .load(atomic::Ordering::Relaxed, guard)
+                    .into_owned(),
+            );
+        }
+    }
+}
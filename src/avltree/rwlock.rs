@@ -7,17 +7,27 @@ use crossbeam_epoch::Shared;
 use crossbeam_utils::sync::ShardedLock;
 use crossbeam_utils::sync::ShardedLockReadGuard;
 use crossbeam_utils::sync::ShardedLockWriteGuard;
+use std::borrow::Borrow;
 use std::cmp::max;
 use std::fmt::Debug;
 use std::mem;
 use std::mem::ManuallyDrop;
+use std::mem::MaybeUninit;
+use std::ops::Bound;
+use std::ops::Deref;
+use std::ops::RangeBounds;
+use std::ops::RangeFull;
+use std::ptr;
 use std::sync::atomic::AtomicIsize;
 use std::sync::atomic::Ordering;
 
-use crate::map::ConcurrentMap;
+use super::{is_after_start, is_before_end};
+use crate::map::{ConcurrentLookup, ConcurrentMap, ConcurrentOrderedMap};
+use crate::util::counter::ShardedCounter;
 
+/// The key is always initialized except the sentinel root, which is never dropped as a node.
 struct Node<K, V> {
-    key: K,
+    key: MaybeUninit<K>,
     height: AtomicIsize,
     inner: ShardedLock<NodeInner<K, V>>,
 }
@@ -25,7 +35,7 @@ struct Node<K, V> {
 impl<K: Debug, V: Debug> Debug for Node<K, V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Node")
-            .field("key", &self.key)
+            .field("key", self.key())
             .field("height", &self.height.load(Ordering::Relaxed))
             .field("inner", &*self.inner.read().unwrap())
             .finish()
@@ -112,16 +122,16 @@ impl<K, V> NodeInner<K, V> {
     }
 }
 
-impl<K: Default, V: Default> Default for Node<K, V> {
-    fn default() -> Self {
-        Self::new(K::default(), V::default())
+impl<K, V> Drop for Node<K, V> {
+    fn drop(&mut self) {
+        unsafe { self.key.as_mut_ptr().drop_in_place() };
     }
 }
 
 impl<K, V> Node<K, V> {
     fn new(key: K, value: V) -> Node<K, V> {
         Node {
-            key,
+            key: MaybeUninit::new(key),
             height: AtomicIsize::new(1),
             inner: ShardedLock::new(NodeInner {
                 value: Some(value),
@@ -131,6 +141,33 @@ impl<K, V> Node<K, V> {
         }
     }
 
+    /// the root without key and value, whose right child is the real root
+    fn sentinel() -> Node<K, V> {
+        Node {
+            key: MaybeUninit::uninit(),
+            height: AtomicIsize::new(1),
+            inner: ShardedLock::new(NodeInner {
+                value: None,
+                left: Atomic::null(),
+                right: Atomic::null(),
+            }),
+        }
+    }
+
+    fn key(&self) -> &K {
+        unsafe { &*self.key.as_ptr() }
+    }
+
+    /// move out the key and value of the node which is not linked on the tree
+    fn into_entry(self) -> (K, V) {
+        let node = ManuallyDrop::new(self);
+
+        unsafe {
+            let inner = ptr::read(&node.inner).into_inner().unwrap();
+            (node.key.as_ptr().read(), inner.value.unwrap())
+        }
+    }
+
     /// rotate left the node
     ///
     /// Change Parent-Right Child to Left Child-Parent, then return new parent(old right child).
@@ -445,21 +482,30 @@ impl<'g, K, V> Cursor<'g, K, V> {
     }
 }
 
+/// the nodes locked by the ordered scan, and the index of the node found on them
+///
+/// All locks are held until the scan is dropped, so the found node is the first one at the moment.
+struct Scan<'g, K, V, G> {
+    nodes: Vec<(Shared<'g, Node<K, V>>, G)>,
+    found: Option<usize>,
+}
+
 pub struct RwLockAVLTree<K, V> {
     root: Atomic<Node<K, V>>,
+    count: ShardedCounter,
 }
 
 impl<K: Debug, V: Debug> Debug for RwLockAVLTree<K, V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         unsafe {
+            let root = self
+                .root
+                .load(Ordering::Acquire, unprotected())
+                .as_ref()
+                .unwrap();
+
             f.debug_struct("RwLockAVLTree")
-                .field(
-                    "root",
-                    self.root
-                        .load(Ordering::Acquire, unprotected())
-                        .as_ref()
-                        .unwrap(),
-                )
+                .field("root", &*root.inner.read().unwrap())
                 .finish()
         }
     }
@@ -471,9 +517,10 @@ impl<K, V> RwLockAVLTree<K, V> {
     /// If there exists the key on the tree, the cursor's current is the node and the dir is Eq.
     /// If there does not exist the key on the tree, the cursor's current is leaf node and the dir is
     /// Left if the key is greater than the key of the node, or Right if the key is less than.
-    fn find<'g>(&self, key: &K, guard: &'g Guard) -> Cursor<'g, K, V>
+    fn find<'g, Q>(&self, key: &Q, guard: &'g Guard) -> Cursor<'g, K, V>
     where
-        K: Ord,
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
     {
         let mut cursor = Cursor::new(self, guard);
 
@@ -483,10 +530,12 @@ impl<K, V> RwLockAVLTree<K, V> {
             }
 
             unsafe {
-                if *key == cursor.current.as_ref().unwrap().key {
+                let current_key = cursor.current.as_ref().unwrap().key().borrow();
+
+                if key == current_key {
                     cursor.dir = Dir::Eq;
                     return cursor;
-                } else if *key < cursor.current.as_ref().unwrap().key {
+                } else if key < current_key {
                     cursor.dir = Dir::Left;
                 } else {
                     // *key > next.key
@@ -496,6 +545,17 @@ impl<K, V> RwLockAVLTree<K, V> {
         }
     }
 
+    /// the number of keys on the tree
+    ///
+    /// It is exact when no operation is running, but approximate while other threads insert or remove.
+    pub fn len(&self) -> usize {
+        self.count.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     /// get the height of the tree
     pub fn get_height(&self) -> usize {
         unsafe {
@@ -519,25 +579,318 @@ impl<K, V> RwLockAVLTree<K, V> {
     }
 }
 
+impl<K: Ord + Clone, V: Clone> RwLockAVLTree<K, V> {
+    /// find (key, value) of the node with the least key on the range by the start bound
+    ///
+    /// The value is None if the node is logically removed.
+    fn weak_ceiling(&self, start: Bound<&K>, guard: &Guard) -> Option<(K, Option<V>)> {
+        let mut cursor = Cursor::new(self, guard);
+        let mut ceiling = None;
+
+        while cursor.move_next(guard).is_ok() {
+            let current = unsafe { cursor.current.as_ref().unwrap() };
+
+            if is_after_start(current.key(), start) {
+                ceiling = Some((current.key().clone(), cursor.inner_guard.value.clone()));
+                cursor.dir = Dir::Left;
+            } else {
+                cursor.dir = Dir::Right;
+            }
+        }
+
+        unsafe { ManuallyDrop::drop(&mut cursor.inner_guard) };
+
+        ceiling
+    }
+
+    /// iterate (key, value) in the ascending order of the key, which is weakly consistent like `RwLockRange`
+    pub fn iter<'g>(&'g self, guard: &'g Guard) -> RwLockRange<'g, K, V, RangeFull> {
+        self.range(.., guard)
+    }
+
+    /// iterate (key, value) whose key is on the range in the ascending order of the key
+    pub fn range<'g, R: RangeBounds<K>>(
+        &'g self,
+        range: R,
+        guard: &'g Guard,
+    ) -> RwLockRange<'g, K, V, R> {
+        RwLockRange {
+            tree: self,
+            guard,
+            start: range.start_bound().cloned(),
+            range,
+            finished: false,
+        }
+    }
+}
+
+/// weakly consistent iterator of (key, value) on the range of RwLockAVLTree
+///
+/// Each step finds the successor of the last key from the root with hand-over-hand read locking,
+/// so no lock is held between the steps, and the rotations do not break the iteration.
+/// The keys are returned in the ascending order without duplicates. The entry which exists during the whole iteration
+/// is always returned, but the entry inserted or removed during the iteration may or may not be returned.
+pub struct RwLockRange<'g, K, V, R> {
+    tree: &'g RwLockAVLTree<K, V>,
+    guard: &'g Guard,
+    start: Bound<K>,
+    range: R,
+    finished: bool,
+}
+
+impl<'g, K, V, R> Iterator for RwLockRange<'g, K, V, R>
+where
+    K: Ord + Clone,
+    V: Clone,
+    R: RangeBounds<K>,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.finished {
+            let start = match &self.start {
+                Bound::Included(key) => Bound::Included(key),
+                Bound::Excluded(key) => Bound::Excluded(key),
+                Bound::Unbounded => Bound::Unbounded,
+            };
+
+            let (key, value) = match self.tree.weak_ceiling(start, self.guard) {
+                Some(entry) if is_before_end(&entry.0, self.range.end_bound()) => entry,
+                _ => {
+                    self.finished = true;
+                    break;
+                }
+            };
+
+            // skip the logically removed node
+            if let Some(value) = value {
+                self.start = Bound::Excluded(key.clone());
+                return Some((key, value));
+            }
+
+            self.start = Bound::Excluded(key);
+        }
+
+        None
+    }
+}
+
+impl<K: Ord, V> RwLockAVLTree<K, V> {
+    /// execute the function on the value of the key while holding the write lock of its node
+    ///
+    /// The function gets None if the key does not exist, and it can insert, replace or remove the value by the slot.
+    fn update<Q, F, T>(&self, key: &Q, f: F) -> T
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord + ToOwned<Owned = K>,
+        F: FnOnce(&mut Option<V>) -> T,
+    {
+        let guard = pin();
+
+        loop {
+            let mut cursor = self.find(key, &guard);
+
+            unsafe {
+                ManuallyDrop::drop(&mut cursor.inner_guard);
+            }
+
+            let current = unsafe { cursor.current.as_ref().unwrap() };
+
+            // check if the current is alive now by checking parent node. If disconnected, retry
+            let parent_read_guard = if let Some((parent, dir)) = cursor.ancestors.last() {
+                let parent_read_guard = unsafe { parent.as_ref().unwrap().inner.read().unwrap() };
+                if !parent_read_guard.is_same_child(*dir, cursor.current, &guard) {
+                    continue;
+                }
+                Some(parent_read_guard)
+            } else {
+                None
+            };
+
+            let mut write_guard = current.inner.write().unwrap();
+
+            drop(parent_read_guard);
+
+            let result = if cursor.dir == Dir::Eq {
+                let existed = write_guard.value.is_some();
+                let result = f(&mut write_guard.value);
+                self.count
+                    .add(write_guard.value.is_some() as isize - existed as isize);
+                result
+            } else {
+                let child = write_guard.get_child(cursor.dir);
+
+                if !child.load(Ordering::Relaxed, &guard).is_null() {
+                    continue; // some thread already writed. Retry
+                }
+
+                let mut value = None;
+                let result = f(&mut value);
+
+                match value {
+                    Some(value) => child.store(
+                        Owned::new(Node::new(key.to_owned(), value)),
+                        Ordering::Relaxed,
+                    ),
+                    None => return result,
+                }
+
+                self.count.increment();
+
+                result
+            };
+
+            drop(write_guard);
+
+            Cursor::repair(cursor, &guard);
+
+            return result;
+        }
+    }
+}
+
+impl<K: Ord, V> RwLockAVLTree<K, V> {
+    /// scan the nodes from the bound in the direction, and find the first node which is not logically removed
+    ///
+    /// The keys are scanned in the ascending order from the start bound if the dir is Right,
+    /// or in the descending order from the end bound if Left.
+    /// Every node is locked from its parent by the lock function, and no lock is released during the scan.
+    /// Since the locks are taken only from the parents to the children like the other operations, it does not deadlock.
+    fn scan<'g, Q, G, L>(
+        &self,
+        bound: Bound<&Q>,
+        dir: Dir,
+        guard: &'g Guard,
+        lock: L,
+    ) -> Scan<'g, K, V, G>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+        G: Deref<Target = NodeInner<K, V>>,
+        L: Fn(&'g Node<K, V>) -> G,
+    {
+        let back = match dir {
+            Dir::Left => Dir::Right,
+            Dir::Right => Dir::Left,
+            Dir::Eq => unreachable!(),
+        };
+
+        // the indexes of the nodes on the bound whose subtree on the dir is not scanned yet
+        let mut pending = Vec::with_capacity(self.get_height() + 5);
+
+        let root = self.root.load(Ordering::Relaxed, guard);
+        let mut scan = Scan {
+            nodes: vec![(root, lock(unsafe { root.as_ref().unwrap() }))],
+            found: None,
+        };
+        let mut next = Some((0, Dir::Right));
+
+        loop {
+            while let Some((index, child_dir)) = next.take() {
+                let child = scan.nodes[index]
+                    .1
+                    .get_child(child_dir)
+                    .load(Ordering::Relaxed, guard);
+
+                if let Some(child_ref) = unsafe { child.as_ref() } {
+                    scan.nodes.push((child, lock(child_ref)));
+                    let child_index = scan.nodes.len() - 1;
+
+                    let key: &Q = child_ref.key().borrow();
+                    let on_bound = match dir {
+                        Dir::Left => is_before_end(key, bound),
+                        _ => is_after_start(key, bound),
+                    };
+
+                    if on_bound {
+                        pending.push(child_index);
+                        next = Some((child_index, back));
+                    } else {
+                        next = Some((child_index, dir));
+                    }
+                }
+            }
+
+            let index = match pending.pop() {
+                Some(index) => index,
+                None => return scan,
+            };
+
+            if scan.nodes[index].1.value.is_some() {
+                scan.found = Some(index);
+                return scan;
+            }
+
+            // the logically removed node is skipped, then scan its subtree on the dir
+            next = Some((index, dir));
+        }
+    }
+
+    /// clone (key, value) of the first node from the bound in the direction while holding the read locks
+    fn scan_entry<Q>(&self, bound: Bound<&Q>, dir: Dir) -> Option<(K, V)>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Ord,
+        V: Clone,
+    {
+        let guard = pin();
+        let scan = self.scan(bound, dir, &guard, |node| node.inner.read().unwrap());
+
+        scan.found.map(|index| {
+            let (node, read_guard) = &scan.nodes[index];
+            let key = unsafe { node.as_ref().unwrap().key().clone() };
+            (key, read_guard.value.clone().unwrap())
+        })
+    }
+
+    /// remove (key, value) of the first node in the direction while holding the write locks
+    ///
+    /// It is linearizable since every node on the scan, from the root sentinel to the found node, is write locked
+    /// until the value is taken. So pop_first and pop_last serialize with every other operation on the tree,
+    /// and the operations on the leftmost or rightmost path are blocked during the whole scan.
+    fn pop_end(&self, dir: Dir) -> Option<(K, V)>
+    where
+        K: Clone,
+    {
+        let guard = pin();
+        let mut scan = self.scan(Bound::<&K>::Unbounded, dir, &guard, |node| {
+            node.inner.write().unwrap()
+        });
+
+        let (node, write_guard) = &mut scan.nodes[scan.found?];
+        let key = unsafe { node.as_ref().unwrap().key().clone() };
+        let value = write_guard.value.take().unwrap();
+        self.count.decrement();
+        drop(scan);
+
+        // the node is logically removed, so cleanup it like `remove`
+        let mut cursor = self.find(&key, &guard);
+        unsafe { ManuallyDrop::drop(&mut cursor.inner_guard) };
+        Cursor::repair(cursor, &guard);
+
+        Some((key, value))
+    }
+}
+
 impl<K, V> ConcurrentMap<K, V> for RwLockAVLTree<K, V>
 where
-    K: Ord + Clone + Default,
-    V: Default,
+    K: Ord,
 {
     fn new() -> Self {
         RwLockAVLTree {
-            root: Atomic::new(Node::default()),
+            root: Atomic::new(Node::sentinel()),
+            count: ShardedCounter::new(),
         }
     }
 
-    fn insert(&self, key: &K, value: V) -> Result<(), V> {
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
         let guard = pin();
 
-        let node = Node::new(key.clone(), value);
+        let node = Node::new(key, value);
 
         // TODO: it can be optimized by re-search nearby ancestors
         loop {
-            let mut cursor = self.find(key, &guard);
+            let mut cursor = self.find(node.key(), &guard);
 
             // unlock read lock and lock write lock... very inefficient, need upgrade from read lock to write lock
             unsafe {
@@ -545,8 +898,7 @@ where
             }
 
             if cursor.dir == Dir::Eq && cursor.inner_guard.value.is_some() {
-                let node_inner = node.inner.into_inner().unwrap();
-                return Err(node_inner.value.unwrap());
+                return Err(node.into_entry());
             }
 
             let current = unsafe { cursor.current.as_ref().unwrap() };
@@ -584,16 +936,17 @@ where
                     write_guard.right.store(Owned::new(node), Ordering::Relaxed);
                 }
                 Dir::Eq => {
-                    let value = node.inner.into_inner().unwrap().value.unwrap();
+                    let (key, value) = node.into_entry();
 
                     if write_guard.value.is_some() {
-                        return Err(value);
+                        return Err((key, value));
                     }
 
                     write_guard.value = Some(value);
                 }
             }
 
+            self.count.increment();
             drop(write_guard);
 
             Cursor::repair(cursor, &guard);
@@ -601,8 +954,14 @@ where
             return Ok(());
         }
     }
+}
 
-    fn lookup<F, R>(&self, key: &K, f: F) -> R
+impl<K, V, Q> ConcurrentLookup<K, V, Q> for RwLockAVLTree<K, V>
+where
+    K: Ord + Borrow<Q>,
+    Q: ?Sized + Ord,
+{
+    fn lookup<F, R>(&self, key: &Q, f: F) -> R
     where
         F: FnOnce(Option<&V>) -> R,
     {
@@ -623,7 +982,7 @@ where
         }
     }
 
-    fn get(&self, key: &K) -> Option<V>
+    fn get(&self, key: &Q) -> Option<V>
     where
         V: Clone,
     {
@@ -640,7 +999,7 @@ where
         }
     }
 
-    fn remove(&self, key: &K) -> Result<V, ()> {
+    fn remove(&self, key: &Q) -> Result<V, ()> {
         let guard = pin();
 
         let mut cursor = self.find(key, &guard);
@@ -660,18 +1019,118 @@ where
         }
 
         let value = write_guard.value.take().unwrap();
+        self.count.decrement();
         drop(write_guard);
 
         Cursor::repair(cursor, &guard);
 
         Ok(value)
     }
+
+    fn upsert(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+    {
+        self.update(key, |slot| slot.replace(value))
+    }
+
+    fn compute<F>(&self, key: &Q, mut f: F) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        self.update(key, |slot| {
+            let value = f(slot.as_ref());
+            mem::replace(slot, value)
+        })
+    }
+
+    fn get_or_insert_with<F>(&self, key: &Q, f: F) -> V
+    where
+        Q: ToOwned<Owned = K>,
+        V: Clone,
+        F: FnOnce() -> V,
+    {
+        self.update(key, |slot| slot.get_or_insert_with(f).clone())
+    }
+
+    fn compare_and_swap_value(&self, key: &Q, current: &V, new: V) -> Result<V, V>
+    where
+        Q: ToOwned<Owned = K>,
+        V: Clone + PartialEq,
+    {
+        self.update(key, |slot| match slot {
+            Some(value) if value == current => Ok(mem::replace(value, new)),
+            _ => Err(new),
+        })
+    }
+}
+
+impl<K: Ord, V> ConcurrentOrderedMap<K, V> for RwLockAVLTree<K, V> {
+    fn first(&self) -> Option<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.scan_entry(Bound::<&K>::Unbounded, Dir::Right)
+    }
+
+    fn last(&self) -> Option<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.scan_entry(Bound::<&K>::Unbounded, Dir::Left)
+    }
+
+    fn pop_first(&self) -> Option<(K, V)>
+    where
+        K: Clone,
+    {
+        self.pop_end(Dir::Right)
+    }
+
+    fn pop_last(&self) -> Option<(K, V)>
+    where
+        K: Clone,
+    {
+        self.pop_end(Dir::Left)
+    }
+
+    fn floor<Q>(&self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Ord,
+        V: Clone,
+    {
+        self.scan_entry(Bound::Included(key), Dir::Left)
+    }
+
+    fn ceiling<Q>(&self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Ord,
+        V: Clone,
+    {
+        self.scan_entry(Bound::Included(key), Dir::Right)
+    }
 }
 
 impl<K, V> Drop for RwLockAVLTree<K, V> {
     fn drop(&mut self) {
         let pin = pin();
-        let mut nodes = vec![mem::replace(&mut self.root, Atomic::null())];
+
+        // the key of the sentinel root is not initialized, so deallocate it without dropping as a node
+        let root = unsafe { mem::replace(&mut self.root, Atomic::null()).into_owned() };
+        let mut root = ManuallyDrop::new(*root.into_box());
+        let right = mem::replace(&mut root.inner.get_mut().unwrap().right, Atomic::null());
+        unsafe { ptr::drop_in_place(&mut root.inner) };
+
+        let mut nodes = Vec::new();
+        if !right.load(Ordering::Relaxed, &pin).is_null() {
+            nodes.push(right);
+        }
+
         while let Some(node) = nodes.pop() {
             let node = unsafe { node.into_owned() };
             let mut write_guard = node.inner.write().unwrap();
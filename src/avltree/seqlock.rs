@@ -1,13 +1,18 @@
+use std::borrow::Borrow;
 use std::cmp::max;
 use std::fmt::Debug;
 use std::mem;
-use std::mem::ManuallyDrop;
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::ops::{Bound, RangeBounds, RangeFull};
+use std::ptr;
 use std::sync::atomic::{AtomicIsize, Ordering};
 
 use crossbeam_epoch::{pin, unprotected, Atomic, Guard, Owned, Shared};
 
+use super::{is_after_start, is_before_end};
 use crate::lock::seqlock::{ReadGuard, SeqLock, WriteGuard};
-use crate::map::ConcurrentMap;
+use crate::map::{ConcurrentLookup, ConcurrentMap, ConcurrentOrderedMap};
+use crate::util::counter::ShardedCounter;
 
 struct NodeInner<K, V> {
     value: Atomic<V>,
@@ -117,8 +122,9 @@ impl<K, V> NodeInner<K, V> {
     }
 }
 
+/// The key is always initialized except the sentinel root, which is never dropped as a node.
 struct Node<K, V> {
-    key: K,
+    key: MaybeUninit<K>,
     height: AtomicIsize,
     inner: SeqLock<NodeInner<K, V>>,
 }
@@ -126,23 +132,23 @@ struct Node<K, V> {
 impl<K: Debug, V: Debug> Debug for Node<K, V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Node")
-            .field("key", &self.key)
+            .field("key", self.key())
             .field("height", &self.height.load(Ordering::Relaxed))
             .field("inner", &*self.inner.write_lock())
             .finish()
     }
 }
 
-impl<K: Default, V: Default> Default for Node<K, V> {
-    fn default() -> Self {
-        Self::new(K::default(), V::default())
+impl<K, V> Drop for Node<K, V> {
+    fn drop(&mut self) {
+        unsafe { self.key.as_mut_ptr().drop_in_place() };
     }
 }
 
 impl<K, V> Node<K, V> {
     fn new(key: K, value: V) -> Node<K, V> {
         Node {
-            key,
+            key: MaybeUninit::new(key),
             height: AtomicIsize::new(1),
             inner: SeqLock::new(NodeInner {
                 value: Atomic::new(value),
@@ -152,6 +158,23 @@ impl<K, V> Node<K, V> {
         }
     }
 
+    /// the root without key and value, whose right child is the real root
+    fn sentinel() -> Node<K, V> {
+        Node {
+            key: MaybeUninit::uninit(),
+            height: AtomicIsize::new(1),
+            inner: SeqLock::new(NodeInner {
+                value: Atomic::null(),
+                left: Atomic::null(),
+                right: Atomic::null(),
+            }),
+        }
+    }
+
+    fn key(&self) -> &K {
+        unsafe { &*self.key.as_ptr() }
+    }
+
     /// rotate left the node
     ///
     /// Change Parent-Right Child to Left Child-Parent, then return new parent(old right child).
@@ -413,14 +436,18 @@ impl<'g, K: Ord, V> Cursor<'g, K, V> {
     /// If there exists the key on the tree, the cursor's current is the node and the dir is Eq.
     /// If there does not exist the key on the tree, the cursor's current is leaf node and the dir is
     /// Left if the key is greater than the key of the node, or Right if the key is less than.
-    fn find(&mut self, key: &K, guard: &'g Guard) {
+    fn find<Q>(&mut self, key: &Q, guard: &'g Guard)
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
         loop {
             if self.move_next(guard).is_err() {
                 break;
             }
 
             unsafe {
-                self.dir = match key.cmp(&self.current.as_ref().unwrap().key) {
+                self.dir = match key.cmp(self.current.as_ref().unwrap().key().borrow()) {
                     std::cmp::Ordering::Less => Dir::Left,
                     std::cmp::Ordering::Equal => Dir::Eq,
                     std::cmp::Ordering::Greater => Dir::Right,
@@ -429,6 +456,25 @@ impl<'g, K: Ord, V> Cursor<'g, K, V> {
         }
     }
 
+    /// find the last state of the cursor by the start bound
+    ///
+    /// The node with the least key on the range is the last one which the cursor moved left from.
+    fn find_ceiling(&mut self, start: Bound<&K>, guard: &'g Guard) {
+        loop {
+            if self.move_next(guard).is_err() {
+                break;
+            }
+
+            let current = unsafe { self.current.as_ref().unwrap() };
+
+            self.dir = if is_after_start(current.key(), start) {
+                Dir::Left
+            } else {
+                Dir::Right
+            };
+        }
+    }
+
     /// move to parent until self.inner_guard is valid
     fn recover(&mut self) {
         while let Some((parent, parent_read_guard, dir)) = self.ancestors.pop() {
@@ -524,41 +570,243 @@ impl<'g, K: Ord, V> Cursor<'g, K, V> {
     }
 }
 
+/// the node read by the ordered scan with its optimistic read guard
+type ScanNode<'g, K, V> = (
+    Shared<'g, Node<K, V>>,
+    ManuallyDrop<ReadGuard<'g, NodeInner<K, V>>>,
+);
+
+/// the nodes read by the ordered scan with their optimistic read guards, and the node found on them with its value
+///
+/// The scan is consistent only if all read guards are valid at once.
+struct Scan<'g, K, V> {
+    nodes: Vec<ScanNode<'g, K, V>>,
+    found: Option<(usize, Shared<'g, V>)>,
+}
+
+impl<'g, K, V> Scan<'g, K, V> {
+    fn validate(&self) -> bool {
+        self.nodes
+            .iter()
+            .all(|(_, read_guard)| read_guard.validate())
+    }
+}
+
 pub struct SeqLockAVLTree<K, V> {
     root: Atomic<Node<K, V>>,
+    count: ShardedCounter,
 }
 
 impl<K: Debug, V: Debug> Debug for SeqLockAVLTree<K, V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         unsafe {
+            let root = self
+                .root
+                .load(Ordering::Acquire, unprotected())
+                .as_ref()
+                .unwrap();
+
             f.debug_struct("SeqLockAVLTree")
-                .field(
-                    "root",
-                    self.root
-                        .load(Ordering::Acquire, unprotected())
-                        .as_ref()
-                        .unwrap(),
-                )
+                .field("root", &*root.inner.write_lock())
                 .finish()
         }
     }
 }
 
-impl<K: Default, V: Default> Default for SeqLockAVLTree<K, V> {
+impl<K, V> Default for SeqLockAVLTree<K, V> {
     fn default() -> Self {
         Self {
-            root: Atomic::new(Node::default()),
+            root: Atomic::new(Node::sentinel()),
+            count: ShardedCounter::new(),
         }
     }
 }
 
 impl<K, V> Drop for SeqLockAVLTree<K, V> {
     fn drop(&mut self) {
-        unsafe { drop(mem::replace(&mut self.root, Atomic::null()).into_owned()) }
+        // the key of the sentinel root is not initialized, so drop only its inner with the descendants
+        unsafe {
+            let root = mem::replace(&mut self.root, Atomic::null()).into_owned();
+            let mut root = ManuallyDrop::new(*root.into_box());
+            ptr::drop_in_place(&mut root.inner);
+        }
+    }
+}
+
+impl<K: Ord, V> SeqLockAVLTree<K, V> {
+    /// find the value of the key optimistically
+    ///
+    /// The pointer of the value is validated by the version of the node's seqlock, and read again if invalid.
+    /// The value is never modified in place and dropped only after the readers' epoch,
+    /// so the reference is live until the guard is dropped even if the value is replaced or removed.
+    fn find_value<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Option<&'g V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut cursor = Cursor::new(self, guard);
+
+        loop {
+            cursor.recover();
+            cursor.find(key, guard);
+
+            if cursor.dir != Dir::Eq {
+                return None;
+            }
+
+            let value = cursor.inner_guard.value.load(Ordering::Acquire, guard);
+
+            if !cursor.inner_guard.validate() {
+                continue;
+            }
+
+            return unsafe { value.as_ref() };
+        }
+    }
+
+    /// Lookup (key, value) from the map with the key without any write lock.
+    ///
+    /// Like `lookup`, the value is validated by the seqlock and read by the reference,
+    /// so readers do not block each other. Unlike `lookup`, it does not need `V: Clone`.
+    pub fn lookup_optimistic<Q, F, R>(&self, key: &Q, f: F) -> R
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+        F: FnOnce(Option<&V>) -> R,
+    {
+        let guard = pin();
+
+        f(self.find_value(key, &guard))
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> SeqLockAVLTree<K, V> {
+    /// find (key, value) of the node with the least key on the range by the start bound
+    ///
+    /// The value is None if the node is logically removed. Like `get`, the value is cloned optimistically,
+    /// which is safe since the value is never modified in place and dropped only after the epoch of the guard.
+    fn weak_ceiling(&self, start: Bound<&K>, guard: &Guard) -> Option<(K, Option<V>)> {
+        let mut cursor = Cursor::new(self, guard);
+
+        loop {
+            cursor.recover();
+            cursor.find_ceiling(start, guard);
+
+            let last = (cursor.current, &cursor.inner_guard, cursor.dir);
+            let ceiling = cursor
+                .ancestors
+                .iter()
+                .map(|(node, read_guard, dir)| (*node, read_guard, *dir))
+                .chain(Some(last))
+                .rev()
+                .find(|(_, _, dir)| *dir == Dir::Left);
+
+            let (node, read_guard, _) = ceiling?;
+
+            let entry = unsafe {
+                (
+                    node.as_ref().unwrap().key().clone(),
+                    read_guard
+                        .value
+                        .load(Ordering::Acquire, guard)
+                        .as_ref()
+                        .cloned(),
+                )
+            };
+
+            if !read_guard.validate() {
+                continue;
+            }
+
+            return Some(entry);
+        }
+    }
+
+    /// iterate (key, value) in the ascending order of the key, which is weakly consistent like `SeqLockRange`
+    pub fn iter<'g>(&'g self, guard: &'g Guard) -> SeqLockRange<'g, K, V, RangeFull> {
+        self.range(.., guard)
+    }
+
+    /// iterate (key, value) whose key is on the range in the ascending order of the key
+    pub fn range<'g, R: RangeBounds<K>>(
+        &'g self,
+        range: R,
+        guard: &'g Guard,
+    ) -> SeqLockRange<'g, K, V, R> {
+        SeqLockRange {
+            tree: self,
+            guard,
+            start: range.start_bound().cloned(),
+            range,
+            finished: false,
+        }
+    }
+}
+
+/// weakly consistent iterator of (key, value) on the range of SeqLockAVLTree
+///
+/// Each step finds the successor of the last key from the root with optimistic read locks,
+/// so it never blocks writers, and the rotations do not break the iteration.
+/// Since no cursor is kept between the steps, each step costs O(log n) and the whole iteration O(n log n).
+/// The keys are returned in the ascending order without duplicates. The entry which exists during the whole iteration
+/// is always returned, but the entry inserted or removed during the iteration may or may not be returned.
+pub struct SeqLockRange<'g, K, V, R> {
+    tree: &'g SeqLockAVLTree<K, V>,
+    guard: &'g Guard,
+    start: Bound<K>,
+    range: R,
+    finished: bool,
+}
+
+impl<'g, K, V, R> Iterator for SeqLockRange<'g, K, V, R>
+where
+    K: Ord + Clone,
+    V: Clone,
+    R: RangeBounds<K>,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.finished {
+            let start = match &self.start {
+                Bound::Included(key) => Bound::Included(key),
+                Bound::Excluded(key) => Bound::Excluded(key),
+                Bound::Unbounded => Bound::Unbounded,
+            };
+
+            let (key, value) = match self.tree.weak_ceiling(start, self.guard) {
+                Some(entry) if is_before_end(&entry.0, self.range.end_bound()) => entry,
+                _ => {
+                    self.finished = true;
+                    break;
+                }
+            };
+
+            // skip the logically removed node
+            if let Some(value) = value {
+                self.start = Bound::Excluded(key.clone());
+                return Some((key, value));
+            }
+
+            self.start = Bound::Excluded(key);
+        }
+
+        None
     }
 }
 
 impl<K, V> SeqLockAVLTree<K, V> {
+    /// the number of keys on the tree
+    ///
+    /// It is exact when no operation is running, but approximate while other threads insert or remove.
+    pub fn len(&self) -> usize {
+        self.count.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     /// get the height of the tree
     pub fn get_height(&self) -> usize {
         unsafe {
@@ -581,18 +829,274 @@ impl<K, V> SeqLockAVLTree<K, V> {
     }
 }
 
+impl<K: Ord, V: Clone> SeqLockAVLTree<K, V> {
+    /// execute the function on the value of the key while holding the write lock of its node
+    ///
+    /// The function gets the value, or None if the key does not exist, and returns the new value with the result.
+    /// The value is not changed if the new value is None, and removed if it is Some(None).
+    /// Since optimistic readers may read the old value, only the changed value is stored on a new allocation,
+    /// and the old one is dropped after the readers' epoch.
+    fn update<Q, F, T>(&self, key: &Q, f: F) -> T
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord + ToOwned<Owned = K>,
+        F: FnOnce(Option<&V>) -> (Option<Option<V>>, T),
+    {
+        let guard = pin();
+
+        let mut cursor = Cursor::new(self, &guard);
+
+        loop {
+            cursor.recover();
+            cursor.find(key, &guard);
+
+            let inner_guard = ManuallyDrop::into_inner(cursor.inner_guard.clone());
+
+            let write_guard = if let Ok(guard) = inner_guard.upgrade() {
+                guard
+            } else {
+                continue;
+            };
+
+            // check if the current is alive now by checking parent node. If disconnected, retry
+            if let Some((_, read_guard, dir)) = cursor.ancestors.last() {
+                if !read_guard.is_same_child(*dir, cursor.current, &guard) || !read_guard.validate()
+                {
+                    continue;
+                }
+            }
+
+            let result = if cursor.dir == Dir::Eq {
+                let old = write_guard.value.load(Ordering::Relaxed, &guard);
+                let (value, result) = f(unsafe { old.as_ref() });
+
+                if let Some(value) = value {
+                    let new = match value {
+                        Some(value) => Owned::new(value).into_shared(&guard),
+                        None => Shared::null(),
+                    };
+                    write_guard.value.store(new, Ordering::Release);
+                    self.count
+                        .add(!new.is_null() as isize - !old.is_null() as isize);
+
+                    // Optimistic readers may still clone the old value, so drop it later.
+                    if !old.is_null() {
+                        unsafe { guard.defer_destroy(old) };
+                    }
+                }
+
+                result
+            } else {
+                let child = write_guard.get_child(cursor.dir);
+
+                if !child.load(Ordering::Relaxed, &guard).is_null() {
+                    continue; // some thread already writed. Retry
+                }
+
+                let (value, result) = f(None);
+
+                match value.flatten() {
+                    Some(value) => child.store(
+                        Owned::new(Node::new(key.to_owned(), value)),
+                        Ordering::Relaxed,
+                    ),
+                    None => return result,
+                }
+
+                self.count.increment();
+
+                result
+            };
+
+            drop(write_guard);
+
+            Cursor::repair(cursor, &guard);
+
+            return result;
+        }
+    }
+}
+
+impl<K: Ord, V> SeqLockAVLTree<K, V> {
+    /// scan the nodes from the bound in the direction, and find the first node which is not logically removed
+    ///
+    /// The keys are scanned in the ascending order from the start bound if the dir is Right,
+    /// or in the descending order from the end bound if Left.
+    /// Every read guard is kept on the scan to validate them at once. If some guard is invalid on the way, return None.
+    fn scan<'g, Q>(&self, bound: Bound<&Q>, dir: Dir, guard: &'g Guard) -> Option<Scan<'g, K, V>>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let back = match dir {
+            Dir::Left => Dir::Right,
+            Dir::Right => Dir::Left,
+            Dir::Eq => unreachable!(),
+        };
+
+        // the indexes of the nodes on the bound whose subtree on the dir is not scanned yet
+        let mut pending = Vec::with_capacity(self.get_height() + 5);
+
+        let root = self.root.load(Ordering::Relaxed, guard);
+        let mut scan = Scan {
+            nodes: vec![(
+                root,
+                ManuallyDrop::new(unsafe { root.as_ref().unwrap().inner.read_lock() }),
+            )],
+            found: None,
+        };
+        let mut next = Some((0, Dir::Right));
+
+        loop {
+            while let Some((index, child_dir)) = next.take() {
+                let read_guard = &scan.nodes[index].1;
+                let child = read_guard
+                    .get_child(child_dir)
+                    .load(Ordering::Relaxed, guard);
+
+                if !read_guard.validate() {
+                    return None;
+                }
+
+                if let Some(child_ref) = unsafe { child.as_ref() } {
+                    let child_guard = unsafe { child_ref.inner.read_lock() };
+                    scan.nodes.push((child, ManuallyDrop::new(child_guard)));
+                    let child_index = scan.nodes.len() - 1;
+
+                    let key: &Q = child_ref.key().borrow();
+                    let on_bound = match dir {
+                        Dir::Left => is_before_end(key, bound),
+                        _ => is_after_start(key, bound),
+                    };
+
+                    if on_bound {
+                        pending.push(child_index);
+                        next = Some((child_index, back));
+                    } else {
+                        next = Some((child_index, dir));
+                    }
+                }
+            }
+
+            let index = match pending.pop() {
+                Some(index) => index,
+                None => return Some(scan),
+            };
+
+            let value = scan.nodes[index].1.value.load(Ordering::Acquire, guard);
+
+            if !value.is_null() {
+                scan.found = Some((index, value));
+                return Some(scan);
+            }
+
+            // the logically removed node is skipped, then scan its subtree on the dir
+            next = Some((index, dir));
+        }
+    }
+
+    /// clone (key, value) of the first node from the bound in the direction
+    ///
+    /// The entry is returned only if all nodes on the scan are not changed, so it is linearizable.
+    /// The value is cloned before the validation, which is safe since it is dropped only after the epoch of the guard.
+    fn scan_entry<Q>(&self, bound: Bound<&Q>, dir: Dir) -> Option<(K, V)>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Ord,
+        V: Clone,
+    {
+        let guard = pin();
+
+        loop {
+            let scan = match self.scan(bound, dir, &guard) {
+                Some(scan) => scan,
+                None => continue,
+            };
+
+            let entry = scan.found.map(|(index, value)| unsafe {
+                (
+                    scan.nodes[index].0.as_ref().unwrap().key().clone(),
+                    value.as_ref().unwrap().clone(),
+                )
+            });
+
+            if scan.validate() {
+                return entry;
+            }
+        }
+    }
+
+    /// remove (key, value) of the first node in the direction
+    ///
+    /// The found node is write locked by upgrading its read guard, then the other nodes are validated.
+    fn pop_end(&self, dir: Dir) -> Option<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let guard = pin();
+
+        loop {
+            let mut scan = match self.scan(Bound::<&K>::Unbounded, dir, &guard) {
+                Some(scan) => scan,
+                None => continue,
+            };
+
+            let index = match scan.found {
+                Some((index, _)) => index,
+                None if scan.validate() => return None,
+                None => continue,
+            };
+
+            let (node, read_guard) = scan.nodes.swap_remove(index);
+
+            let write_guard =
+                if let Ok(write_guard) = ManuallyDrop::into_inner(read_guard).upgrade() {
+                    write_guard
+                } else {
+                    continue;
+                };
+
+            if !scan.validate() {
+                continue;
+            }
+
+            let value = write_guard
+                .value
+                .swap(Shared::null(), Ordering::Acquire, &guard);
+            self.count.decrement();
+            drop(write_guard);
+
+            // the node is logically removed, so cleanup it like `remove`
+            let key = unsafe { node.as_ref().unwrap().key().clone() };
+            let mut cursor = Cursor::new(self, &guard);
+            cursor.find(&key, &guard);
+            Cursor::repair(cursor, &guard);
+
+            // Optimistic readers may still clone the value, so return its clone and drop it later.
+            unsafe {
+                let result = value.as_ref().unwrap().clone();
+                guard.defer_destroy(value);
+
+                return Some((key, result));
+            }
+        }
+    }
+}
+
 impl<K, V> ConcurrentMap<K, V> for SeqLockAVLTree<K, V>
 where
-    K: Ord + Clone + Default,
-    V: Default,
+    K: Ord,
+    V: Clone,
 {
     fn new() -> Self {
         SeqLockAVLTree {
-            root: Atomic::new(Node::default()),
+            root: Atomic::new(Node::sentinel()),
+            count: ShardedCounter::new(),
         }
     }
 
-    fn insert(&self, key: &K, value: V) -> Result<(), V> {
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
         let guard = pin();
 
         let mut cursor = Cursor::new(self, &guard);
@@ -600,7 +1104,7 @@ where
         loop {
             // if the cursor is invalid, then move up until cursor.inner_guard is valid
             cursor.recover();
-            cursor.find(key, &guard);
+            cursor.find(&key, &guard);
 
             let inner_guard = ManuallyDrop::into_inner(cursor.inner_guard.clone());
 
@@ -612,7 +1116,7 @@ where
 
             if cursor.dir == Dir::Eq && !write_guard.value.load(Ordering::Relaxed, &guard).is_null()
             {
-                return Err(value);
+                return Err((key, value));
             }
 
             // check if the current is alive now by checking parent node. If disconnected, retry
@@ -630,7 +1134,7 @@ where
                         continue; // some thread already writed. Retry
                     }
 
-                    let node = Node::new(key.clone(), value);
+                    let node = Node::new(key, value);
                     write_guard.left.store(Owned::new(node), Ordering::Relaxed);
                 }
                 Dir::Right => {
@@ -638,12 +1142,12 @@ where
                         continue; // some thread already writed. Retry
                     }
 
-                    let node = Node::new(key.clone(), value);
+                    let node = Node::new(key, value);
                     write_guard.right.store(Owned::new(node), Ordering::Relaxed);
                 }
                 Dir::Eq => {
                     if !write_guard.value.load(Ordering::Relaxed, &guard).is_null() {
-                        return Err(value);
+                        return Err((key, value));
                     }
 
                     write_guard
@@ -652,6 +1156,7 @@ where
                 }
             }
 
+            self.count.increment();
             drop(write_guard);
 
             Cursor::repair(cursor, &guard);
@@ -659,68 +1164,31 @@ where
             return Ok(());
         }
     }
+}
 
-    fn lookup<F, R>(&self, key: &K, f: F) -> R
+impl<K, V, Q> ConcurrentLookup<K, V, Q> for SeqLockAVLTree<K, V>
+where
+    K: Ord + Borrow<Q>,
+    V: Clone,
+    Q: ?Sized + Ord,
+{
+    fn lookup<F, R>(&self, key: &Q, f: F) -> R
     where
         F: FnOnce(Option<&V>) -> R,
     {
         let guard = pin();
 
-        let mut cursor = Cursor::new(self, &guard);
-
-        loop {
-            cursor.recover();
-            cursor.find(key, &guard);
-
-            if cursor.dir == Dir::Eq {
-                let inner_guard = ManuallyDrop::into_inner(cursor.inner_guard.clone());
-                let write_guard = if let Ok(write_guard) = inner_guard.upgrade() {
-                    write_guard
-                } else {
-                    continue;
-                };
-
-                return unsafe { f(write_guard.value.load(Ordering::Acquire, &guard).as_ref()) };
-            } else {
-                return f(None);
-            }
-        }
+        f(self.find_value(key, &guard))
     }
 
-    fn get(&self, key: &K) -> Option<V>
+    fn get(&self, key: &Q) -> Option<V>
     where
         V: Clone,
     {
-        let guard = pin();
-
-        let mut cursor = Cursor::new(self, &guard);
-
-        loop {
-            cursor.recover();
-            cursor.find(key, &guard);
-
-            if cursor.dir == Dir::Eq {
-                let value = unsafe {
-                    cursor
-                        .inner_guard
-                        .value
-                        .load(Ordering::Acquire, &guard)
-                        .as_ref()
-                        .cloned()
-                };
-
-                if !cursor.inner_guard.validate() {
-                    continue;
-                }
-
-                return value;
-            } else {
-                return None;
-            }
-        }
+        self.lookup(key, |value| value.cloned())
     }
 
-    fn remove(&self, key: &K) -> Result<V, ()> {
+    fn remove(&self, key: &Q) -> Result<V, ()> {
         let guard = pin();
 
         let mut cursor = Cursor::new(self, &guard);
@@ -748,10 +1216,108 @@ where
                 return Err(());
             }
 
+            self.count.decrement();
             drop(write_guard);
             Cursor::repair(cursor, &guard);
 
-            return unsafe { Ok(*(value.into_owned().into_box())) };
+            // Optimistic readers may still clone the value, so return its clone and drop it later.
+            unsafe {
+                let result = value.as_ref().unwrap().clone();
+                guard.defer_destroy(value);
+
+                return Ok(result);
+            }
         }
     }
+
+    fn upsert(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+    {
+        self.update(key, |old| (Some(Some(value)), old.cloned()))
+    }
+
+    fn compute<F>(&self, key: &Q, mut f: F) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        self.update(key, |old| (Some(f(old)), old.cloned()))
+    }
+
+    fn get_or_insert_with<F>(&self, key: &Q, f: F) -> V
+    where
+        Q: ToOwned<Owned = K>,
+        V: Clone,
+        F: FnOnce() -> V,
+    {
+        self.update(key, |old| match old {
+            Some(value) => (None, value.clone()),
+            None => {
+                let value = f();
+                (Some(Some(value.clone())), value)
+            }
+        })
+    }
+
+    fn compare_and_swap_value(&self, key: &Q, current: &V, new: V) -> Result<V, V>
+    where
+        Q: ToOwned<Owned = K>,
+        V: Clone + PartialEq,
+    {
+        self.update(key, |old| match old {
+            Some(value) if value == current => (Some(Some(new)), Ok(value.clone())),
+            _ => (None, Err(new)),
+        })
+    }
+}
+
+impl<K: Ord, V: Clone> ConcurrentOrderedMap<K, V> for SeqLockAVLTree<K, V> {
+    fn first(&self) -> Option<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.scan_entry(Bound::<&K>::Unbounded, Dir::Right)
+    }
+
+    fn last(&self) -> Option<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.scan_entry(Bound::<&K>::Unbounded, Dir::Left)
+    }
+
+    fn pop_first(&self) -> Option<(K, V)>
+    where
+        K: Clone,
+    {
+        self.pop_end(Dir::Right)
+    }
+
+    fn pop_last(&self) -> Option<(K, V)>
+    where
+        K: Clone,
+    {
+        self.pop_end(Dir::Left)
+    }
+
+    fn floor<Q>(&self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Ord,
+        V: Clone,
+    {
+        self.scan_entry(Bound::Included(key), Dir::Left)
+    }
+
+    fn ceiling<Q>(&self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Ord,
+        V: Clone,
+    {
+        self.scan_entry(Bound::Included(key), Dir::Right)
+    }
 }
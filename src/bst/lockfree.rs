@@ -0,0 +1,565 @@
+/*
+ Refer to
+ Natarajan and Mittal, Fast Concurrent Lock-Free Binary Search Trees, PPoPP 2014
+ https://dl.acm.org/doi/10.1145/2555243.2555256
+*/
+
+use std::{borrow::Borrow, mem, ptr, sync::atomic::Ordering};
+
+use crossbeam_epoch::{pin, unprotected, Atomic, Guard, Owned, Shared};
+
+use crate::map::{ConcurrentLookup, ConcurrentMap};
+
+/// the tag on the edge to the leaf which is logically removed
+const FLAG: usize = 1;
+/// the tag on the edge whose source node is being unlinked, so the edge never changes
+const TAG: usize = 2;
+
+/// the key of the node, where the two infinite keys are only for the sentinels
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Key<K> {
+    Fin(K),
+    Inf1,
+    Inf2,
+}
+
+impl<K> Key<K> {
+    /// whether the key goes to the left of the node which has this key
+    #[inline]
+    fn is_greater<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        match self {
+            Key::Fin(k) => key < k.borrow(),
+            _ => true,
+        }
+    }
+
+    #[inline]
+    fn is_equal<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        match self {
+            Key::Fin(k) => k.borrow() == key,
+            _ => false,
+        }
+    }
+}
+
+/// Lock-free external binary search tree
+///
+/// The entries are only on the leaves, and the internal nodes route the search by their keys.
+/// The tree is not balanced, so it is fast only on the random keys.
+///
+/// The tag FLAG on the edge means that the leaf is logically removed,
+/// and the tag TAG means that the source node of the edge is being unlinked.
+/// The value is replaced by swinging the untagged edge from the old leaf to the new leaf of the same key.
+pub struct LockFreeBST<K, V> {
+    root: Node<K, V>, // the sentinel R(Inf2), whose left child is the sentinel S(Inf1)
+}
+
+/// The internal node has both children, and the leaf has no child but the value.
+///
+/// The value is dropped with its leaf after the readers' epoch, so the removed value is returned as a clone.
+struct Node<K, V> {
+    key: Key<K>,
+    value: Option<V>,
+    left: Atomic<Node<K, V>>,
+    right: Atomic<Node<K, V>>,
+}
+
+impl<K, V> Node<K, V> {
+    fn leaf(key: Key<K>, value: Option<V>) -> Self {
+        Self {
+            key,
+            value,
+            left: Atomic::null(),
+            right: Atomic::null(),
+        }
+    }
+
+    fn internal(key: Key<K>, left: Atomic<Self>, right: Atomic<Self>) -> Self {
+        Self {
+            key,
+            value: None,
+            left,
+            right,
+        }
+    }
+
+    /// get the edges to the child on the direction of the key and to the other child
+    #[inline]
+    fn children<Q>(&self, key: &Q) -> (&Atomic<Self>, &Atomic<Self>)
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        if self.key.is_greater(key) {
+            (&self.left, &self.right)
+        } else {
+            (&self.right, &self.left)
+        }
+    }
+
+    #[inline]
+    fn child<Q>(&self, key: &Q) -> &Atomic<Self>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        self.children(key).0
+    }
+}
+
+/// the result of seeking the key
+///
+/// ancestor -> successor: the last untagged edge before the parent on the path
+/// parent -> leaf: the last edge on the path
+///
+/// The edges from the successor to the parent are all tagged,
+/// so they are unlinked together by swinging the edge of the ancestor.
+struct SeekRecord<'g, K, V> {
+    ancestor: &'g Node<K, V>,
+    successor: Shared<'g, Node<K, V>>,
+    parent: &'g Node<K, V>,
+    leaf: Shared<'g, Node<K, V>>,
+}
+
+impl<K, V> Default for LockFreeBST<K, V> {
+    fn default() -> Self {
+        let sentinel = Node::internal(
+            Key::Inf1,
+            Atomic::new(Node::leaf(Key::Inf1, None)),
+            Atomic::new(Node::leaf(Key::Inf2, None)),
+        );
+
+        Self {
+            root: Node::internal(
+                Key::Inf2,
+                Atomic::new(sentinel),
+                Atomic::new(Node::leaf(Key::Inf2, None)),
+            ),
+        }
+    }
+}
+
+impl<K: Ord, V> LockFreeBST<K, V> {
+    /// find the path to the leaf of the key without any modification
+    fn seek<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> SeekRecord<'g, K, V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut ancestor = &self.root;
+        let mut successor = self.root.left.load(Ordering::Acquire, guard);
+        let mut parent = unsafe { successor.deref() };
+        let mut parent_edge = parent.child(key).load(Ordering::Acquire, guard);
+        let mut leaf = parent_edge.with_tag(0);
+
+        loop {
+            let leaf_ref = unsafe { leaf.deref() };
+            let current_edge = leaf_ref.child(key).load(Ordering::Acquire, guard);
+            let current = current_edge.with_tag(0);
+
+            if current.is_null() {
+                break;
+            }
+
+            if parent_edge.tag() & TAG == 0 {
+                ancestor = parent;
+                successor = leaf;
+            }
+
+            parent = leaf_ref;
+            leaf = current;
+            parent_edge = current_edge;
+        }
+
+        SeekRecord {
+            ancestor,
+            successor,
+            parent,
+            leaf,
+        }
+    }
+
+    /// find the leaf of the key which is not logically removed
+    fn find<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Option<&'g Node<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut edge = self.root.left.load(Ordering::Acquire, guard);
+
+        loop {
+            let node = unsafe { edge.with_tag(0).deref() };
+            let next = node.child(key).load(Ordering::Acquire, guard);
+
+            if next.is_null() {
+                if edge.tag() & FLAG == 0 && node.key.is_equal(key) {
+                    return Some(node);
+                }
+
+                return None;
+            }
+
+            edge = next;
+        }
+    }
+
+    /// unlink the flagged leaf and its parent by moving the sibling up to the ancestor
+    ///
+    /// If this thread unlinks them, return true else false.
+    fn cleanup<'g, Q>(&'g self, key: &Q, record: &SeekRecord<'g, K, V>, guard: &'g Guard) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let successor_edge = record.ancestor.child(key);
+        let (mut child_edge, mut sibling_edge) = record.parent.children(key);
+
+        if child_edge.load(Ordering::Acquire, guard).tag() & FLAG == 0 {
+            // the sibling is the flagged leaf, so keep the child instead
+            mem::swap(&mut child_edge, &mut sibling_edge);
+        }
+
+        // freeze the edge to the sibling before moving it
+        let sibling = sibling_edge.fetch_or(TAG, Ordering::AcqRel, guard);
+
+        if successor_edge
+            .compare_exchange(
+                record.successor,
+                sibling.with_tag(sibling.tag() & FLAG),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                guard,
+            )
+            .is_err()
+        {
+            return false;
+        }
+
+        unsafe { Self::retire(key, record, sibling.with_tag(0), guard) };
+
+        true
+    }
+
+    /// unlink the leaf flagged on the record unless another thread already did
+    fn unlink<'g, Q>(&'g self, key: &Q, mut record: SeekRecord<'g, K, V>, guard: &'g Guard)
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let leaf = record.leaf;
+
+        while !self.cleanup(key, &record, guard) {
+            record = self.seek(key, guard);
+
+            if record.leaf != leaf {
+                return;
+            }
+        }
+    }
+
+    /// destroy the unlinked nodes from the successor to the parent with their flagged leaves
+    ///
+    /// All edges of them are frozen by the tags, so no other thread retires them.
+    unsafe fn retire<'g, Q>(
+        key: &Q,
+        record: &SeekRecord<'g, K, V>,
+        sibling: Shared<'g, Node<K, V>>,
+        guard: &'g Guard,
+    ) where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut node = record.successor;
+
+        loop {
+            let node_ref = node.deref();
+            let (child_edge, other_edge) = node_ref.children(key);
+            let child = child_edge.load(Ordering::Acquire, guard).with_tag(0);
+            let other = other_edge.load(Ordering::Acquire, guard).with_tag(0);
+
+            guard.defer_destroy(node);
+
+            if ptr::eq(node_ref, record.parent) {
+                guard.defer_destroy(if child == sibling { other } else { child });
+                return;
+            }
+
+            // the other child of the tagged path is the flagged leaf
+            guard.defer_destroy(other);
+            node = child;
+        }
+    }
+}
+
+impl<K, V> ConcurrentMap<K, V> for LockFreeBST<K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
+        let guard = pin();
+
+        let new_leaf = Owned::new(Node::leaf(Key::Fin(key), Some(value))).into_shared(&guard);
+        let key = match &unsafe { new_leaf.deref() }.key {
+            Key::Fin(key) => key,
+            _ => unreachable!(),
+        };
+
+        loop {
+            let record = self.seek(key, &guard);
+            let leaf_ref = unsafe { record.leaf.deref() };
+
+            if leaf_ref.key.is_equal(key) {
+                let Node { key, value, .. } = *unsafe { new_leaf.into_owned() }.into_box();
+
+                match key {
+                    Key::Fin(key) => return Err((key, value.unwrap())),
+                    _ => unreachable!(),
+                }
+            }
+
+            // the internal node has the greater key, and its right child is the greater leaf
+            let internal = if leaf_ref.key.is_greater(key) {
+                Node::internal(
+                    leaf_ref.key.clone(),
+                    Atomic::from(new_leaf),
+                    Atomic::from(record.leaf),
+                )
+            } else {
+                Node::internal(
+                    Key::Fin(key.clone()),
+                    Atomic::from(record.leaf),
+                    Atomic::from(new_leaf),
+                )
+            };
+
+            match record.parent.child(key).compare_exchange(
+                record.leaf,
+                Owned::new(internal),
+                Ordering::Release,
+                Ordering::Acquire,
+                &guard,
+            ) {
+                Ok(_) => return Ok(()),
+                Err(e) => {
+                    // help the removal of the leaf which blocks the insertion
+                    if e.current.with_tag(0) == record.leaf && e.current.tag() != 0 {
+                        self.cleanup(key, &record, &guard);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<K, V, Q> ConcurrentLookup<K, V, Q> for LockFreeBST<K, V>
+where
+    K: Ord + Clone + Borrow<Q>,
+    V: Clone,
+    Q: ?Sized + Ord,
+{
+    fn lookup<F, R>(&self, key: &Q, f: F) -> R
+    where
+        F: FnOnce(Option<&V>) -> R,
+    {
+        let guard = pin();
+
+        f(self
+            .find(key, &guard)
+            .map(|node| node.value.as_ref().unwrap()))
+    }
+
+    fn get(&self, key: &Q) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.lookup(key, |value| value.cloned())
+    }
+
+    fn remove(&self, key: &Q) -> Result<V, ()> {
+        let guard = pin();
+
+        // flag the edge to the leaf, which is the linearization point
+        loop {
+            let record = self.seek(key, &guard);
+            let leaf_ref = unsafe { record.leaf.deref() };
+
+            if !leaf_ref.key.is_equal(key) {
+                return Err(());
+            }
+
+            match record.parent.child(key).compare_exchange(
+                record.leaf,
+                record.leaf.with_tag(FLAG),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard,
+            ) {
+                Ok(_) => {
+                    // the thread that flags the edge returns the value, while readers may still read it
+                    let value = leaf_ref.value.clone().unwrap();
+                    self.unlink(key, record, &guard);
+
+                    return Ok(value);
+                }
+                Err(e) => {
+                    if e.current.with_tag(0) == record.leaf && e.current.tag() != 0 {
+                        self.cleanup(key, &record, &guard);
+                    }
+                }
+            }
+        }
+    }
+
+    fn upsert(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+    {
+        // the function may be called again on the contention, so it clones the value
+        self.compute(key, |_| Some(value.clone()))
+    }
+
+    fn compute<F>(&self, key: &Q, mut f: F) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        let guard = pin();
+
+        loop {
+            let record = self.seek(key, &guard);
+            let leaf_ref = unsafe { record.leaf.deref() };
+            let edge = record.parent.child(key);
+
+            let current = edge.load(Ordering::Acquire, &guard);
+
+            if current != record.leaf {
+                // help the removal of the leaf which blocks the update
+                if current.with_tag(0) == record.leaf {
+                    self.cleanup(key, &record, &guard);
+                }
+
+                continue;
+            }
+
+            if !leaf_ref.key.is_equal(key) {
+                let new_leaf = Node::leaf(Key::Fin(key.to_owned()), Some(f(None)?));
+                let new_leaf = Owned::new(new_leaf).into_shared(&guard);
+                let new_key = match &unsafe { new_leaf.deref() }.key {
+                    Key::Fin(key) => key,
+                    _ => unreachable!(),
+                };
+
+                // the internal node has the greater key like insert
+                let internal = if leaf_ref.key.is_greater(key) {
+                    Node::internal(
+                        leaf_ref.key.clone(),
+                        Atomic::from(new_leaf),
+                        Atomic::from(record.leaf),
+                    )
+                } else {
+                    Node::internal(
+                        Key::Fin(new_key.clone()),
+                        Atomic::from(record.leaf),
+                        Atomic::from(new_leaf),
+                    )
+                };
+
+                if edge
+                    .compare_exchange(
+                        record.leaf,
+                        Owned::new(internal),
+                        Ordering::Release,
+                        Ordering::Acquire,
+                        &guard,
+                    )
+                    .is_ok()
+                {
+                    return None;
+                }
+
+                drop(unsafe { new_leaf.into_owned() });
+                continue;
+            }
+
+            let old = leaf_ref.value.as_ref().unwrap();
+
+            match f(Some(old)) {
+                Some(value) => {
+                    let new_leaf = Owned::new(Node::leaf(leaf_ref.key.clone(), Some(value)));
+
+                    if edge
+                        .compare_exchange(
+                            record.leaf,
+                            new_leaf,
+                            Ordering::AcqRel,
+                            Ordering::Acquire,
+                            &guard,
+                        )
+                        .is_ok()
+                    {
+                        // the readers may still read the old leaf
+                        let value = old.clone();
+                        unsafe { guard.defer_destroy(record.leaf) };
+
+                        return Some(value);
+                    }
+                }
+                None => {
+                    // flag the edge to the leaf like remove
+                    if edge
+                        .compare_exchange(
+                            record.leaf,
+                            record.leaf.with_tag(FLAG),
+                            Ordering::AcqRel,
+                            Ordering::Acquire,
+                            &guard,
+                        )
+                        .is_ok()
+                    {
+                        let value = old.clone();
+                        self.unlink(key, record, &guard);
+
+                        return Some(value);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<K, V> Drop for LockFreeBST<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = unprotected();
+
+            // the tree may be too deep to drop recursively
+            let mut edges = vec![
+                self.root.left.load(Ordering::Relaxed, guard),
+                self.root.right.load(Ordering::Relaxed, guard),
+            ];
+
+            while let Some(edge) = edges.pop() {
+                let node = edge.with_tag(0).into_owned();
+                let left = node.left.load(Ordering::Relaxed, guard);
+
+                if !left.is_null() {
+                    edges.push(left);
+                    edges.push(node.right.load(Ordering::Relaxed, guard));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,492 @@
+/*
+ Refer to
+ https://dl.acm.org/doi/10.1145/319628.319663
+*/
+
+use std::{
+    borrow::Borrow,
+    mem::{self, MaybeUninit},
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use parking_lot::{RwLock, RwLockWriteGuard};
+
+use super::{slice_insert, slice_remove, B_MAX_NODES, B_MID_INDEX};
+use crate::map::{ConcurrentLookup, ConcurrentMap};
+
+/// Concurrent B+ tree using Lehman-Yao's B-link
+///
+/// Each node has the high key, which is the upper bound(exclusive) of its keys, and the link to its right sibling.
+/// When the node is split, the right half is linked as its sibling before its parent knows it.
+/// So the traversal holds only one node's lock, and moves right if the key is not less than the high key.
+///
+/// The entries are only on the leaves. Like the original algorithm, the nodes are not merged on removing.
+pub struct BLinkTree<K, V> {
+    root: AtomicPtr<Node<K, V>>,
+}
+
+unsafe impl<K: Send, V: Send> Send for BLinkTree<K, V> {}
+unsafe impl<K: Send + Sync, V: Send + Sync> Sync for BLinkTree<K, V> {}
+
+struct Node<K, V> {
+    inner: RwLock<NodeInner<K, V>>,
+}
+
+/// the same layout with btree::Node, but the edges and values are only on the internal nodes and leaves respectively
+struct NodeInner<K, V> {
+    size: usize,
+    depth: usize,
+    keys: [MaybeUninit<K>; B_MAX_NODES],
+    edges: [*mut Node<K, V>; B_MAX_NODES + 1],
+    values: [MaybeUninit<V>; B_MAX_NODES],
+    high_key: Option<K>, // None if it is the rightmost node on its level
+    next: *mut Node<K, V>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(depth: usize) -> Self {
+        Self {
+            inner: RwLock::new(NodeInner {
+                size: 0,
+                depth,
+                keys: unsafe { MaybeUninit::uninit().assume_init() },
+                edges: [ptr::null_mut(); B_MAX_NODES + 1],
+                values: unsafe { MaybeUninit::uninit().assume_init() },
+                high_key: None,
+                next: ptr::null_mut(),
+            }),
+        }
+    }
+}
+
+impl<K, V> NodeInner<K, V> {
+    fn keys(&self) -> &[K] {
+        unsafe { &*(self.keys.get_unchecked(..self.size) as *const [MaybeUninit<K>] as *const [K]) }
+    }
+
+    fn values(&self) -> &[V] {
+        unsafe {
+            &*(self.values.get_unchecked(..self.size) as *const [MaybeUninit<V>] as *const [V])
+        }
+    }
+
+    /// whether the key should be found on the right sibling
+    fn is_over<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        matches!(&self.high_key, Some(high_key) if key >= high_key.borrow())
+    }
+
+    /// the child which may have the key
+    fn child<Q>(&self, key: &Q) -> *mut Node<K, V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let index = self.keys().partition_point(|k| k.borrow() <= key);
+        self.edges[index]
+    }
+
+    /// move the upper half into the new right sibling, and return (separator, right sibling)
+    ///
+    /// The node should be full. The separator is the lower bound of the keys on the right.
+    fn split(&mut self) -> (K, *mut Node<K, V>)
+    where
+        K: Clone,
+    {
+        debug_assert!(self.size == B_MAX_NODES);
+
+        let right = Box::new(Node::new(self.depth));
+        let mut right_inner = right.inner.write();
+
+        let separator = unsafe {
+            if self.depth == 0 {
+                // leaf: [0, mid) and [mid, max), and the separator is copied from the right's first key
+                let count = B_MAX_NODES - B_MID_INDEX;
+
+                ptr::copy_nonoverlapping(
+                    self.keys.as_ptr().add(B_MID_INDEX),
+                    right_inner.keys.as_mut_ptr(),
+                    count,
+                );
+                ptr::copy_nonoverlapping(
+                    self.values.as_ptr().add(B_MID_INDEX),
+                    right_inner.values.as_mut_ptr(),
+                    count,
+                );
+
+                right_inner.size = count;
+                self.size = B_MID_INDEX;
+
+                right_inner.keys()[0].clone()
+            } else {
+                // internal: [0, mid) and [mid + 1, max), and the separator is moved from mid
+                let count = B_MAX_NODES - B_MID_INDEX - 1;
+
+                ptr::copy_nonoverlapping(
+                    self.keys.as_ptr().add(B_MID_INDEX + 1),
+                    right_inner.keys.as_mut_ptr(),
+                    count,
+                );
+                ptr::copy_nonoverlapping(
+                    self.edges.as_ptr().add(B_MID_INDEX + 1),
+                    right_inner.edges.as_mut_ptr(),
+                    count + 1,
+                );
+
+                right_inner.size = count;
+                self.size = B_MID_INDEX;
+
+                self.keys[B_MID_INDEX].as_ptr().read()
+            }
+        };
+
+        right_inner.high_key = self.high_key.replace(separator.clone());
+        right_inner.next = self.next;
+        drop(right_inner);
+
+        self.next = Box::into_raw(right);
+
+        (separator, self.next)
+    }
+
+    /// insert (key, value) into the leaf which is not full
+    fn insert_leaf(&mut self, index: usize, key: K, value: V) {
+        self.size += 1;
+
+        unsafe {
+            slice_insert(&mut self.keys[..self.size], index, MaybeUninit::new(key));
+            slice_insert(
+                &mut self.values[..self.size],
+                index,
+                MaybeUninit::new(value),
+            );
+        }
+    }
+
+    /// remove the entry on the index from the leaf, and return its value
+    fn remove_leaf(&mut self, index: usize) -> V {
+        let size = self.size;
+
+        let value = unsafe {
+            let key = slice_remove(&mut self.keys[..size], index);
+            let value = slice_remove(&mut self.values[..size], index);
+            drop(key.assume_init());
+            value.assume_init()
+        };
+
+        self.size -= 1;
+
+        value
+    }
+
+    /// insert (separator, right child of it) into the internal node which is not full
+    fn insert_internal(&mut self, key: K, edge: *mut Node<K, V>)
+    where
+        K: Ord,
+    {
+        let index = self.keys().partition_point(|k| k < &key);
+        self.size += 1;
+
+        unsafe {
+            slice_insert(&mut self.keys[..self.size], index, MaybeUninit::new(key));
+            slice_insert(&mut self.edges[..(self.size + 1)], index + 1, edge);
+        }
+    }
+}
+
+impl<K, V> Drop for NodeInner<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            for key in self.keys.get_unchecked_mut(..self.size) {
+                key.as_mut_ptr().drop_in_place();
+            }
+
+            if self.depth == 0 {
+                for value in self.values.get_unchecked_mut(..self.size) {
+                    value.as_mut_ptr().drop_in_place();
+                }
+            }
+        }
+    }
+}
+
+impl<K, V> Default for BLinkTree<K, V> {
+    fn default() -> Self {
+        Self {
+            root: AtomicPtr::new(Box::into_raw(Box::new(Node::new(0)))),
+        }
+    }
+}
+
+impl<K: Ord + Clone, V> BLinkTree<K, V> {
+    /// find the node on the depth which may have the key, and push the last visited nodes of upper levels on the stack
+    ///
+    /// The nodes are never deallocated until dropping the tree, so they can be referenced without any lock.
+    fn find_node<'t, Q>(
+        &'t self,
+        key: &Q,
+        depth: usize,
+        stack: &mut Vec<&'t Node<K, V>>,
+    ) -> &'t Node<K, V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut node = unsafe { &*self.root.load(Ordering::Acquire) };
+
+        loop {
+            let inner = node.inner.read();
+
+            if inner.is_over(key) {
+                let next = inner.next;
+                drop(inner);
+
+                node = unsafe { &*next };
+                continue;
+            }
+
+            if inner.depth == depth {
+                return node;
+            }
+
+            let child = inner.child(key);
+            drop(inner);
+
+            stack.push(node);
+            node = unsafe { &*child };
+        }
+    }
+
+    /// lock the node which should have the key on the same level with the node by moving right
+    fn lock_moving_right<'t, Q>(
+        mut node: &'t Node<K, V>,
+        key: &Q,
+    ) -> (&'t Node<K, V>, RwLockWriteGuard<'t, NodeInner<K, V>>)
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        loop {
+            let inner = node.inner.write();
+
+            if !inner.is_over(key) {
+                return (node, inner);
+            }
+
+            let next = inner.next;
+            drop(inner);
+
+            node = unsafe { &*next };
+        }
+    }
+
+    /// insert (key, value) on the index of the locked leaf, and split the leaf and its ancestors if it is full
+    fn insert_entry<'t>(
+        &'t self,
+        (leaf, mut inner): (&'t Node<K, V>, RwLockWriteGuard<'t, NodeInner<K, V>>),
+        index: usize,
+        (key, value): (K, V),
+        stack: Vec<&'t Node<K, V>>,
+    ) {
+        if inner.size < B_MAX_NODES {
+            inner.insert_leaf(index, key, value);
+            return;
+        }
+
+        let (separator, right) = inner.split();
+
+        if key < separator {
+            inner.insert_leaf(index, key, value);
+        } else {
+            let mut right_inner = unsafe { (*right).inner.write() };
+            let index = index - inner.size;
+            right_inner.insert_leaf(index, key, value);
+        }
+
+        self.insert_parent((leaf, inner), (separator, right), stack);
+    }
+
+    /// insert (separator, right) split from the node into its parent, and split the ancestors recursively
+    fn insert_parent<'t>(
+        &'t self,
+        (mut node, mut inner): (&'t Node<K, V>, RwLockWriteGuard<'t, NodeInner<K, V>>),
+        (mut separator, mut right): (K, *mut Node<K, V>),
+        mut stack: Vec<&'t Node<K, V>>,
+    ) {
+        loop {
+            // Since the root is changed only by the thread which holds its lock, check it before unlocking.
+            if ptr::eq(self.root.load(Ordering::Acquire), node) {
+                let root = Box::new(Node::new(inner.depth + 1));
+                let mut root_inner = root.inner.write();
+
+                root_inner.size = 1;
+                root_inner.keys[0] = MaybeUninit::new(separator);
+                root_inner.edges[0] = node as *const _ as *mut _;
+                root_inner.edges[1] = right;
+                drop(root_inner);
+
+                self.root.store(Box::into_raw(root), Ordering::Release);
+                return;
+            }
+
+            let depth = inner.depth + 1;
+            drop(inner);
+
+            // If the stack is empty, the node was the root and the new root is made by another thread.
+            let parent = match stack.pop() {
+                Some(parent) => parent,
+                None => self.find_node(&separator, depth, &mut Vec::new()),
+            };
+
+            let (parent, mut parent_inner) = Self::lock_moving_right(parent, &separator);
+
+            if parent_inner.size < B_MAX_NODES {
+                parent_inner.insert_internal(separator, right);
+                return;
+            }
+
+            let (parent_separator, parent_right) = parent_inner.split();
+
+            if separator < parent_separator {
+                parent_inner.insert_internal(separator, right);
+            } else {
+                unsafe { (*parent_right).inner.write() }.insert_internal(separator, right);
+            }
+
+            node = parent;
+            inner = parent_inner;
+            separator = parent_separator;
+            right = parent_right;
+        }
+    }
+}
+
+impl<K, V> ConcurrentMap<K, V> for BLinkTree<K, V>
+where
+    K: Ord + Clone,
+{
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
+        let mut stack = Vec::new();
+        let leaf = self.find_node(&key, 0, &mut stack);
+        let (leaf, inner) = Self::lock_moving_right(leaf, &key);
+
+        let index = match inner.keys().binary_search(&key) {
+            Ok(_) => return Err((key, value)),
+            Err(index) => index,
+        };
+
+        self.insert_entry((leaf, inner), index, (key, value), stack);
+
+        Ok(())
+    }
+}
+
+impl<K, V, Q> ConcurrentLookup<K, V, Q> for BLinkTree<K, V>
+where
+    K: Ord + Clone + Borrow<Q>,
+    Q: ?Sized + Ord,
+{
+    fn lookup<F, R>(&self, key: &Q, f: F) -> R
+    where
+        F: FnOnce(Option<&V>) -> R,
+    {
+        let mut node = self.find_node(key, 0, &mut Vec::new());
+
+        loop {
+            let inner = node.inner.read();
+
+            if inner.is_over(key) {
+                let next = inner.next;
+                drop(inner);
+
+                node = unsafe { &*next };
+                continue;
+            }
+
+            return match inner.keys().binary_search_by(|k| k.borrow().cmp(key)) {
+                Ok(index) => f(Some(&inner.values()[index])),
+                Err(_) => f(None),
+            };
+        }
+    }
+
+    fn get(&self, key: &Q) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.lookup(key, |value| value.cloned())
+    }
+
+    fn remove(&self, key: &Q) -> Result<V, ()> {
+        let leaf = self.find_node(key, 0, &mut Vec::new());
+        let (_, mut inner) = Self::lock_moving_right(leaf, key);
+
+        let index = inner
+            .keys()
+            .binary_search_by(|k| k.borrow().cmp(key))
+            .map_err(|_| ())?;
+
+        Ok(inner.remove_leaf(index))
+    }
+
+    fn upsert(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+    {
+        let mut value = Some(value);
+
+        // the function is called only once while holding the lock of the leaf
+        self.compute(key, |_| value.take())
+    }
+
+    fn compute<F>(&self, key: &Q, mut f: F) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        let mut stack = Vec::new();
+        let leaf = self.find_node(key, 0, &mut stack);
+        let (leaf, mut inner) = Self::lock_moving_right(leaf, key);
+
+        match inner.keys().binary_search_by(|k| k.borrow().cmp(key)) {
+            Ok(index) => match f(Some(&inner.values()[index])) {
+                Some(value) => {
+                    let old = unsafe { &mut *inner.values[index].as_mut_ptr() };
+                    Some(mem::replace(old, value))
+                }
+                None => Some(inner.remove_leaf(index)),
+            },
+            Err(index) => {
+                let value = f(None)?;
+                self.insert_entry((leaf, inner), index, (key.to_owned(), value), stack);
+
+                None
+            }
+        }
+    }
+}
+
+impl<K, V> Drop for BLinkTree<K, V> {
+    fn drop(&mut self) {
+        fn drop_node<K, V>(node: *mut Node<K, V>) {
+            let mut node = unsafe { Box::from_raw(node) };
+            let inner = node.inner.get_mut();
+
+            if inner.depth > 0 {
+                for edge in &inner.edges[..(inner.size + 1)] {
+                    drop_node(*edge);
+                }
+            }
+        }
+
+        drop_node(*self.root.get_mut());
+    }
+}
@@ -0,0 +1,475 @@
+use std::{
+    borrow::Borrow,
+    cell::RefCell,
+    cmp::Ordering,
+    iter::FromIterator,
+    mem::{self, ManuallyDrop},
+    ptr::{self, NonNull},
+};
+
+use super::{BTree, Cursor, InsertResult, Node, SearchResult, B_MAX_NODES};
+use crate::map::SequentialMap;
+
+/// (depth, (key, value)s, edges) which are moved out of the node
+type Parts<K, V> = (usize, Vec<(K, V)>, Vec<Box<Node<K, V>>>);
+
+impl<K, V> Node<K, V> {
+    /// make the node from (key, value)s and the edges, where the edges are empty for the leaf
+    fn from_parts(depth: usize, entries: Vec<(K, V)>, edges: Vec<Box<Node<K, V>>>) -> Box<Self> {
+        debug_assert!(entries.len() <= B_MAX_NODES);
+        debug_assert!(depth == 0 || edges.len() == entries.len() + 1);
+
+        let mut node = Box::new(Self::new());
+        node.depth = depth;
+        node.size = entries.len();
+
+        unsafe {
+            for (index, (key, value)) in entries.into_iter().enumerate() {
+                ptr::write(node.keys.as_mut_ptr().add(index), key);
+                ptr::write(node.values.as_mut_ptr().add(index), value);
+            }
+
+            for (index, edge) in edges.into_iter().enumerate() {
+                ptr::write(node.edges.as_mut_ptr().add(index), edge);
+            }
+        }
+
+        node
+    }
+
+    /// move out (key, value)s and the edges, and deallocate the node without dropping it
+    fn into_parts(self: Box<Self>) -> Parts<K, V> {
+        unsafe {
+            let node = Box::from_raw(Box::into_raw(self) as *mut ManuallyDrop<Node<K, V>>);
+
+            let entries = (0..node.size)
+                .map(|index| {
+                    (
+                        ptr::read(node.keys.as_ptr().add(index)),
+                        ptr::read(node.values.as_ptr().add(index)),
+                    )
+                })
+                .collect();
+
+            let edges = if node.depth > 0 {
+                (0..=node.size)
+                    .map(|index| ptr::read(node.edges.as_ptr().add(index)))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            (node.depth, entries, edges)
+        }
+    }
+
+    /// make the subtree from the parts of the node
+    ///
+    /// If there is no (key, value) on the internal node, the subtree is its only edge.
+    fn subtree(depth: usize, entries: Vec<(K, V)>, mut edges: Vec<Box<Node<K, V>>>) -> Box<Self> {
+        if depth > 0 && entries.is_empty() {
+            edges.pop().unwrap()
+        } else {
+            Self::from_parts(depth, entries, edges)
+        }
+    }
+
+    /// the number of (key, value)s on the subtree
+    fn count(&self) -> usize {
+        self.size + self.edges().iter().map(|edge| edge.count()).sum::<usize>()
+    }
+
+    /// build the subtree of the depth from the next count (key, value)s of the sorted iterator
+    ///
+    /// The (key, value)s are distributed evenly on the least edges, so every node except the root is at least half full.
+    fn build<I: Iterator<Item = (K, V)>>(entries: &mut I, count: usize, depth: usize) -> Box<Self> {
+        if depth == 0 {
+            return Self::from_parts(0, entries.take(count).collect(), Vec::new());
+        }
+
+        // the maximum number of (key, value)s on the edge, which has B_MAX_NODES + 1 edges on each depth
+        let capacity = (B_MAX_NODES + 1).saturating_pow(depth as u32) - 1;
+        let num_edges = ((count + 1 + capacity) / (capacity + 1)).max(2);
+        let num_children = count - (num_edges - 1);
+
+        let mut separators = Vec::with_capacity(num_edges - 1);
+        let mut edges = Vec::with_capacity(num_edges);
+
+        for index in 0..num_edges {
+            let child_count =
+                num_children / num_edges + (index < num_children % num_edges) as usize;
+            edges.push(Self::build(entries, child_count, depth - 1));
+
+            if index + 1 < num_edges {
+                separators.push(entries.next().unwrap());
+            }
+        }
+
+        Self::from_parts(depth, separators, edges)
+    }
+}
+
+impl<K: Ord, V> BTree<K, V> {
+    fn from_root(root: Box<Node<K, V>>, size: usize) -> Self {
+        let root = Box::leak(root).into();
+
+        Self {
+            root,
+            size,
+            cursor: RefCell::new(Cursor::new(root)),
+        }
+    }
+
+    fn into_root(self) -> Box<Node<K, V>> {
+        let mut tree = ManuallyDrop::new(self);
+
+        unsafe {
+            ptr::drop_in_place(&mut tree.cursor);
+            Box::from_raw(tree.root.as_ptr())
+        }
+    }
+
+    fn depth(&self) -> usize {
+        unsafe { self.root.as_ref().depth }
+    }
+
+    fn first_key(&self) -> Option<&K> {
+        let mut node = unsafe { self.root.as_ref() };
+
+        while node.depth > 0 {
+            node = node.edges().first().unwrap();
+        }
+
+        node.keys().first()
+    }
+
+    fn last_key(&self) -> Option<&K> {
+        let mut node = unsafe { self.root.as_ref() };
+
+        while node.depth > 0 {
+            node = node.edges().last().unwrap();
+        }
+
+        node.keys().last()
+    }
+
+    /// Since the root is emptied only when the tree has no (key, value), it does not use the size.
+    fn is_empty_root(&self) -> bool {
+        unsafe { self.root.as_ref().size == 0 }
+    }
+
+    /// build the tree from (key, value)s which are sorted by the key without duplication
+    fn build(entries: Vec<(K, V)>) -> Self {
+        let size = entries.len();
+        let mut depth = 0;
+
+        while (B_MAX_NODES + 1).saturating_pow(depth as u32 + 1) - 1 < size {
+            depth += 1;
+        }
+
+        let root = Node::build(&mut entries.into_iter(), size, depth);
+        Self::from_root(root, size)
+    }
+
+    /// Build the tree from (key, value)s which are sorted by the key in strictly ascending order.
+    ///
+    /// It builds the nodes from the bottom without any split, so it is much faster than inserting one by one.
+    /// Panic if the keys are not strictly ascending.
+    pub fn from_sorted_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let entries: Vec<_> = iter.into_iter().collect();
+
+        assert!(
+            entries.windows(2).all(|two| two[0].0 < two[1].0),
+            "The keys should be strictly ascending."
+        );
+
+        Self::build(entries)
+    }
+
+    /// insert (key, value) without updating the size
+    fn insert_raw(&mut self, key: K, value: V) {
+        match self.find_mut(&key) {
+            SearchResult::Some { .. } => unreachable!("The key should not exist on the tree."),
+            SearchResult::None { edge_index } => self.insert_recursive(edge_index, key, value),
+        }
+
+        self.clear();
+    }
+
+    /// insert (key, value) and the edge on its right at the edge_index of the node on the cursor
+    ///
+    /// The splitted nodes are merged into the ancestors on the cursor like `insert_recursive`.
+    fn insert_edge_recursive(
+        &mut self,
+        edge_index: usize,
+        key: K,
+        value: V,
+        edge: Box<Node<K, V>>,
+    ) {
+        let mut cursor = self.cursor.borrow_mut();
+        let current = unsafe { cursor.current.as_mut() };
+
+        let mut splitted = match current.insert_inner(edge_index, key, value, edge) {
+            InsertResult::Fitted => return,
+            InsertResult::Splitted { parent, right } => (parent, right),
+        };
+
+        while let Some((mut ancestor, index)) = cursor.ancestors.pop() {
+            let ancestor = unsafe { ancestor.as_mut() };
+
+            let ((key, value), edge) = splitted;
+            splitted = match ancestor.insert_inner(index, key, value, edge) {
+                InsertResult::Fitted => return,
+                InsertResult::Splitted { parent, right } => (parent, right),
+            };
+        }
+
+        let ((key, value), edge) = splitted;
+        let old_root = unsafe { Box::from_raw(self.root.as_ptr()) };
+        let depth = old_root.depth + 1;
+
+        self.root = Box::leak(Node::from_parts(
+            depth,
+            vec![(key, value)],
+            vec![old_root, edge],
+        ))
+        .into();
+    }
+
+    /// join two trees by the separator, where the keys of the left < the separator < the keys of the right
+    ///
+    /// The shorter tree is connected to the taller one as a whole subtree. The size is not updated.
+    fn join(mut left: Self, separator: (K, V), mut right: Self) -> Self {
+        let (key, value) = separator;
+
+        if left.is_empty_root() {
+            right.insert_raw(key, value);
+            drop(left);
+            return right;
+        }
+
+        if right.is_empty_root() {
+            left.insert_raw(key, value);
+            drop(right);
+            return left;
+        }
+
+        match left.depth().cmp(&right.depth()) {
+            Ordering::Equal => {
+                let depth = left.depth() + 1;
+                let root = Node::from_parts(
+                    depth,
+                    vec![(key, value)],
+                    vec![left.into_root(), right.into_root()],
+                );
+
+                Self::from_root(root, 0)
+            }
+            Ordering::Greater => {
+                // connect the right on the rightmost node whose depth is one more than the right
+                let depth = right.depth();
+
+                {
+                    let mut cursor = left.cursor.borrow_mut();
+
+                    while unsafe { cursor.current.as_ref().depth } > depth + 1 {
+                        let current = cursor.current;
+                        let node = unsafe { &mut *current.as_ptr() };
+
+                        cursor.ancestors.push((current, node.size));
+                        cursor.current =
+                            NonNull::from(node.mut_edges().last_mut().unwrap().as_mut());
+                    }
+                }
+
+                let edge_index = unsafe { left.cursor.borrow().current.as_ref().size };
+                left.insert_edge_recursive(edge_index, key, value, right.into_root());
+                left.clear();
+                left
+            }
+            Ordering::Less => {
+                // connect the left on the leftmost node whose depth is one more than the left
+                let depth = left.depth();
+
+                {
+                    let mut cursor = right.cursor.borrow_mut();
+
+                    while unsafe { cursor.current.as_ref().depth } > depth + 1 {
+                        let current = cursor.current;
+                        let node = unsafe { &mut *current.as_ptr() };
+
+                        cursor.ancestors.push((current, 0));
+                        cursor.current =
+                            NonNull::from(node.mut_edges().first_mut().unwrap().as_mut());
+                    }
+                }
+
+                // the left becomes the first edge, and the old first edge is inserted on its right
+                let first = unsafe {
+                    let current = right.cursor.borrow().current;
+                    mem::replace(&mut (*current.as_ptr()).mut_edges()[0], left.into_root())
+                };
+
+                right.insert_edge_recursive(0, key, value, first);
+                right.clear();
+                right
+            }
+        }
+    }
+
+    /// split the subtree into the left one whose keys are less than the key and the right one
+    ///
+    /// The whole edges on the left or right of the key are moved into each tree, and the edge which
+    /// may have the key is splitted recursively. The size is not updated.
+    fn split_node<Q>(node: Box<Node<K, V>>, key: &Q) -> (Self, Self)
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let (depth, mut entries, mut edges) = node.into_parts();
+
+        let (index, found) = match entries.binary_search_by(|(k, _)| k.borrow().cmp(key)) {
+            Ok(index) => (index, true),
+            Err(index) => (index, false),
+        };
+
+        let mut right_entries = entries.split_off(index);
+
+        if depth == 0 {
+            return (
+                Self::from_root(Node::from_parts(0, entries, Vec::new()), 0),
+                Self::from_root(Node::from_parts(0, right_entries, Vec::new()), 0),
+            );
+        }
+
+        let right_edges = edges.split_off(index + 1);
+        let middle = edges.pop().unwrap();
+
+        if found {
+            // the middle edge is less than the key, and the key is the first of the right
+            edges.push(middle);
+
+            let first = right_entries.remove(0);
+            let left = Self::from_root(Node::subtree(depth, entries, edges), 0);
+            let mut right = Self::from_root(Node::subtree(depth, right_entries, right_edges), 0);
+
+            right.insert_raw(first.0, first.1);
+            return (left, right);
+        }
+
+        let (middle_left, middle_right) = Self::split_node(middle, key);
+
+        let left = if let Some(separator) = entries.pop() {
+            let left = Self::from_root(Node::subtree(depth, entries, edges), 0);
+            Self::join(left, separator, middle_left)
+        } else {
+            middle_left
+        };
+
+        let right = if right_entries.is_empty() {
+            middle_right
+        } else {
+            let separator = right_entries.remove(0);
+            let right = Self::from_root(Node::subtree(depth, right_entries, right_edges), 0);
+            Self::join(middle_right, separator, right)
+        };
+
+        (left, right)
+    }
+
+    /// Move all (key, value)s of the other into the tree, and the other becomes empty.
+    ///
+    /// If the keys of one tree are all less than the other, the shorter tree is connected to the taller
+    /// as a whole subtree. Otherwise, both are merged and built again from the bottom.
+    /// Like inserting one by one, the value of the tree is kept if the other has the same key.
+    pub fn append(&mut self, other: &mut Self) {
+        if other.is_empty() {
+            return;
+        }
+
+        if self.is_empty() {
+            mem::swap(self, other);
+            return;
+        }
+
+        let mut left = mem::replace(self, Self::new());
+        let mut right = mem::replace(other, Self::new());
+
+        if left.last_key() < right.first_key() {
+            let size = left.size + right.size;
+            let separator = right.pop_first_raw();
+
+            *self = Self::join(left, separator, right);
+            self.size = size;
+        } else if right.last_key() < left.first_key() {
+            let size = left.size + right.size;
+            let separator = left.pop_first_raw();
+
+            *self = Self::join(right, separator, left);
+            self.size = size;
+        } else {
+            let mut entries = Vec::with_capacity(left.size + right.size);
+            let mut left = left.into_iter().peekable();
+            let mut right = right.into_iter().peekable();
+
+            loop {
+                let order = match (left.peek(), right.peek()) {
+                    (Some((l, _)), Some((r, _))) => l.cmp(r),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => break,
+                };
+
+                match order {
+                    Ordering::Less => entries.push(left.next().unwrap()),
+                    Ordering::Greater => entries.push(right.next().unwrap()),
+                    Ordering::Equal => {
+                        entries.push(left.next().unwrap());
+                        right.next();
+                    }
+                }
+            }
+
+            *self = Self::build(entries);
+        }
+    }
+
+    /// Split the tree into two at the key, and return the one which has the key and the greater keys.
+    ///
+    /// Only the nodes on the path to the key are splitted, and the other nodes are moved as whole subtrees.
+    pub fn split_off<Q>(&mut self, key: &Q) -> Self
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let size = self.size;
+        let root = mem::replace(self, Self::new()).into_root();
+
+        let (mut left, mut right) = Self::split_node(root, key);
+
+        // count the shorter one, which has less nodes to visit
+        if left.depth() <= right.depth() {
+            left.size = unsafe { left.root.as_ref() }.count();
+            right.size = size - left.size;
+        } else {
+            right.size = unsafe { right.root.as_ref() }.count();
+            left.size = size - right.size;
+        }
+
+        *self = left;
+        right
+    }
+}
+
+/// Like inserting one by one, the first value is kept if the key is duplicated.
+impl<K: Ord, V> FromIterator<(K, V)> for BTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut entries: Vec<_> = iter.into_iter().collect();
+
+        // the stable sort keeps the order of the duplicated keys, and dedup keeps the first one
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries.dedup_by(|a, b| a.0 == b.0);
+
+        Self::build(entries)
+    }
+}
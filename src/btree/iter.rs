@@ -0,0 +1,298 @@
+use std::{
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ops::{Bound, RangeBounds},
+    ptr::{self, NonNull},
+    vec,
+};
+
+use super::{BTree, Node};
+
+/// in-order traversal like the cursor of BTree
+///
+/// The stack has (node, index of the key to visit on next) from the root to the node which has the next entry.
+struct RawIter<K, V> {
+    stack: Vec<(NonNull<Node<K, V>>, usize)>,
+}
+
+impl<K, V> RawIter<K, V> {
+    /// get the edge of the node without making any reference of the node
+    unsafe fn edge(node: NonNull<Node<K, V>>, index: usize) -> NonNull<Node<K, V>> {
+        let edges = ptr::addr_of!((*node.as_ptr()).edges) as *const Box<Node<K, V>>;
+        NonNull::from(&**edges.add(index))
+    }
+
+    /// push the leftmost path of the subtree whose root is node
+    fn push_leftmost(&mut self, mut node: NonNull<Node<K, V>>) {
+        loop {
+            self.stack.push((node, 0));
+
+            if unsafe { node.as_ref() }.depth == 0 {
+                return;
+            }
+
+            node = unsafe { Self::edge(node, 0) };
+        }
+    }
+
+    /// push the path to the first key satisfying the bound
+    fn push_lower_bound(&mut self, mut node: NonNull<Node<K, V>>, bound: Bound<&K>)
+    where
+        K: Ord,
+    {
+        loop {
+            let current = unsafe { node.as_ref() };
+
+            let (index, found) = match bound {
+                Bound::Included(key) => match current.keys().binary_search(key) {
+                    Ok(index) => (index, true),
+                    Err(index) => (index, false),
+                },
+                Bound::Excluded(key) => match current.keys().binary_search(key) {
+                    Ok(index) => (index + 1, false),
+                    Err(index) => (index, false),
+                },
+                Bound::Unbounded => (0, false),
+            };
+
+            self.stack.push((node, index));
+
+            if found || current.depth == 0 {
+                return;
+            }
+
+            node = unsafe { Self::edge(node, index) };
+        }
+    }
+
+    /// return (node, index) of the next entry
+    fn next(&mut self) -> Option<(NonNull<Node<K, V>>, usize)> {
+        loop {
+            let (node, index) = self.stack.last_mut()?;
+            let (node, current) = (*node, *index);
+            let node_ref = unsafe { node.as_ref() };
+
+            if current < node_ref.size {
+                *index += 1;
+
+                if node_ref.depth > 0 {
+                    self.push_leftmost(unsafe { Self::edge(node, current + 1) });
+                }
+
+                return Some((node, current));
+            }
+
+            self.stack.pop();
+        }
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    raw: RawIter<K, V>,
+    _marker: PhantomData<&'a BTree<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, index) = self.raw.next()?;
+        let node = unsafe { &*node.as_ptr() };
+
+        Some((&node.keys[index], &node.values[index]))
+    }
+}
+
+pub struct IterMut<'a, K, V> {
+    raw: RawIter<K, V>,
+    _marker: PhantomData<&'a mut BTree<K, V>>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, index) = self.raw.next()?;
+
+        // Since the values returned before are still alive, do not make any reference of the whole node.
+        unsafe {
+            let keys = ptr::addr_of!((*node.as_ptr()).keys) as *const K;
+            let values = ptr::addr_of_mut!((*node.as_ptr()).values) as *mut V;
+
+            Some((&*keys.add(index), &mut *values.add(index)))
+        }
+    }
+}
+
+/// Since the nodes should be forgotten, the entries are moved out before iterating.
+pub struct IntoIter<K, V> {
+    entries: vec::IntoIter<(K, V)>,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+pub struct Keys<'a, K, V> {
+    iter: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(key, _)| key)
+    }
+}
+
+pub struct Values<'a, K, V> {
+    iter: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, value)| value)
+    }
+}
+
+/// in-order iterator on the range, which starts from the lower bound found by descending
+pub struct Range<'a, K, V, R> {
+    iter: Iter<'a, K, V>,
+    range: R,
+}
+
+impl<'a, K: Ord, V, R: RangeBounds<K>> Iterator for Range<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.iter.next()?;
+
+        let over = match self.range.end_bound() {
+            Bound::Included(end) => key > end,
+            Bound::Excluded(end) => key >= end,
+            Bound::Unbounded => false,
+        };
+
+        if over {
+            self.iter.raw.stack.clear();
+            return None;
+        }
+
+        Some((key, value))
+    }
+}
+
+impl<K, V> BTree<K, V> {
+    /// iterate (key, value) in the ascending order of the key
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut raw = RawIter { stack: Vec::new() };
+        raw.push_leftmost(self.root);
+
+        Iter {
+            raw,
+            _marker: PhantomData,
+        }
+    }
+
+    /// iterate (key, mutable value) in the ascending order of the key
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let mut raw = RawIter { stack: Vec::new() };
+        raw.push_leftmost(self.root);
+
+        IterMut {
+            raw,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { iter: self.iter() }
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { iter: self.iter() }
+    }
+
+    /// iterate (key, value) whose key is in the range in the ascending order of the key
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, R>
+    where
+        K: Ord,
+    {
+        let mut raw = RawIter { stack: Vec::new() };
+        raw.push_lower_bound(self.root, range.start_bound());
+
+        Range {
+            iter: Iter {
+                raw,
+                _marker: PhantomData,
+            },
+            range,
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a BTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut BTree<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V> IntoIterator for BTree<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        /// move out the entries in order, and deallocate the node without dropping it
+        fn drain<K, V>(node: NonNull<Node<K, V>>, entries: &mut Vec<(K, V)>) {
+            unsafe {
+                let node = Box::from_raw(node.as_ptr() as *mut ManuallyDrop<Node<K, V>>);
+
+                for index in 0..=node.size {
+                    if node.depth > 0 {
+                        let edge = ptr::read(node.edges.as_ptr().add(index));
+                        drain(NonNull::from(Box::leak(edge)), entries);
+                    }
+
+                    if index < node.size {
+                        entries.push((
+                            ptr::read(node.keys.as_ptr().add(index)),
+                            ptr::read(node.values.as_ptr().add(index)),
+                        ));
+                    }
+                }
+            }
+        }
+
+        let mut tree = ManuallyDrop::new(self);
+        let mut entries = Vec::with_capacity(tree.size);
+
+        unsafe { ptr::drop_in_place(&mut tree.cursor) };
+        drain(tree.root, &mut entries);
+
+        IntoIter {
+            entries: entries.into_iter(),
+        }
+    }
+}
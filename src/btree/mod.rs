@@ -1,9 +1,17 @@
+mod blink;
+mod bulk;
+mod iter;
+
+pub use blink::BLinkTree;
+pub use iter::{IntoIter, Iter, IterMut, Keys, Range, Values};
+
+use std::borrow::Borrow;
 use std::cell::RefCell;
 use std::fmt::Debug;
 use std::ptr;
 use std::{cmp::Ordering, mem, ptr::NonNull};
 
-use crate::map::SequentialMap;
+use crate::map::{OrderedMap, SequentialLookup, SequentialMap};
 
 const B_MAX_NODES: usize = 11;
 const B_MID_INDEX: usize = B_MAX_NODES / 2;
@@ -461,11 +469,15 @@ impl<K: Ord, V> Cursor<K, V> {
         }
     }
 
-    fn search_in_node(&self, key: &K) -> InnerSearchResult {
+    fn search_in_node<Q>(&self, key: &Q) -> InnerSearchResult
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
         let node = unsafe { self.current.as_ref() };
 
         for (index, k) in node.keys().iter().enumerate() {
-            match key.cmp(k) {
+            match key.cmp(k.borrow()) {
                 Ordering::Less => return InnerSearchResult::Descent { edge_index: index },
                 Ordering::Equal => return InnerSearchResult::Some { value_index: index },
                 Ordering::Greater => {}
@@ -522,6 +534,9 @@ pub struct BTree<K, V> {
     cursor: RefCell<Cursor<K, V>>,
 }
 
+// The tree owns its nodes. It is not Sync since lookups move the cursor.
+unsafe impl<K: Send, V: Send> Send for BTree<K, V> {}
+
 impl<K: Debug, V: Debug> Debug for BTree<K, V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         unsafe {
@@ -540,6 +555,17 @@ impl<K, V> Drop for BTree<K, V> {
     }
 }
 
+impl<K, V> BTree<K, V> {
+    /// the number of keys on the tree
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+}
+
 impl<K: Ord, V> BTree<K, V> {
     fn clear(&self) {
         let mut cursor = self.cursor.borrow_mut();
@@ -547,7 +573,11 @@ impl<K: Ord, V> BTree<K, V> {
         cursor.current = self.root;
     }
 
-    fn find_mut(&self, key: &K) -> SearchResult {
+    fn find_mut<Q>(&self, key: &Q) -> SearchResult
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
         let mut cursor = self.cursor.borrow_mut();
 
         loop {
@@ -565,7 +595,11 @@ impl<K: Ord, V> BTree<K, V> {
         }
     }
 
-    fn find(&self, key: &K) -> SearchResult {
+    fn find<Q>(&self, key: &Q) -> SearchResult
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
         let mut cursor = self.cursor.borrow_mut();
 
         loop {
@@ -624,14 +658,17 @@ impl<K: Ord, V> BTree<K, V> {
         self.root = Box::leak(root).into();
     }
 
-    fn remove_recursive(&mut self, value_index: usize) -> V {
+    /// remove (key, value) on the cursor and return it
+    fn remove_recursive(&mut self, value_index: usize) -> (K, V) {
         let mut cursor = self.cursor.borrow_mut();
         let current = unsafe { cursor.current.as_mut() };
 
         let value = if current.depth == 0 {
             let value = unsafe {
-                let _ = slice_remove(current.mut_keys(), value_index);
-                slice_remove(current.mut_values(), value_index)
+                (
+                    slice_remove(current.mut_keys(), value_index),
+                    slice_remove(current.mut_values(), value_index),
+                )
             };
 
             current.size -= 1;
@@ -661,10 +698,10 @@ impl<K: Ord, V> BTree<K, V> {
 
                     predecessor.size -= 1;
 
-                    ptr::write(current.keys.as_mut_ptr().add(value_index), swapped_k);
+                    let key = ptr::replace(current.keys.as_mut_ptr().add(value_index), swapped_k);
                     let value = mem::replace(&mut current.values[value_index], swapped_v);
 
-                    return value;
+                    return (key, value);
                 };
             } else {
                 let successor_edge = unsafe {
@@ -679,9 +716,10 @@ impl<K: Ord, V> BTree<K, V> {
 
                     successor.size -= 1;
 
-                    ptr::write(current.keys.as_mut_ptr().add(value_index), swapped_k);
+                    let key = ptr::replace(current.keys.as_mut_ptr().add(value_index), swapped_k);
+                    let value = mem::replace(&mut current.values[value_index], swapped_v);
 
-                    mem::replace(&mut current.values[value_index], swapped_v)
+                    (key, value)
                 };
 
                 if successor.size > 0 {
@@ -1062,7 +1100,55 @@ impl<K: Ord, V> BTree<K, V> {
     }
 }
 
-impl<K: Ord + Clone, V> SequentialMap<K, V> for BTree<K, V> {
+impl<K: Ord, V> BTree<K, V> {
+    /// remove the first (key, value) on the non-empty tree without updating the size
+    fn pop_first_raw(&mut self) -> (K, V) {
+        let (ancestors, leaf) = unsafe { self.root.as_mut() }.find_begin();
+
+        {
+            let mut cursor = self.cursor.borrow_mut();
+            cursor.ancestors = ancestors;
+            cursor.current = NonNull::from(leaf);
+        }
+
+        let entry = self.remove_recursive(0);
+        self.clear();
+        entry
+    }
+
+    /// remove the last (key, value) on the non-empty tree without updating the size
+    fn pop_last_raw(&mut self) -> (K, V) {
+        let (ancestors, leaf) = unsafe { self.root.as_mut() }.find_end();
+        let value_index = leaf.size - 1;
+
+        {
+            let mut cursor = self.cursor.borrow_mut();
+            cursor.ancestors = ancestors;
+            cursor.current = NonNull::from(leaf);
+        }
+
+        let entry = self.remove_recursive(value_index);
+        self.clear();
+        entry
+    }
+
+    /// get the leaf node which has the least key if first, or the greatest key if not
+    fn end_leaf(&self, first: bool) -> &Node<K, V> {
+        let mut node = unsafe { self.root.as_ref() };
+
+        while node.depth > 0 {
+            node = if first {
+                node.edges().first().unwrap()
+            } else {
+                node.edges().last().unwrap()
+            };
+        }
+
+        node
+    }
+}
+
+impl<K: Ord, V> SequentialMap<K, V> for BTree<K, V> {
     fn new() -> Self {
         let root = Box::leak(Box::new(Node::new())).into();
 
@@ -1073,11 +1159,11 @@ impl<K: Ord + Clone, V> SequentialMap<K, V> for BTree<K, V> {
         }
     }
 
-    fn insert(&mut self, key: &K, value: V) -> Result<(), V> {
-        let result = match self.find_mut(key) {
-            SearchResult::Some { .. } => Err(value),
+    fn insert(&mut self, key: K, value: V) -> Result<(), (K, V)> {
+        let result = match self.find_mut(&key) {
+            SearchResult::Some { .. } => Err((key, value)),
             SearchResult::None { edge_index } => {
-                self.insert_recursive(edge_index, key.clone(), value);
+                self.insert_recursive(edge_index, key, value);
                 self.size += 1;
                 Ok(())
             }
@@ -1086,8 +1172,14 @@ impl<K: Ord + Clone, V> SequentialMap<K, V> for BTree<K, V> {
         self.clear();
         result
     }
+}
 
-    fn lookup(&self, key: &K) -> Option<&V> {
+impl<K: Ord, V, Q> SequentialLookup<K, V, Q> for BTree<K, V>
+where
+    K: Borrow<Q>,
+    Q: ?Sized + Ord,
+{
+    fn lookup(&self, key: &Q) -> Option<&V> {
         let result = match self.find(key) {
             SearchResult::Some { value_index } => unsafe {
                 let value = Some(&self.cursor.borrow().current.as_ref().values[value_index]);
@@ -1100,10 +1192,10 @@ impl<K: Ord + Clone, V> SequentialMap<K, V> for BTree<K, V> {
         result
     }
 
-    fn remove(&mut self, key: &K) -> Result<V, ()> {
+    fn remove(&mut self, key: &Q) -> Result<V, ()> {
         let result = match self.find_mut(key) {
             SearchResult::Some { value_index } => {
-                let value = self.remove_recursive(value_index);
+                let (_, value) = self.remove_recursive(value_index);
                 self.size -= 1;
                 Ok(value)
             }
@@ -1114,3 +1206,87 @@ impl<K: Ord + Clone, V> SequentialMap<K, V> for BTree<K, V> {
         result
     }
 }
+
+impl<K: Ord, V> OrderedMap<K, V> for BTree<K, V> {
+    fn first(&self) -> Option<(&K, &V)> {
+        let leaf = self.end_leaf(true);
+        leaf.keys().first().zip(leaf.values().first())
+    }
+
+    fn last(&self) -> Option<(&K, &V)> {
+        let leaf = self.end_leaf(false);
+        leaf.keys().last().zip(leaf.values().last())
+    }
+
+    fn pop_first(&mut self) -> Option<(K, V)> {
+        if self.size == 0 {
+            return None;
+        }
+
+        self.size -= 1;
+        Some(self.pop_first_raw())
+    }
+
+    fn pop_last(&mut self) -> Option<(K, V)> {
+        if self.size == 0 {
+            return None;
+        }
+
+        self.size -= 1;
+        Some(self.pop_last_raw())
+    }
+
+    fn floor<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut node = unsafe { self.root.as_ref() };
+        let mut floor = None;
+
+        loop {
+            let index = match node.keys().binary_search_by(|k| k.borrow().cmp(key)) {
+                Ok(index) => return Some((&node.keys()[index], &node.values()[index])),
+                Err(index) => index,
+            };
+
+            // the greatest key less than the key on the node
+            if index > 0 {
+                floor = Some((&node.keys()[index - 1], &node.values()[index - 1]));
+            }
+
+            if node.depth == 0 {
+                return floor;
+            }
+
+            node = &node.edges()[index];
+        }
+    }
+
+    fn ceiling<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut node = unsafe { self.root.as_ref() };
+        let mut ceiling = None;
+
+        loop {
+            let index = match node.keys().binary_search_by(|k| k.borrow().cmp(key)) {
+                Ok(index) => return Some((&node.keys()[index], &node.values()[index])),
+                Err(index) => index,
+            };
+
+            // the least key greater than the key on the node
+            if index < node.size {
+                ceiling = Some((&node.keys()[index], &node.values()[index]));
+            }
+
+            if node.depth == 0 {
+                return ceiling;
+            }
+
+            node = &node.edges()[index];
+        }
+    }
+}
@@ -0,0 +1,567 @@
+/*
+ Refer to
+ https://www.cs.cmu.edu/~dga/papers/cuckoo-eurosys14.pdf and
+ https://github.com/efficient/libcuckoo
+*/
+
+use std::{
+    borrow::Borrow,
+    collections::{hash_map::RandomState, VecDeque},
+    hash::{BuildHasher, Hash, Hasher},
+    mem::ManuallyDrop,
+    sync::atomic::Ordering,
+};
+
+use crossbeam_epoch::{pin, unprotected, Atomic, Guard, Owned, Shared};
+use crossbeam_utils::CachePadded;
+
+use crate::lock::seqlock::WriteGuard;
+use crate::lock::SeqLock;
+use crate::map::{ConcurrentLookup, ConcurrentMap};
+use crate::some_or;
+
+/// the number of slots per bucket
+const SLOTS: usize = 4;
+/// the number of lock stripes, which is also the number of buckets on initialization
+const LOCKS: usize = 256;
+/// the max number of buckets visited by BFS to find the cuckoo path
+const MAX_BFS: usize = 256;
+
+/// Bucketized cuckoo hash map
+///
+/// The key is in one of two buckets by two hash functions, and each bucket has several slots.
+/// If both buckets are full, the entries are moved to their alternate buckets along the path found by BFS.
+///
+/// The bucket i is guarded by the seqlock of the stripe (i % LOCKS). The updater locks the stripes of the buckets,
+/// while the lookup reads the buckets optimistically and validates their versions without writing any lock.
+/// Resizing locks all stripes in order, then moves all entries to the new table.
+/// The value of the removed entry may still be read by the lookups, so it is dropped with the entry,
+/// and the remove returns its clone.
+pub struct CuckooHashMap<K, V> {
+    locks: Box<[CachePadded<SeqLock<()>>]>,
+    table: Atomic<Table<K, V>>,
+    hash_builder: RandomState,
+}
+
+struct Table<K, V> {
+    buckets: Box<[Bucket<K, V>]>,
+}
+
+type Bucket<K, V> = [Atomic<Entry<K, V>>; SLOTS];
+
+struct Entry<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+/// the slot which has the entry
+///
+/// On the cuckoo path, it is a hop which moves the entry into its alternate bucket.
+struct Slot<'g, K, V> {
+    bucket: usize,
+    slot: usize,
+    entry: Shared<'g, Entry<K, V>>,
+}
+
+/// the node of BFS to find the cuckoo path
+struct BfsNode<'g, K, V> {
+    bucket: usize,
+    parent: Option<(usize, Slot<'g, K, V>)>, // (index of the parent node, the hop from the parent)
+}
+
+/// the first hash value. The bucket is the lower bits of it.
+#[inline]
+fn primary(hash: u64) -> usize {
+    hash as usize
+}
+
+/// the second hash value, which is independent of the lower bits of the first one
+#[inline]
+fn alternate(hash: u64) -> usize {
+    hash.rotate_left(32).wrapping_mul(0x9E37_79B9_7F4A_7C15) as usize
+}
+
+impl<K, V> Table<K, V> {
+    fn new(size: usize) -> Self {
+        Self {
+            buckets: (0..size).map(|_| Default::default()).collect(),
+        }
+    }
+
+    /// (primary bucket, alternate bucket) of the hash
+    #[inline]
+    fn buckets_of(&self, hash: u64) -> (usize, usize) {
+        let mask = self.buckets.len() - 1;
+
+        (primary(hash) & mask, alternate(hash) & mask)
+    }
+
+    /// the other bucket of the entry which is on the bucket
+    #[inline]
+    fn other_bucket(&self, hash: u64, bucket: usize) -> usize {
+        let (b1, b2) = self.buckets_of(hash);
+
+        if bucket == b1 {
+            b2
+        } else {
+            b1
+        }
+    }
+
+    /// find the empty slot of the bucket
+    fn empty_slot(&self, bucket: usize, guard: &Guard) -> Option<usize> {
+        self.buckets[bucket]
+            .iter()
+            .position(|slot| slot.load(Ordering::Acquire, guard).is_null())
+    }
+}
+
+impl<K: Eq, V> Table<K, V> {
+    /// find the slot of the key on the buckets
+    fn find<'g, Q>(&self, hash: u64, key: &Q, guard: &'g Guard) -> Option<Slot<'g, K, V>>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let (b1, b2) = self.buckets_of(hash);
+
+        for bucket in [b1, b2] {
+            for (slot, link) in self.buckets[bucket].iter().enumerate() {
+                let entry = link.load(Ordering::Acquire, guard);
+
+                if let Some(entry_ref) = unsafe { entry.as_ref() } {
+                    if entry_ref.hash == hash && entry_ref.key.borrow() == key {
+                        return Some(Slot {
+                            bucket,
+                            slot,
+                            entry,
+                        });
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl<K, V> Default for CuckooHashMap<K, V> {
+    fn default() -> Self {
+        Self {
+            locks: (0..LOCKS)
+                .map(|_| CachePadded::new(SeqLock::new(())))
+                .collect(),
+            table: Atomic::new(Table::new(LOCKS)),
+            hash_builder: RandomState::new(),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> CuckooHashMap<K, V> {
+    fn hash<Q: ?Sized + Hash>(&self, key: &Q) -> u64 {
+        let mut hasher = self.hash_builder.build_hasher();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// the stripe of the bucket
+    ///
+    /// Since the number of buckets is a multiple of LOCKS, the stripe of the hash does not change on resizing.
+    #[inline]
+    fn stripe(bucket: usize) -> usize {
+        bucket & (LOCKS - 1)
+    }
+
+    /// lock the stripes of two buckets in order
+    fn lock_two(&self, b1: usize, b2: usize) -> (WriteGuard<()>, Option<WriteGuard<()>>) {
+        let (s1, s2) = (Self::stripe(b1), Self::stripe(b2));
+
+        if s1 == s2 {
+            return (self.locks[s1].write_lock(), None);
+        }
+
+        let (s1, s2) = (s1.min(s2), s1.max(s2));
+        let first = self.locks[s1].write_lock();
+        let second = self.locks[s2].write_lock();
+
+        (first, Some(second))
+    }
+
+    /// find the cuckoo path from the buckets to the bucket which has an empty slot without any lock
+    ///
+    /// The returned hops should be executed from the last one.
+    fn search_path<'g>(
+        table: &'g Table<K, V>,
+        b1: usize,
+        b2: usize,
+        guard: &'g Guard,
+    ) -> Option<Vec<Slot<'g, K, V>>> {
+        let mut nodes = vec![
+            BfsNode {
+                bucket: b1,
+                parent: None,
+            },
+            BfsNode {
+                bucket: b2,
+                parent: None,
+            },
+        ];
+        let mut queue: VecDeque<usize> = (0..nodes.len()).collect();
+
+        while let Some(index) = queue.pop_front() {
+            let bucket = nodes[index].bucket;
+
+            if table.empty_slot(bucket, guard).is_some() {
+                // reconstruct the path from the root
+                let mut path = Vec::new();
+                let mut index = index;
+
+                while let Some((parent, hop)) = nodes[index].parent.take() {
+                    path.push(hop);
+                    index = parent;
+                }
+
+                path.reverse();
+                return Some(path);
+            }
+
+            for (slot, link) in table.buckets[bucket].iter().enumerate() {
+                if nodes.len() >= MAX_BFS {
+                    return None;
+                }
+
+                let entry = link.load(Ordering::Acquire, guard);
+                let entry_ref = some_or!(unsafe { entry.as_ref() }, continue);
+
+                nodes.push(BfsNode {
+                    bucket: table.other_bucket(entry_ref.hash, bucket),
+                    parent: Some((
+                        index,
+                        Slot {
+                            bucket,
+                            slot,
+                            entry,
+                        },
+                    )),
+                });
+                queue.push_back(nodes.len() - 1);
+            }
+        }
+
+        None
+    }
+
+    /// move the entries along the path from the last hop. If the path is changed, return Err(()).
+    fn execute_path(
+        &self,
+        table: Shared<Table<K, V>>,
+        path: &[Slot<K, V>],
+        guard: &Guard,
+    ) -> Result<(), ()> {
+        for hop in path.iter().rev() {
+            let entry_ref = unsafe { hop.entry.deref() };
+            let to = unsafe { table.deref() }.other_bucket(entry_ref.hash, hop.bucket);
+            let _locks = self.lock_two(hop.bucket, to);
+
+            if self.table.load(Ordering::Acquire, guard) != table {
+                return Err(());
+            }
+
+            let table_ref = unsafe { table.deref() };
+            let from = &table_ref.buckets[hop.bucket][hop.slot];
+
+            if from.load(Ordering::Relaxed, guard) != hop.entry {
+                return Err(());
+            }
+
+            let empty = table_ref.empty_slot(to, guard).ok_or(())?;
+
+            // The entry is on both buckets for a moment, but readers will retry by the versions.
+            table_ref.buckets[to][empty].store(hop.entry, Ordering::Release);
+            from.store(Shared::null(), Ordering::Release);
+        }
+
+        Ok(())
+    }
+
+    /// double the buckets if the table is still same with the observed one
+    fn resize(&self, observed: Shared<Table<K, V>>, guard: &Guard) {
+        let _locks: Vec<_> = self.locks.iter().map(|lock| lock.write_lock()).collect();
+
+        if self.table.load(Ordering::Acquire, guard) != observed {
+            return;
+        }
+
+        let old = unsafe { observed.deref() };
+        let mut size = old.buckets.len() * 2;
+
+        // If some entry cannot be placed without the cuckoo path, try with the larger size.
+        let new = 'resize: loop {
+            let new = Table::new(size);
+
+            for entry in old.buckets.iter().flatten() {
+                let entry = entry.load(Ordering::Relaxed, guard);
+                let entry_ref = some_or!(unsafe { entry.as_ref() }, continue);
+
+                let (b1, b2) = new.buckets_of(entry_ref.hash);
+
+                let (bucket, slot) = match new.empty_slot(b1, guard) {
+                    Some(slot) => (b1, slot),
+                    None => match new.empty_slot(b2, guard) {
+                        Some(slot) => (b2, slot),
+                        None => {
+                            size *= 2;
+                            continue 'resize;
+                        }
+                    },
+                };
+
+                new.buckets[bucket][slot].store(entry, Ordering::Relaxed);
+            }
+
+            break new;
+        };
+
+        self.table.store(Owned::new(new), Ordering::Release);
+
+        // The entries are moved to the new table, so destroy only the old table.
+        unsafe { guard.defer_destroy(observed) };
+    }
+}
+
+impl<K, V> ConcurrentMap<K, V> for CuckooHashMap<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
+        let guard = pin();
+
+        let hash = self.hash(&key);
+        let entry = Owned::new(Entry { hash, key, value });
+
+        loop {
+            let table = self.table.load(Ordering::Acquire, &guard);
+            let table_ref = unsafe { table.deref() };
+            let (b1, b2) = table_ref.buckets_of(hash);
+
+            {
+                let _locks = self.lock_two(b1, b2);
+
+                if self.table.load(Ordering::Acquire, &guard) != table {
+                    continue;
+                }
+
+                if table_ref.find(hash, &entry.key, &guard).is_some() {
+                    let Entry { key, value, .. } = *entry.into_box();
+                    return Err((key, value));
+                }
+
+                let empty = [b1, b2].iter().find_map(|&bucket| {
+                    table_ref
+                        .empty_slot(bucket, &guard)
+                        .map(|slot| (bucket, slot))
+                });
+
+                if let Some((bucket, slot)) = empty {
+                    table_ref.buckets[bucket][slot].store(entry, Ordering::Release);
+                    return Ok(());
+                }
+            }
+
+            // Both buckets are full. Make an empty slot by the cuckoo path, or resize.
+            match Self::search_path(table_ref, b1, b2, &guard) {
+                Some(path) => {
+                    let _ = self.execute_path(table, &path, &guard);
+                }
+                None => self.resize(table, &guard),
+            }
+        }
+    }
+}
+
+impl<K, V, Q> ConcurrentLookup<K, V, Q> for CuckooHashMap<K, V>
+where
+    K: Hash + Eq + Borrow<Q>,
+    V: Clone,
+    Q: ?Sized + Hash + Eq,
+{
+    fn lookup<F, R>(&self, key: &Q, f: F) -> R
+    where
+        F: FnOnce(Option<&V>) -> R,
+    {
+        let guard = pin();
+
+        let hash = self.hash(key);
+        let (s1, s2) = (Self::stripe(primary(hash)), Self::stripe(alternate(hash)));
+
+        loop {
+            // read the versions first, so that the table is not changed by resizing after reading them
+            // The guards are not dropped even if the comparison of the keys panics.
+            let lock1 = ManuallyDrop::new(unsafe { self.locks[s1].read_lock() });
+            let lock2 = ManuallyDrop::new(unsafe { self.locks[s2].read_lock() });
+
+            let table = unsafe { self.table.load(Ordering::Acquire, &guard).deref() };
+            let found = table.find(hash, key, &guard);
+
+            let valid = ManuallyDrop::into_inner(lock1).finish();
+
+            if ManuallyDrop::into_inner(lock2).finish() && valid {
+                // the entry is destroyed after the guard is dropped, even if it is removed now
+                return f(found.map(|found| unsafe { &found.entry.deref().value }));
+            }
+        }
+    }
+
+    fn get(&self, key: &Q) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.lookup(key, |value| value.cloned())
+    }
+
+    fn remove(&self, key: &Q) -> Result<V, ()> {
+        let guard = pin();
+
+        let hash = self.hash(key);
+
+        loop {
+            let table = self.table.load(Ordering::Acquire, &guard);
+            let table_ref = unsafe { table.deref() };
+            let (b1, b2) = table_ref.buckets_of(hash);
+
+            let _locks = self.lock_two(b1, b2);
+
+            if self.table.load(Ordering::Acquire, &guard) != table {
+                continue;
+            }
+
+            let Slot {
+                bucket,
+                slot,
+                entry,
+            } = some_or!(table_ref.find(hash, key, &guard), return Err(()));
+
+            table_ref.buckets[bucket][slot].store(Shared::null(), Ordering::Release);
+
+            unsafe {
+                let value = entry.deref().value.clone();
+                guard.defer_destroy(entry);
+
+                return Ok(value);
+            }
+        }
+    }
+
+    fn upsert(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+    {
+        let mut value = Some(value);
+
+        // the function is called only once under the locks of the buckets
+
+        self.compute(key, |_| value.take())
+    }
+
+    fn compute<F>(&self, key: &Q, mut f: F) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        let guard = pin();
+
+        let hash = self.hash(key);
+
+        loop {
+            let table = self.table.load(Ordering::Acquire, &guard);
+            let table_ref = unsafe { table.deref() };
+            let (b1, b2) = table_ref.buckets_of(hash);
+
+            {
+                let _locks = self.lock_two(b1, b2);
+
+                if self.table.load(Ordering::Acquire, &guard) != table {
+                    continue;
+                }
+
+                if let Some(Slot {
+                    bucket,
+                    slot,
+                    entry,
+                }) = table_ref.find(hash, key, &guard)
+                {
+                    let entry_ref = unsafe { entry.deref() };
+
+                    let new = match f(Some(&entry_ref.value)) {
+                        Some(value) => Owned::new(Entry {
+                            hash,
+                            key: key.to_owned(),
+                            value,
+                        })
+                        .into_shared(&guard),
+                        None => Shared::null(),
+                    };
+
+                    table_ref.buckets[bucket][slot].store(new, Ordering::Release);
+
+                    // the lookups may still read the old entry, so it is dropped with the value
+                    let value = entry_ref.value.clone();
+                    unsafe { guard.defer_destroy(entry) };
+
+                    return Some(value);
+                }
+
+                // the absent key is computed only if it can be inserted, so the function is called only once
+                let empty = [b1, b2].iter().find_map(|&bucket| {
+                    table_ref
+                        .empty_slot(bucket, &guard)
+                        .map(|slot| (bucket, slot))
+                });
+
+                if let Some((bucket, slot)) = empty {
+                    let value = some_or!(f(None), return None);
+                    let entry = Owned::new(Entry {
+                        hash,
+                        key: key.to_owned(),
+                        value,
+                    });
+                    table_ref.buckets[bucket][slot].store(entry, Ordering::Release);
+
+                    return None;
+                }
+            }
+
+            // Both buckets are full. Make an empty slot like insert.
+            match Self::search_path(table_ref, b1, b2, &guard) {
+                Some(path) => {
+                    let _ = self.execute_path(table, &path, &guard);
+                }
+                None => self.resize(table, &guard),
+            }
+        }
+    }
+}
+
+impl<K, V> Drop for CuckooHashMap<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = unprotected();
+
+            let table = self.table.load(Ordering::Relaxed, guard).into_owned();
+
+            for entry in table.buckets.iter().flatten() {
+                let entry = entry.load(Ordering::Relaxed, guard);
+
+                if !entry.is_null() {
+                    drop(entry.into_owned());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,9 @@
+mod cuckoo;
+mod robinhood;
+mod splitordered;
+mod striped;
+
+pub use cuckoo::CuckooHashMap;
+pub use robinhood::RobinHoodHashMap;
+pub use splitordered::SplitOrderedHashMap;
+pub use striped::StripedHashMap;
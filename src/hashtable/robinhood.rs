@@ -0,0 +1,188 @@
+/*
+ Refer to
+ https://cs.uwaterloo.ca/research/tr/1986/CS-86-14.pdf and
+ https://codecapsule.com/2013/11/17/robin-hood-hashing-backward-shift-deletion/
+*/
+
+use std::{
+    borrow::Borrow,
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hash, Hasher},
+    mem,
+};
+
+use crate::map::{SequentialLookup, SequentialMap};
+
+/// the number of buckets on initialization
+const INIT_CAPACITY: usize = 16;
+/// the max ratio (numerator, denominator) of the entries to the buckets before doubling buckets
+const MAX_LOAD: (usize, usize) = (7, 8);
+
+/// Open-addressing hash map using Robin Hood hashing
+///
+/// On inserting, the entry far from its home bucket takes the bucket of the entry near from its home.
+/// On removing, the following entries are shifted backward instead of leaving a tombstone.
+pub struct RobinHoodHashMap<K, V> {
+    buckets: Box<[Option<Bucket<K, V>>]>,
+    size: usize,
+    hash_builder: RandomState,
+}
+
+struct Bucket<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+impl<K, V> RobinHoodHashMap<K, V> {
+    #[inline]
+    fn mask(&self) -> usize {
+        self.buckets.len() - 1
+    }
+
+    /// the distance from the home bucket of the hash to the index
+    #[inline]
+    fn distance(&self, hash: u64, index: usize) -> usize {
+        index.wrapping_sub(hash as usize) & self.mask()
+    }
+
+    fn empty_buckets(capacity: usize) -> Box<[Option<Bucket<K, V>>]> {
+        (0..capacity).map(|_| None).collect()
+    }
+}
+
+impl<K: Hash + Eq, V> RobinHoodHashMap<K, V> {
+    fn hash<Q: ?Sized + Hash>(&self, key: &Q) -> u64 {
+        let mut hasher = self.hash_builder.build_hasher();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// find the index of the bucket of the key
+    fn find<Q>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let mut index = hash as usize & self.mask();
+        let mut distance = 0;
+
+        while let Some(bucket) = &self.buckets[index] {
+            // If the key exists, it would have taken this bucket.
+            if self.distance(bucket.hash, index) < distance {
+                return None;
+            }
+
+            if bucket.hash == hash && bucket.key.borrow() == key {
+                return Some(index);
+            }
+
+            index = (index + 1) & self.mask();
+            distance += 1;
+        }
+
+        None
+    }
+
+    /// put the bucket whose key does not exist in the map
+    fn put(&mut self, mut new: Bucket<K, V>) {
+        let mask = self.mask();
+        let mut index = new.hash as usize & mask;
+        let mut distance = 0;
+
+        loop {
+            let bucket = match &mut self.buckets[index] {
+                Some(bucket) => bucket,
+                None => {
+                    self.buckets[index] = Some(new);
+                    return;
+                }
+            };
+
+            let bucket_distance = index.wrapping_sub(bucket.hash as usize) & mask;
+
+            // take from the rich, and continue with the taken one
+            if bucket_distance < distance {
+                mem::swap(bucket, &mut new);
+                distance = bucket_distance;
+            }
+
+            index = (index + 1) & mask;
+            distance += 1;
+        }
+    }
+
+    /// double the buckets and put all entries again
+    fn grow(&mut self) {
+        let capacity = self.buckets.len() * 2;
+        let buckets = mem::replace(&mut self.buckets, Self::empty_buckets(capacity));
+
+        for bucket in buckets.into_vec().into_iter().flatten() {
+            self.put(bucket);
+        }
+    }
+}
+
+impl<K, V> SequentialMap<K, V> for RobinHoodHashMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn new() -> Self {
+        Self {
+            buckets: Self::empty_buckets(INIT_CAPACITY),
+            size: 0,
+            hash_builder: RandomState::new(),
+        }
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Result<(), (K, V)> {
+        let hash = self.hash(&key);
+
+        if self.find(hash, &key).is_some() {
+            return Err((key, value));
+        }
+
+        if (self.size + 1) * MAX_LOAD.1 > self.buckets.len() * MAX_LOAD.0 {
+            self.grow();
+        }
+
+        self.put(Bucket { hash, key, value });
+        self.size += 1;
+
+        Ok(())
+    }
+}
+
+impl<K, V, Q> SequentialLookup<K, V, Q> for RobinHoodHashMap<K, V>
+where
+    K: Hash + Eq + Borrow<Q>,
+    Q: ?Sized + Hash + Eq,
+{
+    fn lookup(&self, key: &Q) -> Option<&V> {
+        let index = self.find(self.hash(key), key)?;
+
+        self.buckets[index].as_ref().map(|bucket| &bucket.value)
+    }
+
+    fn remove(&mut self, key: &Q) -> Result<V, ()> {
+        let mut index = self.find(self.hash(key), key).ok_or(())?;
+        let removed = self.buckets[index].take().unwrap();
+
+        // shift backward the following entries until the empty bucket or the entry on its home
+        loop {
+            let next = (index + 1) & self.mask();
+
+            match &self.buckets[next] {
+                Some(bucket) if self.distance(bucket.hash, next) > 0 => {
+                    self.buckets[index] = self.buckets[next].take();
+                    index = next;
+                }
+                _ => break,
+            }
+        }
+
+        self.size -= 1;
+
+        Ok(removed.value)
+    }
+}
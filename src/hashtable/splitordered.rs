@@ -0,0 +1,597 @@
+/*
+ Refer to
+ The Art of Multiprocessor Programming, 13.3.3 A Lock-Free Hash Set and
+ https://dl.acm.org/doi/10.1145/1147954.1147958
+*/
+
+use std::{
+    borrow::Borrow,
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hash, Hasher},
+    sync::atomic::{AtomicIsize, AtomicUsize, Ordering},
+};
+
+use crossbeam_epoch::{pin, unprotected, Atomic, Guard, Owned, Shared};
+
+use crate::map::{ConcurrentLookup, ConcurrentMap};
+use crate::ok_or;
+
+/// the number of segments. The segment i has max(1, 2^(i-1)) buckets.
+const SEGMENTS: usize = 32;
+/// the max number of buckets
+const MAX_BUCKETS: usize = 1 << (SEGMENTS - 1);
+/// the number of buckets on initialization
+const INIT_BUCKETS: usize = 2;
+/// the average number of entries per bucket that triggers doubling buckets
+const LOAD_FACTOR: usize = 2;
+
+/// Lock-free hash map based on split-ordered list
+///
+/// All entries are in one Harris's linked list sorted by the bit-reversed hash(split-order).
+/// Each bucket points a dummy node in the list, so doubling buckets does not move any entry.
+/// The new buckets are lazily initialized by inserting their dummy nodes from their parent buckets.
+/// The value of the removed node may still be read by other threads, so it is dropped with the node,
+/// and the remove returns its clone. The value is replaced by marking the node with the tagged pointer to
+/// the new node of the same key, like HarrisList.
+pub struct SplitOrderedHashMap<K, V> {
+    segments: [Atomic<Segment<K, V>>; SEGMENTS],
+    buckets: AtomicUsize,
+    /// the number of entries, which may be negative while the remove overtakes the insert of the entry
+    count: AtomicIsize,
+    hash_builder: RandomState,
+}
+
+struct Segment<K, V> {
+    buckets: Box<[Atomic<Node<K, V>>]>,
+}
+
+impl<K, V> Segment<K, V> {
+    fn new(size: usize) -> Self {
+        Self {
+            buckets: (0..size).map(|_| Atomic::null()).collect(),
+        }
+    }
+}
+
+/// The tag 1 on the next pointer of the node means that the node is logically removed.
+struct Node<K, V> {
+    so_key: u64,
+    entry: Option<(K, V)>, // None if it is a dummy node of the bucket
+    next: Atomic<Node<K, V>>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(so_key: u64, entry: Option<(K, V)>) -> Self {
+        Self {
+            so_key,
+            entry,
+            next: Atomic::null(),
+        }
+    }
+
+    #[inline]
+    fn key(&self) -> Option<&K> {
+        self.entry.as_ref().map(|(key, _)| key)
+    }
+
+    #[inline]
+    fn value(&self) -> &V {
+        &self.entry.as_ref().unwrap().1
+    }
+}
+
+/// the position on the list
+///
+/// prev: the link which points curr.
+/// curr: the node of the key, or the first node whose so_key is greater than the key's one.
+struct Cursor<'g, K, V> {
+    prev: &'g Atomic<Node<K, V>>,
+    curr: Shared<'g, Node<K, V>>,
+}
+
+/// split-order key of the entry whose hash is given. It is always odd.
+#[inline]
+fn regular_key(hash: u64) -> u64 {
+    (hash | (1 << 63)).reverse_bits()
+}
+
+/// split-order key of the dummy node of the bucket. It is always even.
+#[inline]
+fn dummy_key(bucket: usize) -> u64 {
+    (bucket as u64).reverse_bits()
+}
+
+/// (segment index, index in the segment) of the bucket
+#[inline]
+fn bucket_index(bucket: usize) -> (usize, usize) {
+    let segment = (usize::BITS - bucket.leading_zeros()) as usize;
+    let base = (1 << segment) >> 1;
+
+    (segment, bucket - base)
+}
+
+/// the parent bucket whose list contains the bucket's first position
+#[inline]
+fn parent_bucket(bucket: usize) -> usize {
+    bucket & !(1 << (usize::BITS - 1 - bucket.leading_zeros()))
+}
+
+impl<K, V> Default for SplitOrderedHashMap<K, V> {
+    fn default() -> Self {
+        let map = Self {
+            segments: Default::default(),
+            buckets: AtomicUsize::new(INIT_BUCKETS),
+            count: AtomicIsize::new(0),
+            hash_builder: RandomState::new(),
+        };
+
+        // the dummy node of bucket 0 is the head of the list
+        let segment = Owned::new(Segment::new(1));
+        segment.buckets[0].store(Owned::new(Node::new(dummy_key(0), None)), Ordering::Relaxed);
+        map.segments[0].store(segment, Ordering::Relaxed);
+
+        map
+    }
+}
+
+impl<K: Hash + Eq, V> SplitOrderedHashMap<K, V> {
+    fn hash<Q: ?Sized + Hash>(&self, key: &Q) -> u64 {
+        let mut hasher = self.hash_builder.build_hasher();
+        key.hash(&mut hasher);
+        hasher.finish() & !(1 << 63)
+    }
+
+    /// get the link of the bucket, allocating its segment if not existing
+    fn bucket_link<'g>(&'g self, bucket: usize, guard: &'g Guard) -> &'g Atomic<Node<K, V>> {
+        let (index, offset) = bucket_index(bucket);
+        let link = &self.segments[index];
+
+        let mut segment = link.load(Ordering::Acquire, guard);
+
+        if segment.is_null() {
+            let new = Owned::new(Segment::new(((1 << index) >> 1).max(1)));
+
+            segment = match link.compare_exchange(
+                Shared::null(),
+                new,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                guard,
+            ) {
+                Ok(new) => new,
+                Err(e) => e.current,
+            };
+        }
+
+        unsafe { &segment.deref().buckets[offset] }
+    }
+
+    /// get the dummy node of the bucket, initializing it if not existing
+    fn get_bucket<'g>(&'g self, bucket: usize, guard: &'g Guard) -> &'g Node<K, V> {
+        let link = self.bucket_link(bucket, guard);
+        let dummy = link.load(Ordering::Acquire, guard);
+
+        if let Some(dummy) = unsafe { dummy.as_ref() } {
+            return dummy;
+        }
+
+        // insert the dummy node from the parent bucket
+        let parent = self.get_bucket(parent_bucket(bucket), guard);
+        let so_key = dummy_key(bucket);
+        let mut node = Owned::new(Node::new(so_key, None));
+
+        let dummy = loop {
+            let (found, cursor) = ok_or!(Self::search(parent, so_key, None, guard), continue);
+
+            if found {
+                // another thread already inserted it
+                break cursor.curr;
+            }
+
+            node.next.store(cursor.curr, Ordering::Relaxed);
+
+            match cursor.prev.compare_exchange(
+                cursor.curr,
+                node,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+                guard,
+            ) {
+                Ok(node) => break node,
+                Err(e) => node = e.new,
+            }
+        };
+
+        link.store(dummy, Ordering::Release);
+
+        unsafe { dummy.deref() }
+    }
+
+    /// get the dummy node of the bucket of the hash
+    fn start<'g>(&'g self, hash: u64, guard: &'g Guard) -> &'g Node<K, V> {
+        let buckets = self.buckets.load(Ordering::Acquire);
+
+        self.get_bucket(hash as usize & (buckets - 1), guard)
+    }
+
+    /// find the cursor by (so_key, key) from the dummy node
+    ///
+    /// While traversing, the chain of logically removed nodes just before curr is physically removed at once.
+    /// If the removal fails, return Err(()) and the caller should retry.
+    /// If success, return (whether the key is found, cursor).
+    fn search<'g, Q>(
+        start: &'g Node<K, V>,
+        so_key: u64,
+        key: Option<&Q>,
+        guard: &'g Guard,
+    ) -> Result<(bool, Cursor<'g, K, V>), ()>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let mut prev = &start.next;
+        let mut prev_next = prev.load(Ordering::Acquire, guard);
+        let mut curr = prev_next;
+
+        let found = loop {
+            let curr_ref = match unsafe { curr.as_ref() } {
+                Some(curr_ref) => curr_ref,
+                None => break false,
+            };
+
+            let next = curr_ref.next.load(Ordering::Acquire, guard);
+
+            if next.tag() == 1 {
+                // curr is logically removed, so skip it
+                curr = next.with_tag(0);
+                continue;
+            }
+
+            if curr_ref.so_key > so_key {
+                break false;
+            }
+
+            // The nodes of the same so_key are not sorted, so check all of them.
+            if curr_ref.so_key == so_key && curr_ref.key().map(Borrow::borrow) == key {
+                break true;
+            }
+
+            prev = &curr_ref.next;
+            prev_next = next;
+            curr = next;
+        };
+
+        if prev_next != curr {
+            // remove the chain of logically removed nodes [prev_next, curr)
+            prev.compare_exchange(prev_next, curr, Ordering::AcqRel, Ordering::Relaxed, guard)
+                .map_err(|_| ())?;
+
+            let mut node = prev_next;
+
+            while node != curr {
+                unsafe {
+                    let next = node.deref().next.load(Ordering::Relaxed, guard);
+                    guard.defer_destroy(node);
+                    node = next.with_tag(0);
+                }
+            }
+        }
+
+        Ok((found, Cursor { prev, curr }))
+    }
+
+    /// find the node by (so_key, key) from the dummy node without any physical removal
+    fn find<'g, Q>(
+        start: &'g Node<K, V>,
+        so_key: u64,
+        key: &Q,
+        guard: &'g Guard,
+    ) -> Option<&'g Node<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let mut curr = start.next.load(Ordering::Acquire, guard);
+
+        while let Some(curr_ref) = unsafe { curr.as_ref() } {
+            if curr_ref.so_key > so_key {
+                break;
+            }
+
+            let next = curr_ref.next.load(Ordering::Acquire, guard);
+
+            // the marked node of the key may be replaced by the next one
+            if curr_ref.so_key == so_key
+                && curr_ref.key().map(Borrow::borrow) == Some(key)
+                && next.tag() == 0
+            {
+                return Some(curr_ref);
+            }
+
+            curr = next.with_tag(0);
+        }
+
+        None
+    }
+
+    /// double the number of buckets if the load is too high
+    fn try_grow(&self, count: usize) {
+        let buckets = self.buckets.load(Ordering::Relaxed);
+
+        if count > buckets * LOAD_FACTOR && buckets < MAX_BUCKETS {
+            let _ = self.buckets.compare_exchange(
+                buckets,
+                buckets * 2,
+                Ordering::Release,
+                Ordering::Relaxed,
+            );
+        }
+    }
+}
+
+impl<K, V> ConcurrentMap<K, V> for SplitOrderedHashMap<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
+        let guard = pin();
+
+        let hash = self.hash(&key);
+        let so_key = regular_key(hash);
+        let start = self.start(hash, &guard);
+
+        let mut node = Owned::new(Node::new(so_key, Some((key, value))));
+
+        loop {
+            let (found, cursor) = ok_or!(Self::search(start, so_key, node.key(), &guard), continue);
+
+            if found {
+                return Err(node.into_box().entry.unwrap());
+            }
+
+            node.next.store(cursor.curr, Ordering::Relaxed);
+
+            match cursor.prev.compare_exchange(
+                cursor.curr,
+                node,
+                Ordering::Release,
+                Ordering::Relaxed,
+                &guard,
+            ) {
+                Ok(_) => break,
+                Err(e) => node = e.new,
+            }
+        }
+
+        let count = self.count.fetch_add(1, Ordering::Relaxed) + 1;
+        self.try_grow(count.max(0) as usize);
+
+        Ok(())
+    }
+}
+
+impl<K, V, Q> ConcurrentLookup<K, V, Q> for SplitOrderedHashMap<K, V>
+where
+    K: Hash + Eq + Borrow<Q>,
+    V: Clone,
+    Q: ?Sized + Hash + Eq,
+{
+    fn lookup<F, R>(&self, key: &Q, f: F) -> R
+    where
+        F: FnOnce(Option<&V>) -> R,
+    {
+        let guard = pin();
+
+        let hash = self.hash(key);
+        let start = self.start(hash, &guard);
+
+        f(Self::find(start, regular_key(hash), key, &guard).map(|node| node.value()))
+    }
+
+    fn get(&self, key: &Q) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.lookup(key, |value| value.cloned())
+    }
+
+    fn remove(&self, key: &Q) -> Result<V, ()> {
+        let guard = pin();
+
+        let hash = self.hash(key);
+        let so_key = regular_key(hash);
+        let start = self.start(hash, &guard);
+
+        loop {
+            let (found, cursor) = ok_or!(Self::search(start, so_key, Some(key), &guard), continue);
+
+            if !found {
+                return Err(());
+            }
+
+            let curr_ref = unsafe { cursor.curr.deref() };
+            let next = curr_ref.next.fetch_or(1, Ordering::AcqRel, &guard);
+
+            if next.tag() == 1 {
+                // another thread already removed it logically
+                continue;
+            }
+
+            // the thread that marks the node returns the value
+            let value = curr_ref.value().clone();
+
+            // try physical removal. If failed, the other thread will do on searching.
+            if cursor
+                .prev
+                .compare_exchange(
+                    cursor.curr,
+                    next,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                    &guard,
+                )
+                .is_ok()
+            {
+                unsafe { guard.defer_destroy(cursor.curr) };
+            }
+
+            self.count.fetch_sub(1, Ordering::Relaxed);
+
+            return Ok(value);
+        }
+    }
+
+    fn upsert(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+    {
+        // the function may be called again on the contention, so it clones the value
+        self.compute(key, |_| Some(value.clone()))
+    }
+
+    fn compute<F>(&self, key: &Q, mut f: F) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        let guard = pin();
+
+        let hash = self.hash(key);
+        let so_key = regular_key(hash);
+        let start = self.start(hash, &guard);
+
+        loop {
+            let (found, cursor) = ok_or!(Self::search(start, so_key, Some(key), &guard), continue);
+
+            if !found {
+                let entry = (key.to_owned(), f(None)?);
+                let node = Owned::new(Node::new(so_key, Some(entry)));
+                node.next.store(cursor.curr, Ordering::Relaxed);
+
+                if cursor
+                    .prev
+                    .compare_exchange(
+                        cursor.curr,
+                        node,
+                        Ordering::Release,
+                        Ordering::Relaxed,
+                        &guard,
+                    )
+                    .is_ok()
+                {
+                    let count = self.count.fetch_add(1, Ordering::Relaxed) + 1;
+                    self.try_grow(count.max(0) as usize);
+
+                    return None;
+                }
+
+                continue;
+            }
+
+            let curr_ref = unsafe { cursor.curr.deref() };
+            let mut next = curr_ref.next.load(Ordering::Acquire, &guard);
+
+            if next.tag() == 1 {
+                continue;
+            }
+
+            let node = f(Some(curr_ref.value())).map(|value| {
+                Owned::new(Node::new(so_key, Some((key.to_owned(), value)))).into_shared(&guard)
+            });
+
+            // mark curr with the pointer to its replacement, or to its next if it is removed
+            let succ = loop {
+                let succ = match node {
+                    Some(node) => {
+                        unsafe { node.deref() }.next.store(next, Ordering::Relaxed);
+                        node
+                    }
+                    None => next,
+                };
+
+                match curr_ref.next.compare_exchange(
+                    next,
+                    succ.with_tag(1),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    &guard,
+                ) {
+                    Ok(_) => break Some(succ),
+                    Err(e) if e.current.tag() == 0 => next = e.current,
+                    Err(_) => break None,
+                }
+            };
+
+            let succ = match succ {
+                Some(succ) => succ,
+                None => {
+                    // another thread already replaced or removed curr, so compute again
+                    if let Some(node) = node {
+                        drop(unsafe { node.into_owned() });
+                    }
+
+                    continue;
+                }
+            };
+
+            // the thread that marks the node returns the value
+            let value = curr_ref.value().clone();
+
+            // try physical removal. If failed, the other thread will do on searching.
+            if cursor
+                .prev
+                .compare_exchange(
+                    cursor.curr,
+                    succ,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                    &guard,
+                )
+                .is_ok()
+            {
+                unsafe { guard.defer_destroy(cursor.curr) };
+            }
+
+            if node.is_none() {
+                self.count.fetch_sub(1, Ordering::Relaxed);
+            }
+
+            return Some(value);
+        }
+    }
+}
+
+impl<K, V> Drop for SplitOrderedHashMap<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = unprotected();
+
+            // All nodes are reachable from the dummy node of bucket 0.
+            let mut node = self.segments[0]
+                .load(Ordering::Relaxed, guard)
+                .deref()
+                .buckets[0]
+                .load(Ordering::Relaxed, guard);
+
+            while !node.is_null() {
+                let next = node.deref().next.load(Ordering::Relaxed, guard);
+                drop(node.into_owned());
+
+                node = next.with_tag(0);
+            }
+
+            for segment in self.segments.iter() {
+                let segment = segment.load(Ordering::Relaxed, guard);
+
+                if !segment.is_null() {
+                    drop(segment.into_owned());
+                }
+            }
+        }
+    }
+}
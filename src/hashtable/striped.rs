@@ -0,0 +1,241 @@
+/*
+ Refer to
+ The Art of Multiprocessor Programming, 13.2.2 A Striped Hash Set
+*/
+
+use std::{
+    borrow::Borrow,
+    cell::UnsafeCell,
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hash, Hasher},
+    mem,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crossbeam_utils::CachePadded;
+
+use crate::lock::{LockGuard, RawSimpleLock};
+use crate::map::{ConcurrentLookup, ConcurrentMap};
+
+/// the number of stripes on the default
+const DEFAULT_STRIPES: usize = 64;
+/// the average number of entries per bucket that triggers doubling buckets
+const LOAD_FACTOR: usize = 4;
+
+/// Hash map whose buckets are split into the fixed number of stripes guarded by the locks
+///
+/// The bucket i is guarded by the lock (i % stripes). Since the number of buckets is always a multiple of stripes,
+/// the stripe of the key does not change on resizing. Resizing takes all locks from the first stripe in order.
+pub struct StripedHashMap<K, V, L: RawSimpleLock> {
+    locks: Box<[CachePadded<L>]>,
+    buckets: UnsafeCell<Box<[Bucket<K, V>]>>,
+    count: AtomicUsize,
+    hash_builder: RandomState,
+}
+
+type Bucket<K, V> = UnsafeCell<Vec<(K, V)>>;
+
+unsafe impl<K: Send, V: Send, L: RawSimpleLock + Send> Send for StripedHashMap<K, V, L> {}
+unsafe impl<K: Send + Sync, V: Send + Sync, L: RawSimpleLock + Sync> Sync
+    for StripedHashMap<K, V, L>
+{
+}
+
+impl<K, V, L: RawSimpleLock> StripedHashMap<K, V, L> {
+    /// create the map with the number of stripes, which is rounded up to the power of 2
+    pub fn with_stripes(stripes: usize) -> Self {
+        let stripes = stripes.max(1).next_power_of_two();
+
+        Self {
+            locks: (0..stripes).map(|_| CachePadded::new(L::new())).collect(),
+            buckets: UnsafeCell::new((0..stripes).map(|_| UnsafeCell::new(Vec::new())).collect()),
+            count: AtomicUsize::new(0),
+            hash_builder: RandomState::new(),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, L: RawSimpleLock> StripedHashMap<K, V, L> {
+    fn hash<Q: ?Sized + Hash>(&self, key: &Q) -> usize {
+        let mut hasher = self.hash_builder.build_hasher();
+        key.hash(&mut hasher);
+        hasher.finish() as usize
+    }
+
+    /// execute the function on (the bucket of the hash, the number of buckets) while holding the lock of its stripe
+    ///
+    /// The lock is held by the guard, so it is released even if the function panics.
+    fn with_bucket<F, R>(&self, hash: usize, f: F) -> R
+    where
+        F: FnOnce(&mut Vec<(K, V)>, usize) -> R,
+    {
+        let _guard = LockGuard::new(&*self.locks[hash & (self.locks.len() - 1)]);
+
+        // Since the lock of the stripe is held, no one can access the bucket nor resize buckets.
+        let buckets = unsafe { &*self.buckets.get() };
+        let capacity = buckets.len();
+
+        f(
+            unsafe { &mut *buckets[hash & (capacity - 1)].get() },
+            capacity,
+        )
+    }
+
+    /// double the buckets if the number of buckets is still same with the observed one
+    fn resize(&self, observed: usize) {
+        let _guards = self
+            .locks
+            .iter()
+            .map(|lock| LockGuard::new(&**lock))
+            .collect::<Vec<_>>();
+
+        // Since all locks are held, no one can access the buckets.
+        let buckets = unsafe { &mut *self.buckets.get() };
+
+        if buckets.len() == observed {
+            let capacity = buckets.len() * 2;
+            let mut new: Box<[Bucket<K, V>]> =
+                (0..capacity).map(|_| UnsafeCell::new(Vec::new())).collect();
+
+            // hash all keys before moving them, so no entry is lost if hashing panics
+            let hashes = buckets
+                .iter_mut()
+                .flat_map(|bucket| bucket.get_mut().iter())
+                .map(|(key, _)| self.hash(key))
+                .collect::<Vec<_>>();
+
+            for ((key, value), hash) in buckets
+                .iter_mut()
+                .flat_map(|bucket| bucket.get_mut().drain(..))
+                .zip(hashes)
+            {
+                new[hash & (capacity - 1)].get_mut().push((key, value));
+            }
+
+            *buckets = new;
+        }
+    }
+}
+
+impl<K, V, L> ConcurrentMap<K, V> for StripedHashMap<K, V, L>
+where
+    K: Hash + Eq,
+    L: RawSimpleLock,
+{
+    fn new() -> Self {
+        Self::with_stripes(DEFAULT_STRIPES)
+    }
+
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
+        let hash = self.hash(&key);
+
+        let capacity = self.with_bucket(hash, |bucket, capacity| {
+            if bucket.iter().any(|(k, _)| *k == key) {
+                return Err((key, value));
+            }
+
+            bucket.push((key, value));
+            Ok(capacity)
+        })?;
+
+        let count = self.count.fetch_add(1, Ordering::Relaxed) + 1;
+
+        if count > capacity * LOAD_FACTOR {
+            self.resize(capacity);
+        }
+
+        Ok(())
+    }
+}
+
+impl<K, V, L, Q> ConcurrentLookup<K, V, Q> for StripedHashMap<K, V, L>
+where
+    K: Hash + Eq + Borrow<Q>,
+    L: RawSimpleLock,
+    Q: ?Sized + Hash + Eq,
+{
+    fn lookup<F, R>(&self, key: &Q, f: F) -> R
+    where
+        F: FnOnce(Option<&V>) -> R,
+    {
+        self.with_bucket(self.hash(key), |bucket, _| {
+            f(bucket
+                .iter()
+                .find(|(k, _)| k.borrow() == key)
+                .map(|(_, v)| v))
+        })
+    }
+
+    fn get(&self, key: &Q) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.lookup(key, |value| value.cloned())
+    }
+
+    fn remove(&self, key: &Q) -> Result<V, ()> {
+        let value = self.with_bucket(self.hash(key), |bucket, _| {
+            let index = bucket
+                .iter()
+                .position(|(k, _)| k.borrow() == key)
+                .ok_or(())?;
+            Ok(bucket.swap_remove(index).1)
+        })?;
+
+        self.count.fetch_sub(1, Ordering::Relaxed);
+
+        Ok(value)
+    }
+
+    fn upsert(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+    {
+        let mut value = Some(value);
+
+        // the function is called only once under the lock of the stripe
+        self.compute(key, |_| value.take())
+    }
+
+    fn compute<F>(&self, key: &Q, mut f: F) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        let (old, exists, capacity) = self.with_bucket(self.hash(key), |bucket, capacity| {
+            let index = bucket.iter().position(|(k, _)| k.borrow() == key);
+
+            // compute before changing the bucket, so the bucket is not changed if the function panics
+            let new = f(index.map(|index| &bucket[index].1));
+            let exists = new.is_some();
+
+            let old = match (index, new) {
+                (Some(index), Some(value)) => Some(mem::replace(&mut bucket[index].1, value)),
+                (Some(index), None) => Some(bucket.swap_remove(index).1),
+                (None, Some(value)) => {
+                    bucket.push((key.to_owned(), value));
+                    None
+                }
+                (None, None) => None,
+            };
+
+            (old, exists, capacity)
+        });
+
+        match (old.is_some(), exists) {
+            (false, true) => {
+                let count = self.count.fetch_add(1, Ordering::Relaxed) + 1;
+
+                if count > capacity * LOAD_FACTOR {
+                    self.resize(capacity);
+                }
+            }
+            (true, false) => {
+                self.count.fetch_sub(1, Ordering::Relaxed);
+            }
+            _ => {}
+        }
+
+        old
+    }
+}
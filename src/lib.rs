@@ -1,8 +1,13 @@
+pub mod art;
 pub mod avltree;
+pub mod bst;
 pub mod btree;
+pub mod hashtable;
 pub mod linkedlist;
 pub mod lock;
 pub mod map;
+pub mod priority_queue;
 pub mod queue;
+pub mod skiplist;
 pub mod stack;
 pub mod util;
@@ -0,0 +1,262 @@
+/*
+ Refer to
+ The Art of Multiprocessor Programming, 9.5 Fine-Grained Synchronization
+*/
+
+use std::{borrow::Borrow, mem::ManuallyDrop, ptr, sync::atomic::Ordering};
+
+use crossbeam_epoch::{pin, unprotected, Atomic, Guard, Owned, Shared};
+
+use crate::lock::{LockGuard, RawSimpleLock};
+use crate::map::{ConcurrentLookup, ConcurrentMap};
+
+/// Sorted linked list using hand-over-hand locking (lock coupling)
+///
+/// The traversal always holds the locks of two adjacent nodes, so the updater can modify them directly.
+pub struct HandOverHandList<K, V, L: RawSimpleLock> {
+    head: Atomic<Node<K, V, L>>,
+    lock: L, // the lock for head
+}
+
+struct Node<K, V, L: RawSimpleLock> {
+    key: K,
+    value: ManuallyDrop<V>,
+    lock: L,
+    next: Atomic<Node<K, V, L>>,
+}
+
+impl<K, V, L: RawSimpleLock> Node<K, V, L> {
+    fn new(key: K, value: V) -> Self {
+        Self {
+            key,
+            value: ManuallyDrop::new(value),
+            lock: L::new(),
+            next: Atomic::null(),
+        }
+    }
+}
+
+/// the locked position on the list
+///
+/// prev: the last node whose key is less than the key, or None if it is head.
+/// curr: the first node whose key is equal or greater than the key, or null if not existing.
+/// Both prev(or head) and curr(if not null) are locked until the cursor is dropped.
+struct Cursor<'g, K, V, L: RawSimpleLock> {
+    prev: Option<&'g Node<K, V, L>>,
+    curr: Shared<'g, Node<K, V, L>>,
+    _prev_guard: LockGuard<'g, L>,
+    _curr_guard: Option<LockGuard<'g, L>>,
+}
+
+impl<K, V, L: RawSimpleLock> Default for HandOverHandList<K, V, L> {
+    fn default() -> Self {
+        Self {
+            head: Atomic::null(),
+            lock: L::new(),
+        }
+    }
+}
+
+impl<K: Ord, V, L: RawSimpleLock> HandOverHandList<K, V, L> {
+    /// get (lock, next link) of the prev. If the prev is None, it is head.
+    fn link<'g>(&'g self, prev: Option<&'g Node<K, V, L>>) -> (&'g L, &'g Atomic<Node<K, V, L>>) {
+        match prev {
+            Some(node) => (&node.lock, &node.next),
+            None => (&self.lock, &self.head),
+        }
+    }
+
+    /// find the cursor by the key using hand-over-hand locking
+    ///
+    /// The locks are held by the guards, so they are released even if comparing the keys panics.
+    fn find<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Cursor<'g, K, V, L>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut prev_guard = LockGuard::new(&self.lock);
+        let mut curr_guard = None;
+
+        let mut prev = None;
+        let mut curr = self.head.load(Ordering::Acquire, guard);
+
+        while let Some(curr_ref) = unsafe { curr.as_ref() } {
+            let lock = LockGuard::new(&curr_ref.lock);
+
+            if curr_ref.key.borrow() >= key {
+                curr_guard = Some(lock);
+                break;
+            }
+
+            // release the lock of prev
+            prev_guard = lock;
+
+            prev = Some(curr_ref);
+            curr = curr_ref.next.load(Ordering::Acquire, guard);
+        }
+
+        Cursor {
+            prev,
+            curr,
+            _prev_guard: prev_guard,
+            _curr_guard: curr_guard,
+        }
+    }
+}
+
+impl<K, V, L> ConcurrentMap<K, V> for HandOverHandList<K, V, L>
+where
+    K: Ord,
+    L: RawSimpleLock,
+{
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
+        let guard = pin();
+
+        let cursor = self.find(&key, &guard);
+
+        if let Some(curr_ref) = unsafe { cursor.curr.as_ref() } {
+            if curr_ref.key == key {
+                return Err((key, value));
+            }
+        }
+
+        let node = Owned::new(Node::new(key, value));
+        node.next.store(cursor.curr, Ordering::Relaxed);
+        self.link(cursor.prev).1.store(node, Ordering::Release);
+
+        Ok(())
+    }
+}
+
+impl<K, V, L, Q> ConcurrentLookup<K, V, Q> for HandOverHandList<K, V, L>
+where
+    K: Ord + Borrow<Q>,
+    L: RawSimpleLock,
+    Q: ?Sized + Ord,
+{
+    fn lookup<F, R>(&self, key: &Q, f: F) -> R
+    where
+        F: FnOnce(Option<&V>) -> R,
+    {
+        let guard = pin();
+
+        let cursor = self.find(key, &guard);
+
+        match unsafe { cursor.curr.as_ref() } {
+            Some(curr_ref) if curr_ref.key.borrow() == key => f(Some(&curr_ref.value)),
+            _ => f(None),
+        }
+    }
+
+    fn get(&self, key: &Q) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.lookup(key, |value| value.cloned())
+    }
+
+    fn remove(&self, key: &Q) -> Result<V, ()> {
+        let guard = pin();
+
+        let cursor = self.find(key, &guard);
+
+        let curr_ref = match unsafe { cursor.curr.as_ref() } {
+            Some(curr_ref) if curr_ref.key.borrow() == key => curr_ref,
+            _ => return Err(()),
+        };
+
+        let value = unsafe { ptr::read(&curr_ref.value) };
+        self.link(cursor.prev).1.store(
+            curr_ref.next.load(Ordering::Acquire, &guard),
+            Ordering::Release,
+        );
+
+        let curr = cursor.curr;
+        drop(cursor);
+
+        // Since reaching curr requires the lock of prev, no other thread can read curr after unlinking.
+        drop(unsafe { curr.into_owned() });
+
+        Ok(ManuallyDrop::into_inner(value))
+    }
+
+    fn upsert(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+    {
+        let mut value = Some(value);
+
+        // the function is called only once while holding the locks of the cursor
+        self.compute(key, |_| value.take())
+    }
+
+    fn compute<F>(&self, key: &Q, mut f: F) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        let guard = pin();
+
+        let cursor = self.find(key, &guard);
+        let link = self.link(cursor.prev).1;
+
+        let curr_ref =
+            unsafe { cursor.curr.as_ref() }.filter(|curr_ref| curr_ref.key.borrow() == key);
+        let new = f(curr_ref.map(|curr_ref| &*curr_ref.value));
+
+        let curr_ref = match curr_ref {
+            Some(curr_ref) => curr_ref,
+            None => {
+                if let Some(value) = new {
+                    let node = Owned::new(Node::new(key.to_owned(), value));
+                    node.next.store(cursor.curr, Ordering::Relaxed);
+                    link.store(node, Ordering::Release);
+                }
+
+                return None;
+            }
+        };
+
+        // replace curr with the new node instead of writing the value through the shared reference of curr
+        let succ = curr_ref.next.load(Ordering::Acquire, &guard);
+        let succ = match new {
+            Some(value) => {
+                let node = Owned::new(Node::new(key.to_owned(), value));
+                node.next.store(succ, Ordering::Relaxed);
+                node.into_shared(&guard)
+            }
+            None => succ,
+        };
+
+        let value = unsafe { ptr::read(&curr_ref.value) };
+        link.store(succ, Ordering::Release);
+
+        let curr = cursor.curr;
+        drop(cursor);
+
+        // Like remove, no other thread can read curr after unlinking.
+        drop(unsafe { curr.into_owned() });
+
+        Some(ManuallyDrop::into_inner(value))
+    }
+}
+
+impl<K, V, L: RawSimpleLock> Drop for HandOverHandList<K, V, L> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = unprotected();
+
+            let mut node = self.head.load(Ordering::Relaxed, guard);
+
+            while !node.is_null() {
+                let mut owned = node.into_owned();
+                ManuallyDrop::drop(&mut owned.value);
+                node = owned.next.load(Ordering::Relaxed, guard);
+            }
+        }
+    }
+}
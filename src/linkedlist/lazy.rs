@@ -0,0 +1,337 @@
+/*
+ Refer to
+ The Art of Multiprocessor Programming, 9.7 Lazy Synchronization and
+ https://people.csail.mit.edu/shanir/publications/Lazy_Concurrent.pdf
+*/
+
+use std::{
+    borrow::Borrow,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crossbeam_epoch::{pin, unprotected, Atomic, Guard, Owned, Shared};
+
+use crate::lock::{LockGuard, RawSimpleLock};
+use crate::map::{ConcurrentLookup, ConcurrentMap};
+
+/// Lazy sorted linked list
+///
+/// The traversal does not take any lock. The updater locks the nodes and validates them by the mark,
+/// then removes the node logically by marking and physically by unlinking.
+/// The value is replaced by linking the new node after the old one before marking the old one,
+/// so the traversal which meets the marked node of the key goes on to the new node.
+/// The value of the removed node may still be read by other threads, so it is dropped with the node,
+/// and the remove returns its clone.
+pub struct LazyList<K, V, L: RawSimpleLock> {
+    head: Atomic<Node<K, V, L>>,
+    lock: L, // the lock for head
+}
+
+struct Node<K, V, L: RawSimpleLock> {
+    key: K,
+    value: V,
+    marked: AtomicBool,
+    lock: L,
+    next: Atomic<Node<K, V, L>>,
+}
+
+impl<K, V, L: RawSimpleLock> Node<K, V, L> {
+    fn new(key: K, value: V) -> Self {
+        Self {
+            key,
+            value,
+            marked: AtomicBool::new(false),
+            lock: L::new(),
+            next: Atomic::null(),
+        }
+    }
+}
+
+/// the position on the list
+///
+/// prev: the last node whose key is less than the key, or None if it is head.
+/// curr: the first node whose key is equal or greater than the key, or null if not existing.
+struct Cursor<'g, K, V, L: RawSimpleLock> {
+    prev: Option<&'g Node<K, V, L>>,
+    curr: Shared<'g, Node<K, V, L>>,
+}
+
+impl<K, V, L: RawSimpleLock> Default for LazyList<K, V, L> {
+    fn default() -> Self {
+        Self {
+            head: Atomic::null(),
+            lock: L::new(),
+        }
+    }
+}
+
+impl<K: Ord, V, L: RawSimpleLock> LazyList<K, V, L> {
+    /// get (lock, next link) of the prev. If the prev is None, it is head.
+    fn link<'g>(&'g self, prev: Option<&'g Node<K, V, L>>) -> (&'g L, &'g Atomic<Node<K, V, L>>) {
+        match prev {
+            Some(node) => (&node.lock, &node.next),
+            None => (&self.lock, &self.head),
+        }
+    }
+
+    /// find the cursor by the key without any lock
+    fn find<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Cursor<'g, K, V, L>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut prev = None;
+        let mut curr = self.head.load(Ordering::Acquire, guard);
+
+        while let Some(curr_ref) = unsafe { curr.as_ref() } {
+            if curr_ref.key.borrow() >= key {
+                break;
+            }
+
+            prev = Some(curr_ref);
+            curr = curr_ref.next.load(Ordering::Acquire, guard);
+        }
+
+        Cursor { prev, curr }
+    }
+
+    /// validate if prev and curr are still unmarked and adjacent. The lock of prev should be held.
+    fn validate(
+        prev: Option<&Node<K, V, L>>,
+        next: &Atomic<Node<K, V, L>>,
+        curr: Shared<Node<K, V, L>>,
+        guard: &Guard,
+    ) -> bool {
+        if let Some(prev) = prev {
+            if prev.marked.load(Ordering::Acquire) {
+                return false;
+            }
+        }
+
+        if let Some(curr) = unsafe { curr.as_ref() } {
+            if curr.marked.load(Ordering::Acquire) {
+                return false;
+            }
+        }
+
+        next.load(Ordering::Acquire, guard) == curr
+    }
+}
+
+impl<K, V, L> ConcurrentMap<K, V> for LazyList<K, V, L>
+where
+    K: Ord,
+    V: Clone,
+    L: RawSimpleLock,
+{
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
+        let guard = pin();
+
+        let node = Owned::new(Node::new(key, value)).into_shared(&guard);
+        let key = &unsafe { node.deref() }.key;
+
+        loop {
+            let Cursor { prev, curr } = self.find(key, &guard);
+            let (lock, next) = self.link(prev);
+
+            let lock = LockGuard::new(lock);
+
+            if !Self::validate(prev, next, curr, &guard) {
+                continue;
+            }
+
+            // since prev is locked, curr cannot be removed until unlocking
+            if let Some(curr_ref) = unsafe { curr.as_ref() } {
+                if curr_ref.key == *key {
+                    drop(lock);
+
+                    let Node { key, value, .. } = *unsafe { node.into_owned() }.into_box();
+                    return Err((key, value));
+                }
+            }
+
+            unsafe { node.deref() }.next.store(curr, Ordering::Relaxed);
+            next.store(node, Ordering::Release);
+
+            return Ok(());
+        }
+    }
+}
+
+impl<K, V, L, Q> ConcurrentLookup<K, V, Q> for LazyList<K, V, L>
+where
+    K: Ord + Borrow<Q>,
+    V: Clone,
+    L: RawSimpleLock,
+    Q: ?Sized + Ord,
+{
+    fn lookup<F, R>(&self, key: &Q, f: F) -> R
+    where
+        F: FnOnce(Option<&V>) -> R,
+    {
+        let guard = pin();
+
+        let mut curr = self.find(key, &guard).curr;
+
+        // the removed node is not destroyed until the guard is dropped
+        while let Some(curr_ref) = unsafe { curr.as_ref() } {
+            if curr_ref.key.borrow() != key {
+                break;
+            }
+
+            if !curr_ref.marked.load(Ordering::Acquire) {
+                return f(Some(&curr_ref.value));
+            }
+
+            // the node may be replaced by the next one
+            curr = curr_ref.next.load(Ordering::Acquire, &guard);
+        }
+
+        f(None)
+    }
+
+    fn get(&self, key: &Q) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.lookup(key, |value| value.cloned())
+    }
+
+    fn remove(&self, key: &Q) -> Result<V, ()> {
+        let guard = pin();
+
+        loop {
+            let Cursor { prev, curr } = self.find(key, &guard);
+            let (lock, next) = self.link(prev);
+
+            let curr_ref = match unsafe { curr.as_ref() } {
+                Some(curr_ref) if curr_ref.key.borrow() == key => curr_ref,
+                _ => {
+                    let lock = LockGuard::new(lock);
+                    let valid = Self::validate(prev, next, curr, &guard);
+                    drop(lock);
+
+                    if valid {
+                        return Err(());
+                    }
+
+                    continue;
+                }
+            };
+
+            let lock = LockGuard::new(lock);
+            let curr_lock = LockGuard::new(&curr_ref.lock);
+
+            if !Self::validate(prev, next, curr, &guard) {
+                continue;
+            }
+
+            // logical removal, then physical removal
+            curr_ref.marked.store(true, Ordering::Release);
+            next.store(
+                curr_ref.next.load(Ordering::Acquire, &guard),
+                Ordering::Release,
+            );
+
+            drop(curr_lock);
+            drop(lock);
+
+            // the thread that marks the node returns the value
+            let value = curr_ref.value.clone();
+            unsafe { guard.defer_destroy(curr) };
+
+            return Ok(value);
+        }
+    }
+
+    fn upsert(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+    {
+        let mut value = Some(value);
+
+        // the function is called only once after validating the locked nodes
+        self.compute(key, |_| value.take())
+    }
+
+    fn compute<F>(&self, key: &Q, mut f: F) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        let guard = pin();
+
+        loop {
+            let Cursor { prev, curr } = self.find(key, &guard);
+            let (lock, next) = self.link(prev);
+
+            let curr_ref = unsafe { curr.as_ref() }.filter(|curr_ref| curr_ref.key.borrow() == key);
+
+            let lock = LockGuard::new(lock);
+            let curr_lock = curr_ref.map(|curr_ref| LockGuard::new(&curr_ref.lock));
+
+            if !Self::validate(prev, next, curr, &guard) {
+                continue;
+            }
+
+            let curr_ref = match curr_ref {
+                Some(curr_ref) => curr_ref,
+                None => {
+                    let value = f(None)?;
+
+                    let node = Owned::new(Node::new(key.to_owned(), value));
+                    node.next.store(curr, Ordering::Relaxed);
+                    next.store(node, Ordering::Release);
+
+                    return None;
+                }
+            };
+
+            let succ = curr_ref.next.load(Ordering::Acquire, &guard);
+
+            let succ = match f(Some(&curr_ref.value)) {
+                Some(value) => {
+                    // link the new node after curr, so the traversal can reach it after marking curr
+                    let node = Owned::new(Node::new(key.to_owned(), value));
+                    node.next.store(succ, Ordering::Relaxed);
+
+                    let node = node.into_shared(&guard);
+                    curr_ref.next.store(node, Ordering::Release);
+
+                    node
+                }
+                None => succ,
+            };
+
+            curr_ref.marked.store(true, Ordering::Release);
+            next.store(succ, Ordering::Release);
+
+            drop(curr_lock);
+            drop(lock);
+
+            let value = curr_ref.value.clone();
+            unsafe { guard.defer_destroy(curr) };
+
+            return Some(value);
+        }
+    }
+}
+
+impl<K, V, L: RawSimpleLock> Drop for LazyList<K, V, L> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = unprotected();
+
+            let mut node = self.head.load(Ordering::Relaxed, guard);
+
+            while !node.is_null() {
+                let owned = node.into_owned();
+                node = owned.next.load(Ordering::Relaxed, guard);
+            }
+        }
+    }
+}
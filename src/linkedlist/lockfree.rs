@@ -0,0 +1,369 @@
+/*
+ Refer to
+ https://www.microsoft.com/en-us/research/wp-content/uploads/2001/10/2001-disc.pdf and
+ https://github.com/kaist-cp/cs431/blob/main/lockfree/src/list.rs
+*/
+
+use std::{borrow::Borrow, cmp::Ordering::*, sync::atomic::Ordering};
+
+use crossbeam_epoch::{pin, unprotected, Atomic, Guard, Owned, Shared};
+
+use crate::map::{ConcurrentLookup, ConcurrentMap};
+use crate::ok_or;
+
+/// Harris's lock-free sorted linked list
+///
+/// The tag 1 on the next pointer of the node means that the node is logically removed.
+/// The value is replaced by marking the node with the tagged pointer to the new node of the same key,
+/// so the new node is linked and the old one is removed at once.
+/// The value of the removed node may still be read by other threads, so it is dropped with the node,
+/// and the remove returns its clone.
+pub struct HarrisList<K, V> {
+    head: Atomic<Node<K, V>>,
+}
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    next: Atomic<Node<K, V>>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(key: K, value: V) -> Self {
+        Self {
+            key,
+            value,
+            next: Atomic::null(),
+        }
+    }
+}
+
+/// the position on the list
+///
+/// prev: the link which points curr.
+/// curr: the first node whose key is equal or greater than the key, or null if not existing.
+struct Cursor<'g, K, V> {
+    prev: &'g Atomic<Node<K, V>>,
+    curr: Shared<'g, Node<K, V>>,
+}
+
+impl<K, V> Default for HarrisList<K, V> {
+    fn default() -> Self {
+        Self {
+            head: Atomic::null(),
+        }
+    }
+}
+
+impl<K: Ord, V> HarrisList<K, V> {
+    /// find the cursor by the key
+    ///
+    /// While traversing, the chain of logically removed nodes just before curr is physically removed at once.
+    /// If the removal fails, return Err(()) and the caller should retry.
+    /// If success, return (whether the key is found, cursor).
+    fn search<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Result<(bool, Cursor<'g, K, V>), ()>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut prev = &self.head;
+        let mut prev_next = prev.load(Ordering::Acquire, guard);
+        let mut curr = prev_next;
+
+        let found = loop {
+            let curr_ref = match unsafe { curr.as_ref() } {
+                Some(curr_ref) => curr_ref,
+                None => break false,
+            };
+
+            let next = curr_ref.next.load(Ordering::Acquire, guard);
+
+            if next.tag() == 1 {
+                // curr is logically removed, so skip it
+                curr = next.with_tag(0);
+                continue;
+            }
+
+            match curr_ref.key.borrow().cmp(key) {
+                Less => {
+                    prev = &curr_ref.next;
+                    prev_next = next;
+                    curr = next;
+                }
+                Equal => break true,
+                Greater => break false,
+            }
+        };
+
+        if prev_next != curr {
+            // remove the chain of logically removed nodes [prev_next, curr)
+            prev.compare_exchange(prev_next, curr, Ordering::AcqRel, Ordering::Relaxed, guard)
+                .map_err(|_| ())?;
+
+            let mut node = prev_next;
+
+            while node != curr {
+                unsafe {
+                    let next = node.deref().next.load(Ordering::Relaxed, guard);
+                    guard.defer_destroy(node);
+                    node = next.with_tag(0);
+                }
+            }
+        }
+
+        Ok((found, Cursor { prev, curr }))
+    }
+
+    /// find the node by the key without any physical removal
+    fn find<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Option<&'g Node<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut curr = self.head.load(Ordering::Acquire, guard);
+
+        while let Some(curr_ref) = unsafe { curr.as_ref() } {
+            let next = curr_ref.next.load(Ordering::Acquire, guard);
+
+            match curr_ref.key.borrow().cmp(key) {
+                Less => curr = next.with_tag(0),
+                Equal => {
+                    if next.tag() == 1 {
+                        // the node may be replaced by the next one
+                        curr = next.with_tag(0);
+                        continue;
+                    }
+
+                    return Some(curr_ref);
+                }
+                Greater => return None,
+            }
+        }
+
+        None
+    }
+}
+
+impl<K, V> ConcurrentMap<K, V> for HarrisList<K, V>
+where
+    K: Ord,
+    V: Clone,
+{
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
+        let guard = pin();
+
+        let mut node = Owned::new(Node::new(key, value));
+
+        loop {
+            let (found, cursor) = ok_or!(self.search(&node.key, &guard), continue);
+
+            if found {
+                let Node { key, value, .. } = *node.into_box();
+                return Err((key, value));
+            }
+
+            node.next.store(cursor.curr, Ordering::Relaxed);
+
+            match cursor.prev.compare_exchange(
+                cursor.curr,
+                node,
+                Ordering::Release,
+                Ordering::Relaxed,
+                &guard,
+            ) {
+                Ok(_) => return Ok(()),
+                Err(e) => node = e.new,
+            }
+        }
+    }
+}
+
+impl<K, V, Q> ConcurrentLookup<K, V, Q> for HarrisList<K, V>
+where
+    K: Ord + Borrow<Q>,
+    V: Clone,
+    Q: ?Sized + Ord,
+{
+    fn lookup<F, R>(&self, key: &Q, f: F) -> R
+    where
+        F: FnOnce(Option<&V>) -> R,
+    {
+        let guard = pin();
+
+        f(self.find(key, &guard).map(|node| &node.value))
+    }
+
+    fn get(&self, key: &Q) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.lookup(key, |value| value.cloned())
+    }
+
+    fn remove(&self, key: &Q) -> Result<V, ()> {
+        let guard = pin();
+
+        loop {
+            let (found, cursor) = ok_or!(self.search(key, &guard), continue);
+
+            if !found {
+                return Err(());
+            }
+
+            let curr_ref = unsafe { cursor.curr.deref() };
+            let next = curr_ref.next.fetch_or(1, Ordering::AcqRel, &guard);
+
+            if next.tag() == 1 {
+                // another thread already removed it logically
+                continue;
+            }
+
+            // the thread that marks the node returns the value
+            let value = curr_ref.value.clone();
+
+            // try physical removal. If failed, the other thread will do on searching.
+            if cursor
+                .prev
+                .compare_exchange(
+                    cursor.curr,
+                    next,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                    &guard,
+                )
+                .is_ok()
+            {
+                unsafe { guard.defer_destroy(cursor.curr) };
+            }
+
+            return Ok(value);
+        }
+    }
+
+    fn upsert(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+    {
+        // the function may be called again on the contention, so it clones the value
+        self.compute(key, |_| Some(value.clone()))
+    }
+
+    fn compute<F>(&self, key: &Q, mut f: F) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        let guard = pin();
+
+        loop {
+            let (found, cursor) = ok_or!(self.search(key, &guard), continue);
+
+            if !found {
+                let node = Owned::new(Node::new(key.to_owned(), f(None)?));
+                node.next.store(cursor.curr, Ordering::Relaxed);
+
+                if cursor
+                    .prev
+                    .compare_exchange(
+                        cursor.curr,
+                        node,
+                        Ordering::Release,
+                        Ordering::Relaxed,
+                        &guard,
+                    )
+                    .is_ok()
+                {
+                    return None;
+                }
+
+                continue;
+            }
+
+            let curr_ref = unsafe { cursor.curr.deref() };
+            let mut next = curr_ref.next.load(Ordering::Acquire, &guard);
+
+            if next.tag() == 1 {
+                continue;
+            }
+
+            let node = f(Some(&curr_ref.value))
+                .map(|value| Owned::new(Node::new(key.to_owned(), value)).into_shared(&guard));
+
+            // mark curr with the pointer to its replacement, or to its next if it is removed
+            let succ = loop {
+                let succ = match node {
+                    Some(node) => {
+                        unsafe { node.deref() }.next.store(next, Ordering::Relaxed);
+                        node
+                    }
+                    None => next,
+                };
+
+                match curr_ref.next.compare_exchange(
+                    next,
+                    succ.with_tag(1),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    &guard,
+                ) {
+                    Ok(_) => break Some(succ),
+                    Err(e) if e.current.tag() == 0 => next = e.current,
+                    Err(_) => break None,
+                }
+            };
+
+            let succ = match succ {
+                Some(succ) => succ,
+                None => {
+                    // another thread already replaced or removed curr, so compute again
+                    if let Some(node) = node {
+                        drop(unsafe { node.into_owned() });
+                    }
+
+                    continue;
+                }
+            };
+
+            // the thread that marks the node returns the value
+            let value = curr_ref.value.clone();
+
+            // try physical removal. If failed, the other thread will do on searching.
+            if cursor
+                .prev
+                .compare_exchange(
+                    cursor.curr,
+                    succ,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                    &guard,
+                )
+                .is_ok()
+            {
+                unsafe { guard.defer_destroy(cursor.curr) };
+            }
+
+            return Some(value);
+        }
+    }
+}
+
+impl<K, V> Drop for HarrisList<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = unprotected();
+
+            let mut node = self.head.load(Ordering::Relaxed, guard);
+
+            while !node.is_null() {
+                let next = node.deref().next.load(Ordering::Relaxed, guard);
+                drop(node.into_owned());
+
+                node = next.with_tag(0);
+            }
+        }
+    }
+}
@@ -1,4 +1,14 @@
-use crate::map::SequentialMap;
+mod handoverhand;
+mod lazy;
+mod lockfree;
+
+pub use handoverhand::HandOverHandList;
+pub use lazy::LazyList;
+pub use lockfree::HarrisList;
+
+use std::borrow::Borrow;
+
+use crate::map::{SequentialLookup, SequentialMap};
 
 // simple sequential linked list
 pub struct LinkedList<K, V> {
@@ -29,7 +39,7 @@ impl<K, V> Node<K, V> {
 
 impl<K, V> SequentialMap<K, V> for LinkedList<K, V>
 where
-    K: Default + Eq + Clone,
+    K: Default + Eq,
     V: Default,
 {
     fn new() -> LinkedList<K, V> {
@@ -38,16 +48,16 @@ where
         }
     }
 
-    fn insert(&mut self, key: &K, value: V) -> Result<(), V> {
-        let new = Box::new(Node::new(key.clone(), value));
+    fn insert(&mut self, key: K, value: V) -> Result<(), (K, V)> {
+        let new = Box::new(Node::new(key, value));
 
         let mut current = &mut self.head.next;
 
         loop {
             match current {
                 Some(node) => {
-                    if node.key == *key {
-                        return Err(new.value);
+                    if node.key == new.key {
+                        return Err((new.key, new.value));
                     }
 
                     current = &mut node.next;
@@ -59,8 +69,15 @@ where
             }
         }
     }
+}
 
-    fn lookup(&self, key: &K) -> Option<&V> {
+impl<K, V, Q> SequentialLookup<K, V, Q> for LinkedList<K, V>
+where
+    K: Default + Eq + Borrow<Q>,
+    V: Default,
+    Q: ?Sized + Eq,
+{
+    fn lookup(&self, key: &Q) -> Option<&V> {
         let mut current = &self.head.next;
 
         loop {
@@ -68,7 +85,7 @@ where
                 Some(node) => {
                     let value = &node.value;
 
-                    if node.key == *key {
+                    if node.key.borrow() == key {
                         return Some(value);
                     }
 
@@ -79,13 +96,13 @@ where
         }
     }
 
-    fn remove(&mut self, key: &K) -> Result<V, ()> {
+    fn remove(&mut self, key: &Q) -> Result<V, ()> {
         let mut prev = &mut self.head;
 
         loop {
             match prev.next.is_some() {
                 true => {
-                    if prev.next.as_ref().unwrap().key == *key {
+                    if prev.next.as_ref().unwrap().key.borrow() == key {
                         let mut node = prev.next.take();
                         prev.next = node.as_mut().unwrap().next.take();
 
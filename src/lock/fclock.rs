@@ -13,7 +13,7 @@ use crossbeam_epoch::{pin, unprotected, Atomic, Guard, Owned, Shared};
 use crossbeam_utils::{Backoff, CachePadded};
 use thread_local::ThreadLocal;
 
-use super::RawSimpleLock;
+use super::{LockGuard, RawSimpleLock};
 
 pub trait FlatCombining<T> {
     fn apply(&mut self, operation: T) -> T;
@@ -339,6 +339,17 @@ impl<T: Send + Sync, L: RawSimpleLock> FCLock<T, L> {
         }
     }
 
+    /// apply the operation on the target in the calling thread while holding the lock
+    ///
+    /// Unlike `try_combine`, the operation is never applied by another thread.
+    /// The lock is released even if applying the operation panics.
+    pub fn apply_locally(&self, operation: T) -> T {
+        let _guard = LockGuard::new(&*self.lock);
+
+        // Since the lock is held, no combiner can access the target.
+        unsafe { (*self.target.get()).apply(operation) }
+    }
+
     #[cfg(feature = "concurrent_stat")]
     pub fn print_stat(&self) {
         println!("{:?}", self.stat);
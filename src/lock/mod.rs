@@ -20,3 +20,22 @@ pub unsafe trait RawSimpleLock {
     /// Release lock
     fn unlock(&self);
 }
+
+/// the guard of RawSimpleLock which releases the lock on drop, even if the code holding the lock panics
+pub(crate) struct LockGuard<'l, L: RawSimpleLock> {
+    lock: &'l L,
+}
+
+impl<'l, L: RawSimpleLock> LockGuard<'l, L> {
+    pub(crate) fn new(lock: &'l L) -> Self {
+        lock.lock();
+
+        Self { lock }
+    }
+}
+
+impl<'l, L: RawSimpleLock> Drop for LockGuard<'l, L> {
+    fn drop(&mut self) {
+        self.lock.unlock();
+    }
+}
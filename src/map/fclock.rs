@@ -0,0 +1,241 @@
+use std::{
+    marker::PhantomData,
+    mem,
+    panic::{self, AssertUnwindSafe},
+};
+
+use crossbeam_epoch::pin;
+
+use crate::lock::{
+    fclock::{FCLock, FlatCombining},
+    RawSimpleLock,
+};
+
+use super::{
+    compute_on, get_or_insert_on, ConcurrentLookup, ConcurrentMap, SequentialLookup, SequentialMap,
+};
+use crate::util::counter::ShardedCounter;
+
+/// The operation is the function on the map, since the key and the function of lookup are generic per call.
+enum MapOp<K, V, M> {
+    Request(Box<dyn FnOnce(&mut M)>),
+    Response(PhantomData<(K, V)>),
+}
+
+// The operation sent to the combiner thread is checked to be Send by `FCMap::execute`.
+// The operation with the caller's function may not be Send, but it is applied by the caller itself with
+// `FCLock::apply_locally`, so it never moves to another thread. The operation is never shared.
+unsafe impl<K, V, M> Send for MapOp<K, V, M> {}
+unsafe impl<K, V, M> Sync for MapOp<K, V, M> {}
+
+impl<K: Eq, V, M: SequentialMap<K, V>> FlatCombining<MapOp<K, V, M>> for M {
+    fn apply(&mut self, operation: MapOp<K, V, M>) -> MapOp<K, V, M> {
+        match operation {
+            MapOp::Request(f) => {
+                f(self);
+                MapOp::Response(PhantomData)
+            }
+            MapOp::Response(_) => unreachable!("The response cannot be applied."),
+        }
+    }
+}
+
+/// Concurrent map which executes the operations of SequentialMap by flat combining
+///
+/// The operations by the key are executed by the combiner, which may be another thread than the caller.
+/// If the operation panics, the combiner catches it and the caller resumes it after the lock is released.
+/// The operations with the caller's function, `lookup`, `compute` and `get_or_insert_with`, are executed by the
+/// caller itself while holding the lock, so the function and its result need not be sendable.
+pub struct FCMap<K, V, L: RawSimpleLock, M: SequentialMap<K, V>>
+where
+    K: Eq,
+{
+    map: FCLock<MapOp<K, V, M>, L>,
+    count: ShardedCounter,
+}
+
+unsafe impl<K, V, L, M> Send for FCMap<K, V, L, M>
+where
+    K: Eq + Send,
+    V: Send,
+    L: RawSimpleLock,
+    M: SequentialMap<K, V> + Send,
+{
+}
+unsafe impl<K, V, L, M> Sync for FCMap<K, V, L, M>
+where
+    K: Eq + Send,
+    V: Send,
+    L: RawSimpleLock,
+    M: SequentialMap<K, V> + Send,
+{
+}
+
+impl<K: Eq, V, L: RawSimpleLock, M: SequentialMap<K, V>> FCMap<K, V, L, M> {
+    /// execute the function on the map by the combiner, and return its result
+    fn execute<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut M) -> R + Send,
+        R: Send,
+    {
+        let guard = pin();
+        let mut result = None;
+
+        {
+            let result = &mut result;
+            // the panic is caught not to unwind the combiner holding the lock
+            let operation: Box<dyn FnOnce(&mut M) + Send + '_> = Box::new(move |map: &mut M| {
+                *result = Some(panic::catch_unwind(AssertUnwindSafe(|| f(map))))
+            });
+
+            // The thread waits until the combiner responds, so the operation never outlives the borrowed ones.
+            let operation: Box<dyn FnOnce(&mut M)> = unsafe { mem::transmute(operation) };
+
+            let record = self.map.acquire_record(&guard);
+            let record_ref = unsafe { record.deref() };
+
+            record_ref.set(MapOp::Request(operation));
+
+            self.map.try_combine(record, &guard);
+        }
+
+        match result.unwrap() {
+            Ok(result) => result,
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+
+    /// execute the function on the map in the calling thread while holding the lock, and return its result
+    fn execute_locally<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut M) -> R,
+    {
+        let mut result = None;
+
+        {
+            let result = &mut result;
+            let operation: Box<dyn FnOnce(&mut M) + '_> =
+                Box::new(move |map: &mut M| *result = Some(f(map)));
+
+            // The operation is applied before returning, so it never outlives the borrowed ones.
+            let operation: Box<dyn FnOnce(&mut M)> = unsafe { mem::transmute(operation) };
+
+            self.map.apply_locally(MapOp::Request(operation));
+        }
+
+        result.unwrap()
+    }
+
+    /// the number of keys on the map
+    ///
+    /// It is exact when no operation is running, but approximate while other threads insert or remove.
+    pub fn len(&self) -> usize {
+        self.count.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[cfg(feature = "concurrent_stat")]
+    pub fn print_stat(&self) {
+        self.map.print_stat();
+    }
+}
+
+impl<K, V, L, M> ConcurrentMap<K, V> for FCMap<K, V, L, M>
+where
+    K: 'static + Eq + Send + Sync,
+    V: 'static + Send,
+    L: RawSimpleLock,
+    M: 'static + SequentialMap<K, V>,
+{
+    fn new() -> Self {
+        Self {
+            map: FCLock::new(M::new()),
+            count: ShardedCounter::new(),
+        }
+    }
+
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
+        let result = self.execute(|map| map.insert(key, value));
+
+        if result.is_ok() {
+            self.count.increment();
+        }
+
+        result
+    }
+}
+
+impl<K, V, L, M, Q> ConcurrentLookup<K, V, Q> for FCMap<K, V, L, M>
+where
+    K: 'static + Eq,
+    V: 'static + Send,
+    L: RawSimpleLock,
+    M: 'static + SequentialMap<K, V> + SequentialLookup<K, V, Q>,
+    Q: ?Sized + Sync,
+{
+    fn lookup<F, R>(&self, key: &Q, f: F) -> R
+    where
+        F: FnOnce(Option<&V>) -> R,
+    {
+        self.execute_locally(|map| f(map.lookup(key)))
+    }
+
+    fn get(&self, key: &Q) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.execute(|map| map.lookup(key).cloned())
+    }
+
+    fn remove(&self, key: &Q) -> Result<V, ()> {
+        let result = self.execute(|map| map.remove(key));
+
+        if result.is_ok() {
+            self.count.decrement();
+        }
+
+        result
+    }
+
+    fn upsert(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+    {
+        let (old, _) = self.execute_locally(|map| compute_on(map, key, |_| Some(value)));
+
+        if old.is_none() {
+            self.count.increment();
+        }
+
+        old
+    }
+
+    fn compute<F>(&self, key: &Q, f: F) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        let (old, exists) = self.execute_locally(|map| compute_on(map, key, f));
+        self.count.add(exists as isize - old.is_some() as isize);
+
+        old
+    }
+
+    fn get_or_insert_with<F>(&self, key: &Q, f: F) -> V
+    where
+        Q: ToOwned<Owned = K>,
+        V: Clone,
+        F: FnOnce() -> V,
+    {
+        let (value, inserted) = self.execute_locally(|map| get_or_insert_on(map, key, f));
+
+        if inserted {
+            self.count.increment();
+        }
+
+        value
+    }
+}
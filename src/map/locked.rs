@@ -0,0 +1,275 @@
+use std::{
+    cell::UnsafeCell,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use parking_lot::RwLock;
+
+use super::{
+    compute_on, get_or_insert_on, ConcurrentLookup, ConcurrentMap, SequentialLookup, SequentialMap,
+};
+use crate::lock::{LockGuard, RawSimpleLock};
+
+/// Concurrent map which guards the whole SequentialMap by a single lock
+///
+/// Every operation holds the lock, so it is linearizable but never runs in parallel.
+pub struct Locked<L: RawSimpleLock, M> {
+    lock: L,
+    map: UnsafeCell<M>,
+    count: AtomicUsize,
+}
+
+unsafe impl<L: RawSimpleLock + Send, M: Send> Send for Locked<L, M> {}
+unsafe impl<L: RawSimpleLock + Sync, M: Send> Sync for Locked<L, M> {}
+
+impl<L: RawSimpleLock, M> Locked<L, M> {
+    /// execute the function on the map while holding the lock
+    fn with_map<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut M, &AtomicUsize) -> R,
+    {
+        let _guard = LockGuard::new(&self.lock);
+
+        // Since the lock is held, no one can access the map.
+        f(unsafe { &mut *self.map.get() }, &self.count)
+    }
+
+    /// the number of keys on the map
+    pub fn len(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K, V, L, M> ConcurrentMap<K, V> for Locked<L, M>
+where
+    K: Eq,
+    L: RawSimpleLock,
+    M: SequentialMap<K, V>,
+{
+    fn new() -> Self {
+        Self {
+            lock: L::new(),
+            map: UnsafeCell::new(M::new()),
+            count: AtomicUsize::new(0),
+        }
+    }
+
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
+        self.with_map(|map, count| {
+            let result = map.insert(key, value);
+
+            if result.is_ok() {
+                count.fetch_add(1, Ordering::Relaxed);
+            }
+
+            result
+        })
+    }
+}
+
+impl<K, V, L, M, Q> ConcurrentLookup<K, V, Q> for Locked<L, M>
+where
+    K: Eq,
+    L: RawSimpleLock,
+    M: SequentialMap<K, V> + SequentialLookup<K, V, Q>,
+    Q: ?Sized,
+{
+    fn lookup<F, R>(&self, key: &Q, f: F) -> R
+    where
+        F: FnOnce(Option<&V>) -> R,
+    {
+        self.with_map(|map, _| f(map.lookup(key)))
+    }
+
+    fn get(&self, key: &Q) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.with_map(|map, _| map.lookup(key).cloned())
+    }
+
+    fn remove(&self, key: &Q) -> Result<V, ()> {
+        self.with_map(|map, count| {
+            let result = map.remove(key);
+
+            if result.is_ok() {
+                count.fetch_sub(1, Ordering::Relaxed);
+            }
+
+            result
+        })
+    }
+
+    fn upsert(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+    {
+        let mut value = Some(value);
+
+        // the function is called only once under the lock
+        self.compute(key, |_| value.take())
+    }
+
+    fn compute<F>(&self, key: &Q, f: F) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        self.with_map(|map, count| {
+            let (old, exists) = compute_on(map, key, f);
+
+            match (old.is_some(), exists) {
+                (false, true) => count.fetch_add(1, Ordering::Relaxed),
+                (true, false) => count.fetch_sub(1, Ordering::Relaxed),
+                _ => 0,
+            };
+
+            old
+        })
+    }
+
+    fn get_or_insert_with<F>(&self, key: &Q, f: F) -> V
+    where
+        Q: ToOwned<Owned = K>,
+        V: Clone,
+        F: FnOnce() -> V,
+    {
+        self.with_map(|map, count| {
+            let (value, inserted) = get_or_insert_on(map, key, f);
+
+            if inserted {
+                count.fetch_add(1, Ordering::Relaxed);
+            }
+
+            value
+        })
+    }
+}
+
+/// Concurrent map which guards the whole SequentialMap by a reader-writer lock
+///
+/// The lookups share the read lock, so they run in parallel unless a writer holds the lock.
+/// It is shared across threads only if the map is Sync, i.e. its lookups do not mutate it.
+pub struct RwLocked<M> {
+    map: RwLock<M>,
+    count: AtomicUsize,
+}
+
+impl<M> RwLocked<M> {
+    /// the number of keys on the map
+    pub fn len(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K, V, M> ConcurrentMap<K, V> for RwLocked<M>
+where
+    K: Eq,
+    M: SequentialMap<K, V>,
+{
+    fn new() -> Self {
+        Self {
+            map: RwLock::new(M::new()),
+            count: AtomicUsize::new(0),
+        }
+    }
+
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
+        let mut map = self.map.write();
+        let result = map.insert(key, value);
+
+        if result.is_ok() {
+            self.count.fetch_add(1, Ordering::Relaxed);
+        }
+
+        result
+    }
+}
+
+impl<K, V, M, Q> ConcurrentLookup<K, V, Q> for RwLocked<M>
+where
+    K: Eq,
+    M: SequentialMap<K, V> + SequentialLookup<K, V, Q>,
+    Q: ?Sized,
+{
+    fn lookup<F, R>(&self, key: &Q, f: F) -> R
+    where
+        F: FnOnce(Option<&V>) -> R,
+    {
+        f(self.map.read().lookup(key))
+    }
+
+    fn get(&self, key: &Q) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.map.read().lookup(key).cloned()
+    }
+
+    fn remove(&self, key: &Q) -> Result<V, ()> {
+        let mut map = self.map.write();
+        let result = map.remove(key);
+
+        if result.is_ok() {
+            self.count.fetch_sub(1, Ordering::Relaxed);
+        }
+
+        result
+    }
+
+    fn upsert(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+    {
+        let mut value = Some(value);
+
+        // the function is called only once under the lock
+        self.compute(key, |_| value.take())
+    }
+
+    fn compute<F>(&self, key: &Q, f: F) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        let mut map = self.map.write();
+        let (old, exists) = compute_on(&mut *map, key, f);
+
+        match (old.is_some(), exists) {
+            (false, true) => self.count.fetch_add(1, Ordering::Relaxed),
+            (true, false) => self.count.fetch_sub(1, Ordering::Relaxed),
+            _ => 0,
+        };
+
+        old
+    }
+
+    fn get_or_insert_with<F>(&self, key: &Q, f: F) -> V
+    where
+        Q: ToOwned<Owned = K>,
+        V: Clone,
+        F: FnOnce() -> V,
+    {
+        // the value is usually on the map, so try the read lock first
+        if let Some(value) = self.map.read().lookup(key) {
+            return value.clone();
+        }
+
+        let mut map = self.map.write();
+        let (value, inserted) = get_or_insert_on(&mut *map, key, f);
+
+        if inserted {
+            self.count.fetch_add(1, Ordering::Relaxed);
+        }
+
+        value
+    }
+}
@@ -0,0 +1,276 @@
+mod fclock;
+mod locked;
+
+pub use fclock::FCMap;
+pub use locked::{Locked, RwLocked};
+
+use std::borrow::Borrow;
+
+/// Key-value map which is only used in a single thread
+///
+/// The key is found by `SequentialLookup<K, V, K>`, and may also be found by the borrowed forms of K.
+pub trait SequentialMap<K: Eq, V>: SequentialLookup<K, V> {
+    fn new() -> Self;
+
+    /// Insert (key, vaule) into the map.
+    ///
+    /// If success, return Ok(()).
+    /// If fail, return Err((key, value)) that you tried to insert.
+    fn insert(&mut self, key: K, value: V) -> Result<(), (K, V)>;
+}
+
+/// Lookup and remove of `SequentialMap` by the key borrowed as Q
+///
+/// The key is borrowed as Q, so that `String` keys can be found by `&str`.
+/// Each map implements it for the Q which is consistent with K by `Borrow` in its own way,
+/// e.g. `Q: Ord` for the ordered maps and `Q: Hash + Eq` for the hashed maps.
+pub trait SequentialLookup<K: Eq, V, Q: ?Sized = K> {
+    /// Lookup (key, value) from the map with the key.
+    ///
+    /// If success, return the reference of the value.
+    /// If fail, return None.
+    fn lookup(&self, key: &Q) -> Option<&V>;
+
+    /// Remove (key, value) from the map with the key.
+    ///
+    /// If success, return Ok(value) which is inserted before.
+    /// If fail, return Err(()).
+    fn remove(&mut self, key: &Q) -> Result<V, ()>;
+}
+
+/// Key-value map which can be shared among threads
+///
+/// Like `SequentialMap`, the key is found by `ConcurrentLookup<K, V, K>`, and may also be found by the borrowed forms.
+pub trait ConcurrentMap<K: Eq, V>: ConcurrentLookup<K, V> {
+    fn new() -> Self;
+
+    /// Insert (key, vaule) into the map.
+    ///
+    /// If success, return Ok(()).
+    /// If fail, return Err((key, value)) that you tried to insert.
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)>;
+}
+
+/// Operations of `ConcurrentMap` by the key borrowed as Q
+///
+/// Like `SequentialLookup`, each map implements it for the Q which its lookup needs.
+/// The methods which may insert the key make the owned key from Q only on inserting.
+pub trait ConcurrentLookup<K: Eq, V, Q: ?Sized = K> {
+    /// Lookup (key, value) from the map with the key.
+    ///
+    /// Execute function with the reference of the value, or None if it failed to find.
+    fn lookup<F, R>(&self, key: &Q, f: F) -> R
+    where
+        F: FnOnce(Option<&V>) -> R;
+
+    /// Lookup (key, value) from the map with the key
+    ///
+    /// If success, return the copy of value
+    /// If fail, return None
+    fn get(&self, key: &Q) -> Option<V>
+    where
+        V: Clone;
+
+    /// Remove (key, value) from the map with the key.
+    ///
+    /// If success, return Ok(value) which is inserted before.
+    /// If fail, return Err(()).
+    fn remove(&self, key: &Q) -> Result<V, ()>;
+
+    /// Insert (key, value) into the map, or replace the value if the key exists.
+    ///
+    /// Return the replaced value, or None if the key did not exist.
+    fn upsert(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        Self: ConcurrentMap<K, V>;
+
+    /// Compute the new value of the key from the current one atomically.
+    ///
+    /// The function gets the reference of the current value, or None if the key does not exist.
+    /// If it returns Some(value), the value is inserted or replaced. If it returns None, the key is removed.
+    /// Return the value before computing.
+    ///
+    /// The lock-free maps call the function without any lock, so they call it again if another thread changes the
+    /// key before the result is stored. Only the result of the last call is stored.
+    fn compute<F>(&self, key: &Q, f: F) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        F: FnMut(Option<&V>) -> Option<V>,
+        Self: ConcurrentMap<K, V>;
+
+    /// Lookup the value of the key, or insert the value made by the function if the key does not exist.
+    ///
+    /// Return the copy of the value on the map.
+    fn get_or_insert_with<F>(&self, key: &Q, f: F) -> V
+    where
+        Q: ToOwned<Owned = K>,
+        V: Clone,
+        F: FnOnce() -> V,
+        Self: ConcurrentMap<K, V>,
+    {
+        let mut f = Some(f);
+        let mut value = None;
+
+        loop {
+            if let Some(current) = self.get(key) {
+                return current;
+            }
+
+            let new = value.take().unwrap_or_else(|| (f.take().unwrap())());
+
+            match self.insert(key.to_owned(), new.clone()) {
+                Ok(()) => return new,
+                Err((_, new)) => value = Some(new), // inserted by another thread, so read it again
+            }
+        }
+    }
+
+    /// Replace the value of the key with the new value only if the value is equal to the current.
+    ///
+    /// If success, return Ok(value) which is replaced.
+    /// If fail, return Err(new) that you tried to replace with.
+    fn compare_and_swap_value(&self, key: &Q, current: &V, new: V) -> Result<V, V>
+    where
+        Q: ToOwned<Owned = K>,
+        V: Clone + PartialEq,
+        Self: ConcurrentMap<K, V>,
+    {
+        let mut swapped = false;
+
+        // the current value is written back on mismatch, since the function may be called again
+        let old = self.compute(key, |value| {
+            swapped = matches!(value, Some(value) if value == current);
+
+            if swapped {
+                Some(new.clone())
+            } else {
+                value.cloned()
+            }
+        });
+
+        if swapped {
+            Ok(old.unwrap())
+        } else {
+            Err(new)
+        }
+    }
+}
+
+/// Key-value map which keeps the keys in the ascending order, and is only used in a single thread
+pub trait OrderedMap<K: Ord, V>: SequentialMap<K, V> {
+    /// Return (key, value) with the least key, or None if the map is empty.
+    fn first(&self) -> Option<(&K, &V)>;
+
+    /// Return (key, value) with the greatest key, or None if the map is empty.
+    fn last(&self) -> Option<(&K, &V)>;
+
+    /// Remove (key, value) with the least key, or return None if the map is empty.
+    fn pop_first(&mut self) -> Option<(K, V)>;
+
+    /// Remove (key, value) with the greatest key, or return None if the map is empty.
+    fn pop_last(&mut self) -> Option<(K, V)>;
+
+    /// Return (key, value) with the greatest key less than or equal to the key.
+    fn floor<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord;
+
+    /// Return (key, value) with the least key greater than or equal to the key.
+    fn ceiling<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord;
+}
+
+/// Key-value map which keeps the keys in the ascending order, and can be shared among threads
+///
+/// Every method is linearizable. The returned entry is on the map at the linearization point,
+/// and no entry between it and the given key exists at the moment.
+/// Unlike `OrderedMap`, the entry is returned as a copy since other threads may remove it.
+pub trait ConcurrentOrderedMap<K: Ord, V>: ConcurrentMap<K, V> {
+    /// Return the copy of (key, value) with the least key, or None if the map is empty.
+    fn first(&self) -> Option<(K, V)>
+    where
+        K: Clone,
+        V: Clone;
+
+    /// Return the copy of (key, value) with the greatest key, or None if the map is empty.
+    fn last(&self) -> Option<(K, V)>
+    where
+        K: Clone,
+        V: Clone;
+
+    /// Remove (key, value) with the least key, or return None if the map is empty.
+    fn pop_first(&self) -> Option<(K, V)>
+    where
+        K: Clone;
+
+    /// Remove (key, value) with the greatest key, or return None if the map is empty.
+    fn pop_last(&self) -> Option<(K, V)>
+    where
+        K: Clone;
+
+    /// Return the copy of (key, value) with the greatest key less than or equal to the key.
+    fn floor<Q>(&self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Ord,
+        V: Clone;
+
+    /// Return the copy of (key, value) with the least key greater than or equal to the key.
+    fn ceiling<Q>(&self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q> + Clone,
+        Q: ?Sized + Ord,
+        V: Clone;
+}
+
+/// compute the new value of the key on the sequential map like `ConcurrentMap::compute`
+///
+/// Return the value before computing, and whether the key exists after computing.
+pub(crate) fn compute_on<K, V, M, Q, F>(map: &mut M, key: &Q, f: F) -> (Option<V>, bool)
+where
+    K: Eq,
+    M: SequentialMap<K, V> + SequentialLookup<K, V, Q>,
+    Q: ?Sized + ToOwned<Owned = K>,
+    F: FnOnce(Option<&V>) -> Option<V>,
+{
+    // compute before changing the map, so the map is not changed if the function panics
+    let new = f(map.lookup(key));
+    let old = map.remove(key).ok();
+
+    match new {
+        Some(value) => {
+            // the key is just removed, so inserting never fails
+            let result = map.insert(key.to_owned(), value);
+            debug_assert!(result.is_ok());
+
+            (old, true)
+        }
+        None => (old, false),
+    }
+}
+
+/// lookup the value of the key on the sequential map, or insert the value made by the function
+///
+/// Return the copy of the value, and whether it is inserted.
+pub(crate) fn get_or_insert_on<K, V, M, Q, F>(map: &mut M, key: &Q, f: F) -> (V, bool)
+where
+    K: Eq,
+    V: Clone,
+    M: SequentialMap<K, V> + SequentialLookup<K, V, Q>,
+    Q: ?Sized + ToOwned<Owned = K>,
+    F: FnOnce() -> V,
+{
+    if let Some(value) = map.lookup(key) {
+        return (value.clone(), false);
+    }
+
+    let value = f();
+    let result = map.insert(key.to_owned(), value.clone());
+    debug_assert!(result.is_ok());
+
+    (value, true)
+}
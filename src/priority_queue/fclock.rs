@@ -0,0 +1,142 @@
+use std::{hint::unreachable_unchecked, marker::PhantomData};
+
+use crossbeam_epoch::pin;
+use crossbeam_utils::Backoff;
+
+use crate::lock::{
+    fclock::{FCLock, FlatCombining},
+    RawSimpleLock,
+};
+
+use super::{ConcurrentPriorityQueue, SequentialPriorityQueue};
+use crate::util::counter::ShardedCounter;
+
+#[derive(Debug, PartialEq)]
+enum PriorityQueueOp<K, V> {
+    PushRequest(K, V),
+    PushResponse,
+    PopRequest,
+    PopResponse(Option<(K, V)>),
+}
+
+unsafe impl<K, V> Send for PriorityQueueOp<K, V> {}
+unsafe impl<K, V> Sync for PriorityQueueOp<K, V> {}
+
+impl<K: Ord, V, Q: SequentialPriorityQueue<K, V>> FlatCombining<PriorityQueueOp<K, V>> for Q {
+    fn apply(&mut self, operation: PriorityQueueOp<K, V>) -> PriorityQueueOp<K, V> {
+        match operation {
+            PriorityQueueOp::PushRequest(key, value) => {
+                self.push(key, value);
+                PriorityQueueOp::PushResponse
+            }
+            PriorityQueueOp::PopRequest => PriorityQueueOp::PopResponse(self.pop_min()),
+            _ => unreachable!("The response cannot be applied."),
+        }
+    }
+}
+
+/// priority queue which applies the operations on the sequential priority queue by flat combining
+///
+/// It is linearizable since the combiner applies the operations one by one.
+pub struct FCPriorityQueue<K: Ord, V, L: RawSimpleLock, Q: SequentialPriorityQueue<K, V>> {
+    queue: FCLock<PriorityQueueOp<K, V>, L>,
+    count: ShardedCounter,
+    _marker: PhantomData<Q>,
+}
+
+unsafe impl<K, V, L, Q> Send for FCPriorityQueue<K, V, L, Q>
+where
+    K: Ord + Send,
+    V: Send,
+    L: RawSimpleLock,
+    Q: SequentialPriorityQueue<K, V> + Send,
+{
+}
+unsafe impl<K, V, L, Q> Sync for FCPriorityQueue<K, V, L, Q>
+where
+    K: Ord + Send,
+    V: Send,
+    L: RawSimpleLock,
+    Q: SequentialPriorityQueue<K, V> + Send,
+{
+}
+
+impl<K: Ord, V, L: RawSimpleLock, Q: SequentialPriorityQueue<K, V>> FCPriorityQueue<K, V, L, Q> {
+    /// the number of values on the queue
+    ///
+    /// It is exact when no operation is running, but approximate while other threads push or pop.
+    pub fn len(&self) -> usize {
+        self.count.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[cfg(feature = "concurrent_stat")]
+    pub fn print_stat(&self) {
+        self.queue.print_stat();
+    }
+}
+
+impl<K, V, L, Q> ConcurrentPriorityQueue<K, V> for FCPriorityQueue<K, V, L, Q>
+where
+    K: 'static + Ord,
+    V: 'static,
+    L: RawSimpleLock,
+    Q: 'static + SequentialPriorityQueue<K, V>,
+{
+    fn new() -> Self {
+        let queue = Q::new();
+
+        Self {
+            queue: FCLock::new(queue),
+            count: ShardedCounter::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn push(&self, key: K, value: V) {
+        let guard = pin();
+
+        let record = self.queue.acquire_record(&guard);
+        let record_ref = unsafe { record.deref() };
+
+        record_ref.set(PriorityQueueOp::PushRequest(key, value));
+
+        self.queue.try_combine(record, &guard);
+        self.count.increment();
+    }
+
+    fn try_pop_min(&self) -> Option<(K, V)> {
+        let guard = pin();
+
+        let record = self.queue.acquire_record(&guard);
+        let record_ref = unsafe { record.deref() };
+
+        record_ref.set(PriorityQueueOp::PopRequest);
+
+        self.queue.try_combine(record, &guard);
+
+        if let PriorityQueueOp::PopResponse(entry) = record_ref.get_operation(&guard) {
+            if entry.is_some() {
+                self.count.decrement();
+            }
+
+            entry
+        } else {
+            unsafe { unreachable_unchecked() }
+        }
+    }
+
+    fn pop_min(&self) -> (K, V) {
+        let backoff = Backoff::new();
+
+        loop {
+            match self.try_pop_min() {
+                Some(entry) => return entry,
+                None => backoff.snooze(),
+            }
+        }
+    }
+}
@@ -0,0 +1,363 @@
+/*
+ Refer to
+ The Art of Multiprocessor Programming, 15.5 A Skiplist-Based Unbounded Priority Queue and
+ https://doi.org/10.1109/IPDPS.2000.845994
+*/
+
+use std::{
+    mem::ManuallyDrop,
+    ptr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crossbeam_epoch::{pin, unprotected, Atomic, Guard, Owned, Shared};
+use crossbeam_utils::{Backoff, CachePadded};
+use rand::{thread_rng, Rng};
+
+use super::ConcurrentPriorityQueue;
+use crate::util::counter::ShardedCounter;
+
+const MAX_HEIGHT: usize = 32;
+
+/// get random height in [1, MAX_HEIGHT] whose probability of height h is 1/2^h
+#[inline]
+fn random_height() -> usize {
+    let random: u32 = thread_rng().gen();
+    (random.trailing_ones() as usize + 1).min(MAX_HEIGHT)
+}
+
+/// Lock-free skip list priority queue by Lotan and Shavit
+///
+/// The nodes are sorted by the key, then by the push order, so the same key can be pushed several times.
+/// Popping claims the first unclaimed node on level 0 by marking its next pointer with the tag 1,
+/// then unlinks it on all levels as the lock-free skip list does.
+/// Since the node cannot be freed while other threads traverse it, the key is cloned to be returned.
+///
+/// It is quiescently consistent, not linearizable:
+/// the pop can miss the key pushed concurrently behind its traversal and return a greater key instead.
+pub struct SkipListPriorityQueue<K, V> {
+    head: [Atomic<Node<K, V>>; MAX_HEIGHT],
+    /// the ticket for the next push, which breaks the ties of the same keys
+    ticket: CachePadded<AtomicUsize>,
+    count: ShardedCounter,
+}
+
+struct Node<K, V> {
+    key: K,
+    ticket: usize,
+    value: ManuallyDrop<V>,
+    next: Box<[Atomic<Node<K, V>>]>,
+    /// the number of the levels which the node is linked on, and 1 for the pushing thread
+    ///
+    /// If it becomes 0, no one can reach the node newly, so it can be destroyed.
+    refs: AtomicUsize,
+}
+
+impl<K: Ord, V> Node<K, V> {
+    fn new(key: K, ticket: usize, value: V, height: usize) -> Self {
+        Self {
+            key,
+            ticket,
+            value: ManuallyDrop::new(value),
+            next: (0..height).map(|_| Atomic::null()).collect(),
+            refs: AtomicUsize::new(1),
+        }
+    }
+
+    #[inline]
+    fn height(&self) -> usize {
+        self.next.len()
+    }
+
+    #[inline]
+    fn is_removed(&self, guard: &Guard) -> bool {
+        self.next[0].load(Ordering::Acquire, guard).tag() == 1
+    }
+
+    #[inline]
+    fn is_less(&self, key: &K, ticket: usize) -> bool {
+        (&self.key, self.ticket) < (key, ticket)
+    }
+
+    /// decrease the reference count, then destroy it if no one can reach it
+    fn release(node: Shared<Node<K, V>>, guard: &Guard) {
+        unsafe {
+            if node.deref().refs.fetch_sub(1, Ordering::AcqRel) == 1 {
+                guard.defer_destroy(node);
+            }
+        }
+    }
+}
+
+/// the result of searching the key and the ticket
+///
+/// preds: the links of the last node which is less than the key and the ticket on each level
+/// succs: the first node which is equal or greater than the key and the ticket on each level
+struct Cursor<'g, K, V> {
+    preds: [&'g Atomic<Node<K, V>>; MAX_HEIGHT],
+    succs: [Shared<'g, Node<K, V>>; MAX_HEIGHT],
+}
+
+impl<K, V> Default for SkipListPriorityQueue<K, V> {
+    fn default() -> Self {
+        Self {
+            head: Default::default(),
+            ticket: CachePadded::new(AtomicUsize::new(0)),
+            count: ShardedCounter::new(),
+        }
+    }
+}
+
+impl<K: Ord, V> SkipListPriorityQueue<K, V> {
+    /// the number of values on the queue
+    ///
+    /// It is exact when no operation is running, but approximate while other threads push or pop.
+    pub fn len(&self) -> usize {
+        self.count.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let guard = pin();
+        let mut node = self.head[0].load(Ordering::Acquire, &guard);
+
+        while let Some(node_ref) = unsafe { node.as_ref() } {
+            let next = node_ref.next[0].load(Ordering::Acquire, &guard);
+
+            if next.tag() == 0 {
+                return false;
+            }
+
+            node = next.with_tag(0);
+        }
+
+        true
+    }
+
+    /// find the cursor by the key and the ticket
+    ///
+    /// While traversing, the logically removed nodes are physically removed on each level.
+    fn search<'g>(&'g self, key: &K, ticket: usize, guard: &'g Guard) -> Cursor<'g, K, V> {
+        'retry: loop {
+            let mut cursor = Cursor {
+                preds: [&self.head[0]; MAX_HEIGHT],
+                succs: [Shared::null(); MAX_HEIGHT],
+            };
+
+            let mut pred: &'g [Atomic<Node<K, V>>] = &self.head;
+
+            for level in (0..MAX_HEIGHT).rev() {
+                let mut curr = pred[level].load(Ordering::Acquire, guard);
+
+                if curr.tag() == 1 {
+                    // the pred is removed on the level. Retry
+                    continue 'retry;
+                }
+
+                while let Some(curr_ref) = unsafe { curr.as_ref() } {
+                    let succ = curr_ref.next[level].load(Ordering::Acquire, guard);
+
+                    if succ.tag() == 1 {
+                        // curr is logically removed, so unlink it on the level
+                        let succ = succ.with_tag(0);
+
+                        if pred[level]
+                            .compare_exchange(
+                                curr,
+                                succ,
+                                Ordering::AcqRel,
+                                Ordering::Relaxed,
+                                guard,
+                            )
+                            .is_err()
+                        {
+                            continue 'retry;
+                        }
+
+                        Node::release(curr, guard);
+                        curr = succ;
+                        continue;
+                    }
+
+                    if curr_ref.is_less(key, ticket) {
+                        pred = &curr_ref.next;
+                        curr = succ;
+                    } else {
+                        break;
+                    }
+                }
+
+                cursor.preds[level] = &pred[level];
+                cursor.succs[level] = curr;
+            }
+
+            return cursor;
+        }
+    }
+}
+
+impl<K, V> ConcurrentPriorityQueue<K, V> for SkipListPriorityQueue<K, V>
+where
+    K: Ord + Clone,
+{
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(&self, key: K, value: V) {
+        let guard = pin();
+
+        let ticket = self.ticket.fetch_add(1, Ordering::Relaxed);
+        let height = random_height();
+        let node = Owned::new(Node::new(key, ticket, value, height)).into_shared(&guard);
+        let node_ref = unsafe { node.deref() };
+        let key = &node_ref.key;
+
+        // link on level 0. The pair of the key and the ticket is unique, so it always succeeds.
+        let mut cursor = loop {
+            let cursor = self.search(key, ticket, &guard);
+
+            node_ref.next[0].store(cursor.succs[0], Ordering::Relaxed);
+            node_ref.refs.fetch_add(1, Ordering::Relaxed);
+
+            if cursor.preds[0]
+                .compare_exchange(
+                    cursor.succs[0],
+                    node,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                    &guard,
+                )
+                .is_ok()
+            {
+                break cursor;
+            }
+
+            node_ref.refs.fetch_sub(1, Ordering::Relaxed);
+        };
+
+        self.count.increment();
+
+        // link on upper levels
+        'link: for level in 1..height {
+            loop {
+                let next = node_ref.next[level].load(Ordering::Acquire, &guard);
+
+                // If the node is being popped, stop linking.
+                if next.tag() == 1
+                    || node_ref.next[level]
+                        .compare_exchange(
+                            next,
+                            cursor.succs[level],
+                            Ordering::AcqRel,
+                            Ordering::Acquire,
+                            &guard,
+                        )
+                        .is_err()
+                {
+                    break 'link;
+                }
+
+                node_ref.refs.fetch_add(1, Ordering::Relaxed);
+
+                if cursor.preds[level]
+                    .compare_exchange(
+                        cursor.succs[level],
+                        node,
+                        Ordering::Release,
+                        Ordering::Relaxed,
+                        &guard,
+                    )
+                    .is_ok()
+                {
+                    break;
+                }
+
+                node_ref.refs.fetch_sub(1, Ordering::Relaxed);
+
+                cursor = self.search(key, ticket, &guard);
+
+                if cursor.succs[0] != node {
+                    // the node is already popped
+                    break 'link;
+                }
+            }
+        }
+
+        if node_ref.is_removed(&guard) {
+            // the node may be linked after the popper's unlinking. Unlink it again.
+            let _ = self.search(key, ticket, &guard);
+        }
+
+        Node::release(node, &guard);
+    }
+
+    fn try_pop_min(&self) -> Option<(K, V)> {
+        let guard = pin();
+
+        let mut node = self.head[0].load(Ordering::Acquire, &guard);
+
+        while let Some(node_ref) = unsafe { node.as_ref() } {
+            let mut next = node_ref.next[0].load(Ordering::Acquire, &guard);
+
+            // skip the node claimed by another thread without writing on it
+            if next.tag() == 0 {
+                next = node_ref.next[0].fetch_or(1, Ordering::AcqRel, &guard);
+            }
+
+            if next.tag() == 1 {
+                node = next.with_tag(0);
+                continue;
+            }
+
+            // the thread that marks the node on level 0 owns the value
+            for level in (1..node_ref.height()).rev() {
+                let _ = node_ref.next[level].fetch_or(1, Ordering::AcqRel, &guard);
+            }
+
+            let value = unsafe { ptr::read(&node_ref.value) };
+            let key = node_ref.key.clone();
+
+            // unlink the node on all levels
+            let _ = self.search(&node_ref.key, node_ref.ticket, &guard);
+            self.count.decrement();
+
+            return Some((key, ManuallyDrop::into_inner(value)));
+        }
+
+        None
+    }
+
+    fn pop_min(&self) -> (K, V) {
+        let backoff = Backoff::new();
+
+        loop {
+            match self.try_pop_min() {
+                Some(entry) => return entry,
+                None => backoff.snooze(),
+            }
+        }
+    }
+}
+
+impl<K, V> Drop for SkipListPriorityQueue<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = unprotected();
+
+            let mut node = self.head[0].load(Ordering::Relaxed, guard);
+
+            // All nodes which are not destroyed are still linked on level 0.
+            while !node.is_null() {
+                let mut owned = node.into_owned();
+                let next = owned.next[0].load(Ordering::Relaxed, guard);
+
+                // the value of popped node is already moved out
+                if next.tag() == 0 {
+                    ManuallyDrop::drop(&mut owned.value);
+                }
+
+                node = next.with_tag(0);
+            }
+        }
+    }
+}
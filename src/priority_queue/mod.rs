@@ -0,0 +1,125 @@
+mod fclock;
+mod lockfree;
+
+pub use fclock::FCPriorityQueue;
+pub use lockfree::SkipListPriorityQueue;
+
+pub trait SequentialPriorityQueue<K: Ord, V> {
+    fn new() -> Self;
+    fn push(&mut self, key: K, value: V);
+    fn pop_min(&mut self) -> Option<(K, V)>;
+}
+
+pub trait ConcurrentPriorityQueue<K: Ord, V> {
+    fn new() -> Self;
+    fn push(&self, key: K, value: V);
+    /// non-blocking pop of the minimum key that can return `None` when the queue is observed as empty.
+    fn try_pop_min(&self) -> Option<(K, V)>;
+    /// blocking pop of the minimum key that can wait for returning value.
+    fn pop_min(&self) -> (K, V);
+}
+
+/// sequential min pairing heap
+///
+/// The push merges the new node with the root in O(1),
+/// and the pop merges the children of the root by two passes in amortized O(log n).
+pub struct PairingHeap<K, V> {
+    root: Option<Box<Node<K, V>>>,
+    len: usize,
+}
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    children: Vec<Box<Node<K, V>>>,
+}
+
+impl<K: Ord, V> Node<K, V> {
+    fn new(key: K, value: V) -> Box<Self> {
+        Box::new(Self {
+            key,
+            value,
+            children: Vec::new(),
+        })
+    }
+
+    /// link the roots of two heaps, so the greater root becomes the last child of the other
+    fn meld(mut left: Box<Self>, mut right: Box<Self>) -> Box<Self> {
+        if right.key < left.key {
+            right.children.push(left);
+            right
+        } else {
+            left.children.push(right);
+            left
+        }
+    }
+}
+
+impl<K: Ord, V> PairingHeap<K, V> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn peek_min(&self) -> Option<(&K, &V)> {
+        self.root.as_ref().map(|root| (&root.key, &root.value))
+    }
+}
+
+impl<K: Ord, V> SequentialPriorityQueue<K, V> for PairingHeap<K, V> {
+    fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    fn push(&mut self, key: K, value: V) {
+        let node = Node::new(key, value);
+
+        self.root = Some(match self.root.take() {
+            Some(root) => Node::meld(root, node),
+            None => node,
+        });
+        self.len += 1;
+    }
+
+    fn pop_min(&mut self) -> Option<(K, V)> {
+        let root = *self.root.take()?;
+        let Node {
+            key,
+            value,
+            children,
+        } = root;
+
+        // meld the pairs from left to right, then meld them into one from right to left
+        let mut pairs = Vec::new();
+        let mut children = children.into_iter();
+
+        while let Some(left) = children.next() {
+            pairs.push(match children.next() {
+                Some(right) => Node::meld(left, right),
+                None => left,
+            });
+        }
+
+        self.root = pairs
+            .into_iter()
+            .rev()
+            .reduce(|acc, node| Node::meld(node, acc));
+        self.len -= 1;
+
+        Some((key, value))
+    }
+}
+
+impl<K, V> Drop for PairingHeap<K, V> {
+    fn drop(&mut self) {
+        // drop the nodes by the stack, since the heap can be as deep as its size
+        let mut stack: Vec<_> = self.root.take().into_iter().collect();
+
+        while let Some(mut node) = stack.pop() {
+            stack.append(&mut node.children);
+        }
+    }
+}
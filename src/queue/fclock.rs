@@ -9,6 +9,7 @@ use crate::lock::{
 };
 
 use super::{ConcurrentQueue, SequentialQueue};
+use crate::util::counter::ShardedCounter;
 
 #[derive(Debug, PartialEq)]
 enum QueueOp<V> {
@@ -36,6 +37,7 @@ impl<V, Q: SequentialQueue<V>> FlatCombining<QueueOp<V>> for Q {
 
 pub struct FCQueue<V, L: RawSimpleLock, Q: SequentialQueue<V>> {
     queue: FCLock<QueueOp<V>, L>,
+    count: ShardedCounter,
     _marker: PhantomData<Q>,
 }
 
@@ -43,6 +45,17 @@ unsafe impl<V, L: RawSimpleLock, Q: SequentialQueue<V>> Send for FCQueue<V, L, Q
 unsafe impl<V, L: RawSimpleLock, Q: SequentialQueue<V>> Sync for FCQueue<V, L, Q> {}
 
 impl<V, L: RawSimpleLock, Q: SequentialQueue<V>> FCQueue<V, L, Q> {
+    /// the number of values on the queue
+    ///
+    /// It is exact when no operation is running, but approximate while other threads push or pop.
+    pub fn len(&self) -> usize {
+        self.count.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     #[cfg(feature = "concurrent_stat")]
     pub fn print_stat(&self) {
         self.queue.print_stat();
@@ -57,6 +70,7 @@ impl<V: 'static, L: RawSimpleLock, Q: 'static + SequentialQueue<V> + FlatCombini
 
         Self {
             queue: FCLock::new(queue),
+            count: ShardedCounter::new(),
             _marker: PhantomData,
         }
     }
@@ -70,6 +84,7 @@ impl<V: 'static, L: RawSimpleLock, Q: 'static + SequentialQueue<V> + FlatCombini
         record_ref.set(QueueOp::EnqRequest(value));
 
         self.queue.try_combine(record, &guard);
+        self.count.increment();
     }
 
     fn try_pop(&self) -> Option<V> {
@@ -85,6 +100,10 @@ impl<V: 'static, L: RawSimpleLock, Q: 'static + SequentialQueue<V> + FlatCombini
         let operation = record_ref.get_operation(&guard);
 
         if let QueueOp::DeqResponse(value) = operation {
+            if value.is_some() {
+                self.count.decrement();
+            }
+
             value
         } else {
             unsafe { unreachable_unchecked() }
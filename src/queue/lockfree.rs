@@ -10,10 +10,12 @@ use crossbeam_epoch::{pin, unprotected, Atomic, Owned, Shared};
 use crossbeam_utils::{Backoff, CachePadded};
 
 use super::ConcurrentQueue;
+use crate::util::counter::ShardedCounter;
 
 pub struct MSQueue<V> {
     head: CachePadded<Atomic<Node<V>>>,
     tail: CachePadded<Atomic<Node<V>>>,
+    count: ShardedCounter,
 }
 
 struct Node<V> {
@@ -30,11 +32,28 @@ impl<V> Node<V> {
     }
 }
 
+impl<V> MSQueue<V> {
+    /// the number of values on the queue
+    ///
+    /// It is exact when no operation is running, but approximate while other threads push or pop.
+    pub fn len(&self) -> usize {
+        self.count.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let guard = pin();
+        let head = self.head.load(Ordering::Acquire, &guard);
+
+        unsafe { head.deref().next.load(Ordering::Acquire, &guard).is_null() }
+    }
+}
+
 impl<V> ConcurrentQueue<V> for MSQueue<V> {
     fn new() -> Self {
         let queue = Self {
             head: CachePadded::new(Atomic::null()),
             tail: CachePadded::new(Atomic::null()),
+            count: ShardedCounter::new(),
         };
 
         // store dummy node into both head and tail
@@ -80,6 +99,7 @@ impl<V> ConcurrentQueue<V> for MSQueue<V> {
                         Ordering::Relaxed,
                         &guard,
                     );
+                    self.count.increment();
                     break;
                 }
             } else {
@@ -134,6 +154,8 @@ impl<V> ConcurrentQueue<V> for MSQueue<V> {
                 .is_ok()
             {
                 // free head and get head_next's value
+                self.count.decrement();
+
                 unsafe {
                     guard.defer_destroy(head);
                     return Some(ptr::read(&head_next.deref().value).assume_init());
@@ -0,0 +1,643 @@
+/*
+ Refer to
+ The Art of Multiprocessor Programming, 14.4 A Lock-Free Concurrent Skiplist and
+ https://www.cl.cam.ac.uk/techreports/UCAM-CL-TR-579.pdf
+*/
+
+use std::{
+    borrow::Borrow,
+    ops::{Bound, RangeBounds},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crossbeam_epoch::{pin, unprotected, Atomic, Guard, Owned, Shared};
+use rand::{thread_rng, Rng};
+
+use crate::map::{ConcurrentLookup, ConcurrentMap};
+use crate::some_or;
+
+const MAX_HEIGHT: usize = 32;
+
+/// get random height in [1, MAX_HEIGHT] whose probability of height h is 1/2^h
+#[inline]
+fn random_height() -> usize {
+    let random: u32 = thread_rng().gen();
+    (random.trailing_ones() as usize + 1).min(MAX_HEIGHT)
+}
+
+/// Lock-free skip list
+///
+/// The tag 1 on the next pointer of the level means that the node is logically removed on the level.
+/// The node is logically removed from the list when the level 0 is marked.
+/// The value is replaced by marking the level 0 of the node with the tagged pointer to the new node of the same key,
+/// so the new node is linked on the level 0 and the old one is removed at once.
+/// The value of the removed node may still be read by other threads under their guards,
+/// so it is dropped with the node, and the remove returns its clone.
+pub struct LockFreeSkipList<K, V> {
+    head: [Atomic<Node<K, V>>; MAX_HEIGHT],
+}
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    next: Box<[Atomic<Node<K, V>>]>,
+    /// the number of the levels which the node is linked on, and 1 for the inserting thread
+    ///
+    /// If it becomes 0, no one can reach the node newly, so it can be destroyed.
+    refs: AtomicUsize,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(key: K, value: V, height: usize) -> Self {
+        Self {
+            key,
+            value,
+            next: (0..height).map(|_| Atomic::null()).collect(),
+            refs: AtomicUsize::new(1),
+        }
+    }
+
+    #[inline]
+    fn height(&self) -> usize {
+        self.next.len()
+    }
+
+    #[inline]
+    fn is_removed(&self, guard: &Guard) -> bool {
+        self.next[0].load(Ordering::Acquire, guard).tag() == 1
+    }
+
+    /// decrease the reference count, then destroy it if no one can reach it
+    fn release(node: Shared<Node<K, V>>, guard: &Guard) {
+        unsafe {
+            if node.deref().refs.fetch_sub(1, Ordering::AcqRel) == 1 {
+                guard.defer_destroy(node);
+            }
+        }
+    }
+}
+
+/// the result of searching the key
+///
+/// preds: the links of the last node whose key is less than the key on each level
+/// succs: the first node whose key is equal or greater than the key on each level
+struct Cursor<'g, K, V> {
+    preds: [&'g Atomic<Node<K, V>>; MAX_HEIGHT],
+    succs: [Shared<'g, Node<K, V>>; MAX_HEIGHT],
+}
+
+impl<'g, K: Ord, V> Cursor<'g, K, V> {
+    /// get the node of the key on level 0 if it exists
+    fn found<Q>(&self, key: &Q) -> Option<Shared<'g, Node<K, V>>>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let node = self.succs[0];
+
+        match unsafe { node.as_ref() } {
+            Some(node_ref) if node_ref.key.borrow() == key => Some(node),
+            _ => None,
+        }
+    }
+}
+
+impl<K, V> Default for LockFreeSkipList<K, V> {
+    fn default() -> Self {
+        Self {
+            head: Default::default(),
+        }
+    }
+}
+
+impl<K: Ord, V> LockFreeSkipList<K, V> {
+    /// find the cursor by the key
+    ///
+    /// While traversing, the logically removed nodes are physically removed on each level.
+    fn search<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Cursor<'g, K, V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        'retry: loop {
+            let mut cursor = Cursor {
+                preds: [&self.head[0]; MAX_HEIGHT],
+                succs: [Shared::null(); MAX_HEIGHT],
+            };
+
+            let mut pred: &'g [Atomic<Node<K, V>>] = &self.head;
+
+            for level in (0..MAX_HEIGHT).rev() {
+                let mut curr = pred[level].load(Ordering::Acquire, guard);
+
+                if curr.tag() == 1 {
+                    // the pred is removed on the level. Retry
+                    continue 'retry;
+                }
+
+                while let Some(curr_ref) = unsafe { curr.as_ref() } {
+                    let succ = curr_ref.next[level].load(Ordering::Acquire, guard);
+
+                    if succ.tag() == 1 {
+                        // curr is logically removed, so unlink it on the level
+                        let succ = succ.with_tag(0);
+
+                        if pred[level]
+                            .compare_exchange(
+                                curr,
+                                succ,
+                                Ordering::AcqRel,
+                                Ordering::Relaxed,
+                                guard,
+                            )
+                            .is_err()
+                        {
+                            continue 'retry;
+                        }
+
+                        Node::release(curr, guard);
+                        curr = succ;
+                        continue;
+                    }
+
+                    if curr_ref.key.borrow() < key {
+                        pred = &curr_ref.next;
+                        curr = succ;
+                    } else {
+                        break;
+                    }
+                }
+
+                cursor.preds[level] = &pred[level];
+                cursor.succs[level] = curr;
+            }
+
+            return cursor;
+        }
+    }
+
+    /// link the node on the upper levels after it is linked on the level 0 by the cursor of its key,
+    /// then release the reference of the linking thread
+    fn link_upper<'g>(
+        &'g self,
+        node: Shared<'g, Node<K, V>>,
+        mut cursor: Cursor<'g, K, V>,
+        guard: &'g Guard,
+    ) {
+        let node_ref = unsafe { node.deref() };
+        let key = &node_ref.key;
+        let height = node_ref.height();
+
+        'link: for level in 1..height {
+            loop {
+                let next = node_ref.next[level].load(Ordering::Acquire, guard);
+
+                // If the node is being removed, stop linking.
+                if next.tag() == 1
+                    || node_ref.next[level]
+                        .compare_exchange(
+                            next,
+                            cursor.succs[level],
+                            Ordering::AcqRel,
+                            Ordering::Acquire,
+                            guard,
+                        )
+                        .is_err()
+                {
+                    break 'link;
+                }
+
+                node_ref.refs.fetch_add(1, Ordering::Relaxed);
+
+                if cursor.preds[level]
+                    .compare_exchange(
+                        cursor.succs[level],
+                        node,
+                        Ordering::Release,
+                        Ordering::Relaxed,
+                        guard,
+                    )
+                    .is_ok()
+                {
+                    break;
+                }
+
+                node_ref.refs.fetch_sub(1, Ordering::Relaxed);
+
+                cursor = self.search(key, guard);
+
+                if cursor.found(key) != Some(node) {
+                    // the node is already removed
+                    break 'link;
+                }
+            }
+        }
+
+        if node_ref.is_removed(guard) {
+            // the node may be linked after the remover's unlinking. Unlink it again.
+            let _ = self.search(key, guard);
+        }
+
+        Node::release(node, guard);
+    }
+
+    /// find the first node whose key is in the bound without any physical removal
+    ///
+    /// The returned node may be logically removed.
+    fn lower_bound<'g, Q>(&'g self, bound: Bound<&Q>, guard: &'g Guard) -> Shared<'g, Node<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut pred: &'g [Atomic<Node<K, V>>] = &self.head;
+        let mut curr = Shared::null();
+
+        for level in (0..MAX_HEIGHT).rev() {
+            curr = pred[level].load(Ordering::Acquire, guard).with_tag(0);
+
+            while let Some(curr_ref) = unsafe { curr.as_ref() } {
+                let succ = curr_ref.next[level].load(Ordering::Acquire, guard);
+
+                if succ.tag() == 1 {
+                    // curr is logically removed on the level, so skip it
+                    curr = succ.with_tag(0);
+                    continue;
+                }
+
+                let is_less = match bound {
+                    Bound::Included(key) => curr_ref.key.borrow() < key,
+                    Bound::Excluded(key) => curr_ref.key.borrow() <= key,
+                    Bound::Unbounded => false,
+                };
+
+                if !is_less {
+                    break;
+                }
+
+                pred = &curr_ref.next;
+                curr = succ;
+            }
+        }
+
+        curr
+    }
+
+    /// skip logically removed nodes on level 0 from the node
+    fn skip_removed<'g>(
+        mut node: Shared<'g, Node<K, V>>,
+        guard: &'g Guard,
+    ) -> Option<&'g Node<K, V>> {
+        while let Some(node_ref) = unsafe { node.as_ref() } {
+            let next = node_ref.next[0].load(Ordering::Acquire, guard);
+
+            if next.tag() == 0 {
+                return Some(node_ref);
+            }
+
+            node = next.with_tag(0);
+        }
+
+        None
+    }
+
+    /// find the node of the key without any physical removal
+    fn find<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Option<&'g Node<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let node = unsafe { self.lower_bound(Bound::Included(key), guard).as_ref() }?;
+
+        if node.key.borrow() == key && !node.is_removed(guard) {
+            Some(node)
+        } else {
+            None
+        }
+    }
+
+    /// get the entry whose key is minimum
+    pub fn min<'g>(&'g self, guard: &'g Guard) -> Option<(&'g K, &'g V)> {
+        let node = self.head[0].load(Ordering::Acquire, guard);
+
+        Self::skip_removed(node, guard).map(|node| (&node.key, &node.value))
+    }
+
+    /// get the entry whose key is maximum
+    pub fn max<'g>(&'g self, guard: &'g Guard) -> Option<(&'g K, &'g V)> {
+        let mut pred: &'g [Atomic<Node<K, V>>] = &self.head;
+        let mut last = None;
+
+        for level in (0..MAX_HEIGHT).rev() {
+            let mut curr = pred[level].load(Ordering::Acquire, guard).with_tag(0);
+
+            while let Some(curr_ref) = unsafe { curr.as_ref() } {
+                let next = curr_ref.next[level].load(Ordering::Acquire, guard);
+
+                // only the alive node can be the last node
+                if !curr_ref.is_removed(guard) {
+                    pred = &curr_ref.next;
+                    last = Some(curr_ref);
+                }
+
+                curr = next.with_tag(0);
+            }
+        }
+
+        last.map(|node| (&node.key, &node.value))
+    }
+
+    /// get the first entry whose key is greater than the key
+    pub fn successor<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Option<(&'g K, &'g V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let node = self.lower_bound(Bound::Excluded(key), guard);
+
+        Self::skip_removed(node, guard).map(|node| (&node.key, &node.value))
+    }
+
+    /// iterate all entries in ascending order of the keys
+    pub fn iter<'g>(&'g self, guard: &'g Guard) -> Range<'g, K, V, (Bound<K>, Bound<K>)> {
+        self.range((Bound::Unbounded, Bound::Unbounded), guard)
+    }
+
+    /// iterate the entries whose keys are in the range in ascending order of the keys
+    ///
+    /// The iterator is weakly consistent: it returns the entries alive when it visits them,
+    /// and it may or may not return the entries inserted or removed concurrently.
+    pub fn range<'g, R: RangeBounds<K>>(
+        &'g self,
+        range: R,
+        guard: &'g Guard,
+    ) -> Range<'g, K, V, R> {
+        let next = self.lower_bound(range.start_bound(), guard);
+
+        Range { next, range, guard }
+    }
+}
+
+/// the iterator on the range of the skip list
+pub struct Range<'g, K, V, R: RangeBounds<K>> {
+    next: Shared<'g, Node<K, V>>,
+    range: R,
+    guard: &'g Guard,
+}
+
+impl<'g, K: Ord, V, R: RangeBounds<K>> Iterator for Range<'g, K, V, R> {
+    type Item = (&'g K, &'g V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = LockFreeSkipList::skip_removed(self.next, self.guard)?;
+
+        let in_range = match self.range.end_bound() {
+            Bound::Included(key) => node.key <= *key,
+            Bound::Excluded(key) => node.key < *key,
+            Bound::Unbounded => true,
+        };
+
+        if !in_range {
+            self.next = Shared::null();
+            return None;
+        }
+
+        self.next = node.next[0].load(Ordering::Acquire, self.guard).with_tag(0);
+
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<K, V> ConcurrentMap<K, V> for LockFreeSkipList<K, V>
+where
+    K: Ord,
+    V: Clone,
+{
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
+        let guard = pin();
+
+        let node = Owned::new(Node::new(key, value, random_height())).into_shared(&guard);
+        let node_ref = unsafe { node.deref() };
+        let key = &node_ref.key;
+
+        // link on level 0, which is the linearization point
+        let cursor = loop {
+            let cursor = self.search(key, &guard);
+
+            if cursor.found(key).is_some() {
+                let Node { key, value, .. } = *unsafe { node.into_owned() }.into_box();
+                return Err((key, value));
+            }
+
+            node_ref.next[0].store(cursor.succs[0], Ordering::Relaxed);
+            node_ref.refs.fetch_add(1, Ordering::Relaxed);
+
+            if cursor.preds[0]
+                .compare_exchange(
+                    cursor.succs[0],
+                    node,
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                    &guard,
+                )
+                .is_ok()
+            {
+                break cursor;
+            }
+
+            node_ref.refs.fetch_sub(1, Ordering::Relaxed);
+        };
+
+        self.link_upper(node, cursor, &guard);
+
+        Ok(())
+    }
+}
+
+impl<K, V, Q> ConcurrentLookup<K, V, Q> for LockFreeSkipList<K, V>
+where
+    K: Ord + Borrow<Q>,
+    V: Clone,
+    Q: ?Sized + Ord,
+{
+    fn lookup<F, R>(&self, key: &Q, f: F) -> R
+    where
+        F: FnOnce(Option<&V>) -> R,
+    {
+        let guard = pin();
+
+        f(self.find(key, &guard).map(|node| &node.value))
+    }
+
+    fn get(&self, key: &Q) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.lookup(key, |value| value.cloned())
+    }
+
+    fn remove(&self, key: &Q) -> Result<V, ()> {
+        let guard = pin();
+
+        loop {
+            let cursor = self.search(key, &guard);
+
+            let node = some_or!(cursor.found(key), return Err(()));
+            let node_ref = unsafe { node.deref() };
+
+            // mark from top level to level 1, then the level 0
+            for level in (1..node_ref.height()).rev() {
+                let _ = node_ref.next[level].fetch_or(1, Ordering::AcqRel, &guard);
+            }
+
+            if node_ref.next[0].fetch_or(1, Ordering::AcqRel, &guard).tag() == 1 {
+                // another thread already removed it logically
+                continue;
+            }
+
+            // the thread that marks the node on level 0 returns the value
+            let value = node_ref.value.clone();
+
+            // unlink the node on all levels
+            let _ = self.search(key, &guard);
+
+            return Ok(value);
+        }
+    }
+
+    fn upsert(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+    {
+        // the function may be called again on the contention, so it clones the value
+        self.compute(key, |_| Some(value.clone()))
+    }
+
+    fn compute<F>(&self, key: &Q, mut f: F) -> Option<V>
+    where
+        Q: ToOwned<Owned = K>,
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        let guard = pin();
+
+        loop {
+            let cursor = self.search(key, &guard);
+
+            let found = match cursor.found(key) {
+                Some(found) => found,
+                None => {
+                    let node = Node::new(key.to_owned(), f(None)?, random_height());
+                    let node = Owned::new(node).into_shared(&guard);
+                    let node_ref = unsafe { node.deref() };
+
+                    // link on level 0 like insert
+                    node_ref.next[0].store(cursor.succs[0], Ordering::Relaxed);
+                    node_ref.refs.fetch_add(1, Ordering::Relaxed);
+
+                    if cursor.preds[0]
+                        .compare_exchange(
+                            cursor.succs[0],
+                            node,
+                            Ordering::Release,
+                            Ordering::Relaxed,
+                            &guard,
+                        )
+                        .is_err()
+                    {
+                        drop(unsafe { node.into_owned() });
+                        continue;
+                    }
+
+                    self.link_upper(node, cursor, &guard);
+
+                    return None;
+                }
+            };
+
+            let found_ref = unsafe { found.deref() };
+            let mut next = found_ref.next[0].load(Ordering::Acquire, &guard);
+
+            if next.tag() == 1 {
+                continue;
+            }
+
+            let node = f(Some(&found_ref.value)).map(|value| {
+                let node = Owned::new(Node::new(key.to_owned(), value, random_height()));
+
+                // for the link on level 0, which is made by marking the found node
+                node.refs.fetch_add(1, Ordering::Relaxed);
+                node.into_shared(&guard)
+            });
+
+            // mark from top level to level 1 like remove
+            for level in (1..found_ref.height()).rev() {
+                let _ = found_ref.next[level].fetch_or(1, Ordering::AcqRel, &guard);
+            }
+
+            // mark the level 0 with the pointer to its replacement, or to its next if it is removed
+            let marked = loop {
+                let succ = match node {
+                    Some(node) => {
+                        unsafe { node.deref() }.next[0].store(next, Ordering::Relaxed);
+                        node
+                    }
+                    None => next,
+                };
+
+                match found_ref.next[0].compare_exchange(
+                    next,
+                    succ.with_tag(1),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    &guard,
+                ) {
+                    Ok(_) => break true,
+                    Err(e) if e.current.tag() == 0 => next = e.current,
+                    Err(_) => break false,
+                }
+            };
+
+            if !marked {
+                // another thread already replaced or removed the found node, so compute again
+                if let Some(node) = node {
+                    drop(unsafe { node.into_owned() });
+                }
+
+                continue;
+            }
+
+            // the thread that marks the node on level 0 returns the value
+            let value = found_ref.value.clone();
+
+            // unlink the found node on all levels, then link the new node on the upper levels
+            let cursor = self.search(key, &guard);
+
+            if let Some(node) = node {
+                self.link_upper(node, cursor, &guard);
+            }
+
+            return Some(value);
+        }
+    }
+}
+
+impl<K, V> Drop for LockFreeSkipList<K, V> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = unprotected();
+
+            let mut node = self.head[0].load(Ordering::Relaxed, guard);
+
+            // All nodes which are not destroyed are still linked on level 0.
+            while !node.is_null() {
+                let next = node.deref().next[0].load(Ordering::Relaxed, guard);
+                drop(node.into_owned());
+
+                node = next.with_tag(0);
+            }
+        }
+    }
+}
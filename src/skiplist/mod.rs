@@ -0,0 +1,3 @@
+mod lockfree;
+
+pub use lockfree::{LockFreeSkipList, Range};
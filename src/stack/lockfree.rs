@@ -5,9 +5,11 @@ use crossbeam_utils::Backoff;
 use rand::{thread_rng, Rng};
 
 use super::ConcurrentStack;
+use crate::util::counter::ShardedCounter;
 
 pub struct TreiberStack<V> {
     head: Atomic<Node<V>>,
+    count: ShardedCounter,
 }
 
 impl<V> Default for TreiberStack<V> {
@@ -31,6 +33,13 @@ impl<V> Node<V> {
 }
 
 impl<V> TreiberStack<V> {
+    /// the number of values on the stack
+    ///
+    /// It is exact when no operation is running, but approximate while other threads push or pop.
+    pub fn len(&self) -> usize {
+        self.count.get()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.head.load(Ordering::Relaxed, &pin()).is_null()
     }
@@ -85,6 +94,7 @@ impl<V> ConcurrentStack<V> for TreiberStack<V> {
     fn new() -> Self {
         Self {
             head: Atomic::null(),
+            count: ShardedCounter::new(),
         }
     }
 
@@ -98,6 +108,8 @@ impl<V> ConcurrentStack<V> for TreiberStack<V> {
             node = e;
             backoff.spin();
         }
+
+        self.count.increment();
     }
 
     fn try_pop(&self) -> Option<V> {
@@ -107,6 +119,10 @@ impl<V> ConcurrentStack<V> for TreiberStack<V> {
 
         loop {
             if let Ok(value) = self.treiber_try_pop(&guard) {
+                if value.is_some() {
+                    self.count.decrement();
+                }
+
                 return value;
             }
 
@@ -155,6 +171,18 @@ impl<V> Default for EBStack<V> {
 }
 
 impl<V> EBStack<V> {
+    /// the number of values on the stack
+    ///
+    /// The pair of push and pop eliminated by each other does not change it.
+    /// It is exact when no operation is running, but approximate while other threads push or pop.
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
     fn elem_try_push(&self, node: Owned<Node<V>>, guard: &Guard) -> Result<(), Owned<Node<V>>> {
         let node = match self.stack.treiber_try_push(node, guard) {
             Ok(_) => return Ok(()),
@@ -269,6 +297,8 @@ impl<V> ConcurrentStack<V> for EBStack<V> {
         while let Err(e) = self.elem_try_push(node, &guard) {
             node = e;
         }
+
+        self.stack.count.increment();
     }
 
     fn try_pop(&self) -> Option<V> {
@@ -276,6 +306,10 @@ impl<V> ConcurrentStack<V> for EBStack<V> {
 
         loop {
             if let Ok(value) = self.elem_try_pop(&guard) {
+                if value.is_some() {
+                    self.stack.count.decrement();
+                }
+
                 return value;
             }
         }
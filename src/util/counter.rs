@@ -0,0 +1,61 @@
+use std::sync::atomic::{AtomicIsize, Ordering};
+
+use crossbeam_utils::CachePadded;
+use thread_local::ThreadLocal;
+
+/// Counter sharded by threads for the size of concurrent collections
+///
+/// Each thread updates only its own shard, so the update does not contend with other threads.
+/// The sum reads all shards without any synchronization, so it is exact only when no thread is updating.
+/// While updating concurrently, it is approximate, and may not be the size at any moment.
+pub struct ShardedCounter {
+    shards: ThreadLocal<CachePadded<AtomicIsize>>,
+}
+
+impl Default for ShardedCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShardedCounter {
+    pub fn new() -> Self {
+        Self {
+            shards: ThreadLocal::new(),
+        }
+    }
+
+    /// add delta on the shard of the current thread
+    #[inline]
+    pub fn add(&self, delta: isize) {
+        let shard = self.shards.get_or(|| CachePadded::new(AtomicIsize::new(0)));
+
+        // Only the owner thread writes the shard, so it does not need any atomic read-modify-write.
+        shard.store(shard.load(Ordering::Relaxed) + delta, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn increment(&self) {
+        self.add(1);
+    }
+
+    #[inline]
+    pub fn decrement(&self) {
+        self.add(-1);
+    }
+
+    /// the sum of all shards
+    ///
+    /// It may be negative temporarily if the decrement of a thread is read before the increment of another thread.
+    pub fn sum(&self) -> isize {
+        self.shards
+            .iter()
+            .map(|shard| shard.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// the sum of all shards, which is clamped to 0
+    pub fn get(&self) -> usize {
+        self.sum().max(0) as usize
+    }
+}
@@ -1,3 +1,4 @@
+pub mod counter;
 pub mod random;
 
 #[macro_export]
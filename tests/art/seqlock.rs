@@ -0,0 +1,281 @@
+use cds::{
+    art::SeqLockART,
+    map::{ConcurrentLookup, ConcurrentMap},
+};
+use crossbeam_utils::thread;
+use rand::{prelude::SliceRandom, thread_rng};
+
+use crate::util::map::{
+    stress_concurrent, stress_concurrent_as_sequential, test_compute_concurrent,
+    test_lookup_remove_concurrent,
+};
+
+#[test]
+fn test_seqlock_art() {
+    let num = 4096;
+    let tree: SeqLockART<u32, u32> = SeqLockART::new();
+
+    for i in 0..num {
+        assert_eq!(tree.insert(i, i), Ok(()));
+    }
+
+    for i in 0..num {
+        assert_eq!(tree.insert(i, i), Err((i, i)));
+    }
+
+    for i in 0..num {
+        assert_eq!(tree.get(&i), Some(i));
+    }
+
+    for i in 0..num {
+        assert_eq!(tree.remove(&i), Ok(i));
+    }
+
+    for i in 0..num {
+        assert_eq!(tree.remove(&i), Err(()));
+        assert_eq!(tree.get(&i), None);
+    }
+}
+
+#[test]
+fn test_seqlock_art_str_key() {
+    let tree: SeqLockART<String, usize> = SeqLockART::new();
+
+    // the keys share the long prefixes and make every kind of the nodes
+    let mut keys = vec![String::new()];
+
+    for i in 0..=255u8 {
+        keys.push(format!("prefix/{}", i as char));
+        keys.push(format!("prefix/{}/{}", i % 16, i));
+        keys.push(format!("prefix/long/common/path/{}", i % 4));
+    }
+
+    keys.sort();
+    keys.dedup();
+    keys.shuffle(&mut thread_rng());
+
+    for (i, key) in keys.iter().enumerate() {
+        assert_eq!(tree.insert(key.clone(), i), Ok(()));
+    }
+
+    for (i, key) in keys.iter().enumerate() {
+        assert_eq!(tree.get(key.as_str()), Some(i));
+    }
+
+    assert_eq!(tree.get("prefix"), None);
+    assert_eq!(tree.get("prefix/long"), None);
+
+    for (i, key) in keys.iter().enumerate().step_by(2) {
+        assert_eq!(tree.remove(key.as_str()), Ok(i));
+    }
+
+    for (i, key) in keys.iter().enumerate() {
+        let expected = if i % 2 == 0 { None } else { Some(i) };
+        assert_eq!(tree.get(key.as_str()), expected);
+    }
+}
+
+#[test]
+fn test_seqlock_art_scan_prefix() {
+    let tree: SeqLockART<String, usize> = SeqLockART::new();
+    let keys = [
+        "app",
+        "apple",
+        "application",
+        "apply",
+        "banana",
+        "band",
+        "bandana",
+    ];
+
+    for (i, key) in keys.iter().enumerate() {
+        assert_eq!(tree.insert(key.to_string(), i), Ok(()));
+    }
+
+    let scan = |prefix: &str| {
+        let mut keys: Vec<_> = tree
+            .scan_prefix(prefix)
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect();
+        keys.sort();
+        keys
+    };
+
+    assert_eq!(scan("app"), ["app", "apple", "application", "apply"]);
+    assert_eq!(scan("appl"), ["apple", "application", "apply"]);
+    assert_eq!(scan("applic"), ["application"]);
+    assert_eq!(scan("band"), ["band", "bandana"]);
+    assert_eq!(scan("b"), ["banana", "band", "bandana"]);
+    assert_eq!(scan("c"), Vec::<String>::new());
+    assert_eq!(scan("applez"), Vec::<String>::new());
+    assert_eq!(scan("").len(), keys.len());
+
+    assert_eq!(tree.remove("apple"), Ok(1));
+    assert_eq!(scan("appl"), ["application", "apply"]);
+}
+
+#[test]
+fn test_seqlock_art_remove_concurrent() {
+    let tree: SeqLockART<u32, u32> = SeqLockART::new();
+    let num = 4096;
+
+    for i in 0..num {
+        assert_eq!(tree.insert(i, i), Ok(()));
+    }
+
+    // every key is removed by exactly one thread
+    let removed: u32 = thread::scope(|s| {
+        let handles = (0..8)
+            .map(|_| {
+                s.spawn(|_| {
+                    (0..num)
+                        .filter(|i| tree.remove(i).is_ok())
+                        .map(|_| 1)
+                        .sum::<u32>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles.into_iter().map(|h| h.join().unwrap()).sum()
+    })
+    .unwrap();
+
+    assert_eq!(removed, num);
+    assert!(tree.scan_prefix(&()).is_empty());
+}
+
+#[test]
+fn test_seqlock_art_scan_concurrent() {
+    let tree: SeqLockART<u32, u32> = SeqLockART::new();
+    let num = 4096;
+
+    for i in (0..num).step_by(2) {
+        assert_eq!(tree.insert(i, i), Ok(()));
+    }
+
+    // the even keys exist during the whole scans while the writers insert and remove the odd keys
+    thread::scope(|s| {
+        for t in 0..4 {
+            let tree = &tree;
+
+            s.spawn(move |_| {
+                for _ in 0..20 {
+                    for i in (2 * t + 1..num).step_by(8) {
+                        assert_eq!(tree.insert(i, i), Ok(()));
+                    }
+
+                    for i in (2 * t + 1..num).step_by(8) {
+                        assert_eq!(tree.remove(&i), Ok(i));
+                    }
+                }
+            });
+        }
+
+        for _ in 0..2 {
+            s.spawn(|_| {
+                for _ in 0..20 {
+                    let keys = tree
+                        .scan_prefix(&())
+                        .into_iter()
+                        .map(|(k, _)| k)
+                        .collect::<Vec<_>>();
+
+                    assert!(keys.windows(2).all(|w| w[0] < w[1]));
+                    assert!(keys
+                        .iter()
+                        .filter(|k| *k % 2 == 0)
+                        .copied()
+                        .eq((0..num).step_by(2)));
+                }
+            });
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn test_seqlock_art_bytes_key() {
+    let tree: SeqLockART<Vec<u8>, usize> = SeqLockART::new();
+    let keys: [&[u8]; 7] = [b"", b"\0", b"\0\0", b"\0\xff", b"a", b"a\0b", b"ab"];
+
+    for (i, key) in keys.iter().enumerate() {
+        assert_eq!(tree.insert(key.to_vec(), i), Ok(()));
+    }
+
+    for (i, key) in keys.iter().enumerate() {
+        assert_eq!(tree.get(*key), Some(i));
+    }
+
+    // the keys are scanned in the order of the bytes, where the shorter prefix is first
+    let scan = |prefix: &[u8]| {
+        tree.scan_prefix(prefix)
+            .into_iter()
+            .map(|(_, v)| v)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(scan(b""), [0, 1, 2, 3, 4, 5, 6]);
+    assert_eq!(scan(b"\0"), [1, 2, 3]);
+    assert_eq!(scan(b"a"), [4, 5, 6]);
+    assert_eq!(scan(b"a\0"), [5]);
+    assert_eq!(scan(b"b"), Vec::<usize>::new());
+
+    assert_eq!(tree.remove(b"\0".as_slice()), Ok(1));
+    assert_eq!(scan(b"\0"), [2, 3]);
+}
+
+#[test]
+fn test_seqlock_art_integer_order() {
+    let tree: SeqLockART<i32, i32> = SeqLockART::new();
+    let mut keys = vec![i32::MIN, -65536, -256, -1, 0, 1, 255, 256, 65536, i32::MAX];
+    keys.shuffle(&mut thread_rng());
+
+    for key in &keys {
+        assert_eq!(tree.insert(*key, *key), Ok(()));
+    }
+
+    keys.sort();
+    let scan = tree.scan_prefix(&()).into_iter().map(|(k, _)| k);
+    assert!(scan.eq(keys));
+}
+
+#[test]
+fn test_seqlock_art_empty_key() {
+    let tree: SeqLockART<(), usize> = SeqLockART::new();
+
+    assert_eq!(tree.get(&()), None);
+    assert_eq!(tree.insert((), 0), Ok(()));
+    assert_eq!(tree.insert((), 1), Err(((), 1)));
+    assert_eq!(tree.get(&()), Some(0));
+    assert_eq!(tree.scan_prefix(&()), [((), 0)]);
+    assert_eq!(tree.remove(&()), Ok(0));
+    assert_eq!(tree.remove(&()), Err(()));
+    assert!(tree.scan_prefix(&()).is_empty());
+}
+
+#[test]
+fn test_seqlock_art_lookup_remove_concurrent() {
+    test_lookup_remove_concurrent::<SeqLockART<_, _>>();
+}
+
+#[test]
+fn test_seqlock_art_compute_concurrent() {
+    test_compute_concurrent::<SeqLockART<_, _>>();
+}
+
+#[test]
+fn stress_seqlock_art_sequential() {
+    stress_concurrent_as_sequential::<String, SeqLockART<_, _>>(100_000);
+}
+
+#[test]
+fn stress_seqlock_art_concurrent() {
+    stress_concurrent::<u32, SeqLockART<_, _>>(200_000, 16, false);
+    stress_concurrent::<String, SeqLockART<_, _>>(200_000, 16, false);
+}
+
+#[test]
+fn assert_seqlock_art_concurrent() {
+    stress_concurrent::<u8, SeqLockART<_, _>>(100_000, 32, true);
+}
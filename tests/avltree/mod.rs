@@ -1,8 +1,16 @@
+mod persistent;
 mod rwlock;
 mod seqlock;
 
 use crate::util::map::stress_sequential;
-use cds::{avltree::AVLTree, map::SequentialMap};
+use cds::{
+    avltree::AVLTree,
+    map::{OrderedMap, SequentialLookup, SequentialMap},
+};
+use rand::{prelude::SliceRandom, thread_rng};
+use std::collections::{BTreeMap, VecDeque};
+use std::num::NonZeroU64;
+use std::sync::Arc;
 
 #[test]
 fn test_insert_lookup_avl_tree() {
@@ -10,11 +18,11 @@ fn test_insert_lookup_avl_tree() {
 
     for i in 0..65535 {
         // 65535 = 2^16 - 1
-        assert_eq!(avl.insert(&i, i), Ok(()));
+        assert_eq!(avl.insert(i, i), Ok(()));
     }
 
     assert_eq!(avl.get_height(), 16);
-    assert_eq!(avl.insert(&65536, 65536), Ok(()));
+    assert_eq!(avl.insert(65536, 65536), Ok(()));
     assert_eq!(avl.get_height(), 17);
 
     for i in 0..65535 {
@@ -33,14 +41,14 @@ fn test_remove_avl_tree() {
      *    1           5
      */
 
-    assert_eq!(avl.insert(&3, 3), Ok(()));
-    assert_eq!(avl.insert(&2, 2), Ok(()));
-    assert_eq!(avl.insert(&4, 4), Ok(()));
-    assert_eq!(avl.insert(&1, 1), Ok(()));
-    assert_eq!(avl.insert(&5, 5), Ok(()));
+    assert_eq!(avl.insert(3, 3), Ok(()));
+    assert_eq!(avl.insert(2, 2), Ok(()));
+    assert_eq!(avl.insert(4, 4), Ok(()));
+    assert_eq!(avl.insert(1, 1), Ok(()));
+    assert_eq!(avl.insert(5, 5), Ok(()));
 
     assert_eq!(avl.remove(&1), Ok(1)); // remove when the node is leaf
-    assert_eq!(avl.insert(&1, 1), Ok(()));
+    assert_eq!(avl.insert(1, 1), Ok(()));
 
     assert_eq!(avl.remove(&2), Ok(2)); // remove when the node has only left node
     assert_eq!(avl.remove(&4), Ok(4)); // remove when the node has only right node
@@ -51,13 +59,13 @@ fn test_remove_avl_tree() {
 
     // side case of remove when the node has two nodes
     let mut avl: AVLTree<i32, i32> = AVLTree::new();
-    assert_eq!(avl.insert(&4, 4), Ok(()));
-    assert_eq!(avl.insert(&0, 0), Ok(()));
-    assert_eq!(avl.insert(&-1, -1), Ok(()));
-    assert_eq!(avl.insert(&5, 5), Ok(()));
-    assert_eq!(avl.insert(&6, 6), Ok(()));
-    assert_eq!(avl.insert(&2, 2), Ok(()));
-    assert_eq!(avl.insert(&1, 1), Ok(()));
+    assert_eq!(avl.insert(4, 4), Ok(()));
+    assert_eq!(avl.insert(0, 0), Ok(()));
+    assert_eq!(avl.insert(-1, -1), Ok(()));
+    assert_eq!(avl.insert(5, 5), Ok(()));
+    assert_eq!(avl.insert(6, 6), Ok(()));
+    assert_eq!(avl.insert(2, 2), Ok(()));
+    assert_eq!(avl.insert(1, 1), Ok(()));
 
     assert_eq!(avl.remove(&4), Ok(4));
 
@@ -69,7 +77,159 @@ fn test_remove_avl_tree() {
     assert_eq!(avl.lookup(&6), Some(&6));
 }
 
+#[test]
+fn test_non_default_key_avl_tree() {
+    let value = Arc::new(0);
+    let mut avl: AVLTree<NonZeroU64, Arc<i32>> = AVLTree::new();
+
+    for i in 1..=64 {
+        let key = NonZeroU64::new(i).unwrap();
+        assert_eq!(avl.insert(key, value.clone()), Ok(()));
+    }
+
+    for i in (1..=64).step_by(2) {
+        assert!(avl.remove(&NonZeroU64::new(i).unwrap()).is_ok());
+    }
+
+    assert_eq!(Arc::strong_count(&value), 33);
+    assert!(avl.keys().map(|key| key.get()).eq((2..=64).step_by(2)));
+
+    drop(avl);
+    assert_eq!(Arc::strong_count(&value), 1);
+}
+
 #[test]
 fn stress_avl_tree() {
     stress_sequential::<String, AVLTree<_, _>>(100_000);
 }
+
+#[test]
+// the reversed range is intended to check that it is empty
+#[allow(clippy::reversed_empty_ranges)]
+fn test_iter_range_avl_tree() {
+    let num = 4096;
+    let mut keys: Vec<i32> = (0..num).map(|i| i * 2).collect();
+    keys.shuffle(&mut thread_rng());
+
+    let mut tree: AVLTree<i32, i32> = AVLTree::new();
+    let mut reference = BTreeMap::new();
+
+    for key in keys {
+        assert_eq!(tree.insert(key, key), Ok(()));
+        reference.insert(key, key);
+    }
+
+    assert!(tree.iter().eq(reference.iter()));
+    assert!(tree.keys().eq(reference.keys()));
+    assert!(tree.values().eq(reference.values()));
+
+    for (start, end) in [(-1, 10), (0, 9), (1, 8), (3000, 5000), (8190, 9000)] {
+        assert!(tree.range(start..end).eq(reference.range(start..end)));
+        assert!(tree.range(start..=end).eq(reference.range(start..=end)));
+        assert!(tree.range(start..).eq(reference.range(start..)));
+        assert!(tree.range(..end).eq(reference.range(..end)));
+    }
+
+    assert_eq!(tree.range(100..50).next(), None);
+
+    for (_, value) in tree.iter_mut() {
+        *value += 1;
+    }
+
+    for (_, value) in &mut tree {
+        *value -= 2;
+    }
+
+    assert!(tree
+        .into_iter()
+        .eq(reference.into_iter().map(|(key, value)| (key, value - 1))));
+}
+
+#[test]
+// the reversed range is intended to check that it is empty
+#[allow(clippy::reversed_empty_ranges)]
+fn test_rank_select_avl_tree() {
+    let num = 4096;
+    let mut keys: Vec<i32> = (0..num).map(|i| i * 2).collect();
+    keys.shuffle(&mut thread_rng());
+
+    let mut tree: AVLTree<i32, i32> = AVLTree::new();
+    let mut reference = BTreeMap::new();
+
+    for key in keys.iter() {
+        assert_eq!(tree.insert(*key, *key), Ok(()));
+        reference.insert(*key, *key);
+    }
+
+    // remove the half of the keys to check the sizes are renewed on removing
+    for key in keys.iter().take(num as usize / 2) {
+        assert_eq!(tree.remove(key), Ok(*key));
+        reference.remove(key);
+    }
+
+    assert_eq!(tree.len(), reference.len());
+
+    for (index, (key, value)) in reference.iter().enumerate() {
+        assert_eq!(tree.rank(key), index);
+        assert_eq!(tree.rank(&(key + 1)), index + 1);
+        assert_eq!(tree.select(index), Some((key, value)));
+    }
+
+    assert_eq!(tree.rank(&-1), 0);
+    assert_eq!(tree.select(reference.len()), None);
+
+    for (start, end) in [(-1, 10), (0, 9), (1, 8), (3000, 5000), (8190, 9000)] {
+        assert_eq!(
+            tree.count_range(start..end),
+            reference.range(start..end).count()
+        );
+        assert_eq!(
+            tree.count_range(start..=end),
+            reference.range(start..=end).count()
+        );
+        assert_eq!(tree.count_range(start..), reference.range(start..).count());
+        assert_eq!(tree.count_range(..end), reference.range(..end).count());
+    }
+
+    assert_eq!(tree.count_range(100..50), 0);
+    assert_eq!(tree.count_range::<i32, _>(..), reference.len());
+}
+
+#[test]
+fn test_ordered_avl_tree() {
+    let num = 4096;
+    let mut keys: Vec<i32> = (0..num).map(|i| i * 2).collect();
+    keys.shuffle(&mut thread_rng());
+
+    let mut tree: AVLTree<i32, i32> = AVLTree::new();
+    let mut reference = BTreeMap::new();
+
+    assert_eq!(tree.first(), None);
+    assert_eq!(tree.last(), None);
+    assert_eq!(tree.pop_first(), None);
+    assert_eq!(tree.ceiling(&0), None);
+
+    for key in keys {
+        assert_eq!(tree.insert(key, key), Ok(()));
+        reference.insert(key, key);
+    }
+
+    for key in -1..num * 2 + 1 {
+        assert_eq!(tree.floor(&key), reference.range(..=key).next_back());
+        assert_eq!(tree.ceiling(&key), reference.range(key..).next());
+    }
+
+    // pop from both ends alternately until the tree is empty
+    let mut reference: VecDeque<_> = reference.into_iter().collect();
+
+    while !reference.is_empty() {
+        assert_eq!(tree.first(), reference.front().map(|(k, v)| (k, v)));
+        assert_eq!(tree.last(), reference.back().map(|(k, v)| (k, v)));
+
+        assert_eq!(tree.pop_first(), reference.pop_front());
+        assert_eq!(tree.pop_last(), reference.pop_back());
+        assert_eq!(tree.len(), reference.len());
+    }
+
+    assert_eq!(tree.pop_last(), None);
+}
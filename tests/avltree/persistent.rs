@@ -0,0 +1,195 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use cds::{
+    avltree::{PersistentAVLTree, RcuAVLTree},
+    map::{ConcurrentLookup, ConcurrentMap, SequentialLookup, SequentialMap},
+};
+use crossbeam_utils::thread;
+use rand::{prelude::SliceRandom, thread_rng};
+
+use crate::util::map::{stress_concurrent, stress_concurrent_as_sequential, stress_sequential};
+
+#[test]
+fn test_persistent_avl_tree() {
+    let mut tree: PersistentAVLTree<i32, i32> = PersistentAVLTree::new();
+
+    for i in 0..65535 {
+        // 65535 = 2^16 - 1
+        assert_eq!(tree.insert(i, i), Ok(()));
+    }
+
+    assert_eq!(tree.get_height(), 16);
+    assert_eq!(tree.len(), 65535);
+
+    for i in 0..65535 {
+        assert_eq!(tree.lookup(&i), Some(&i));
+    }
+
+    for i in (0..65535).step_by(2) {
+        assert_eq!(tree.remove(&i), Ok(i));
+    }
+
+    assert_eq!(tree.remove(&0), Err(()));
+    assert!(tree.iter().map(|(k, _)| *k).eq((1..65535).step_by(2)));
+}
+
+#[test]
+fn test_persistent_avl_tree_versions() {
+    let mut keys: Vec<i32> = (0..1024).collect();
+    keys.shuffle(&mut thread_rng());
+
+    let mut versions = vec![PersistentAVLTree::new()];
+    let mut reference = BTreeMap::new();
+    let mut references = vec![reference.clone()];
+
+    for key in keys.iter() {
+        let tree = versions.last().unwrap().inserted(*key, *key).unwrap();
+        assert_eq!(tree.inserted(*key, 0).err(), Some((*key, 0)));
+
+        reference.insert(*key, *key);
+        versions.push(tree);
+        references.push(reference.clone());
+    }
+
+    for key in keys.iter().take(512) {
+        let tree = versions.last().unwrap().removed(key).unwrap();
+        assert!(tree.removed(key).is_none());
+
+        reference.remove(key);
+        versions.push(tree);
+        references.push(reference.clone());
+    }
+
+    // every version is kept as it was
+    for (tree, reference) in versions.iter().zip(references.iter()) {
+        assert_eq!(tree.len(), reference.len());
+        assert!(tree.iter().eq(reference.iter()));
+    }
+
+    let tree = versions.last().unwrap();
+    assert!(tree.ptr_eq(&tree.clone()));
+    assert!(!tree.ptr_eq(&versions[0]));
+}
+
+#[test]
+fn test_persistent_avl_tree_shared() {
+    let value = Arc::new(0);
+    let mut tree = PersistentAVLTree::new();
+
+    for i in 0..64 {
+        assert_eq!(tree.insert(i, value.clone()), Ok(()));
+    }
+
+    // the snapshot shares all nodes, and the update copies only the nodes on the path
+    let snapshot = tree.clone();
+    assert_eq!(Arc::strong_count(&value), 65);
+
+    assert!(tree.remove(&0).is_ok());
+    assert!(Arc::strong_count(&value) <= 65 + 2 * snapshot.get_height());
+    assert_eq!(snapshot.len(), 64);
+    assert_eq!(tree.len(), 63);
+
+    drop(snapshot);
+    assert_eq!(Arc::strong_count(&value), 64);
+
+    drop(tree);
+    assert_eq!(Arc::strong_count(&value), 1);
+}
+
+#[test]
+fn stress_persistent_avl_tree() {
+    stress_sequential::<String, PersistentAVLTree<_, _>>(100_000);
+}
+
+#[test]
+fn test_rcu_avl_tree() {
+    let num = 64;
+    let tree: RcuAVLTree<i32, i32> = RcuAVLTree::new();
+
+    for i in 0..num {
+        assert_eq!(tree.insert(i, i), Ok(()));
+    }
+
+    for i in 0..num {
+        assert_eq!(tree.insert(i, i), Err((i, i)));
+    }
+
+    let snapshot = tree.snapshot();
+
+    for i in 0..num {
+        assert_eq!(tree.get(&i), Some(i));
+    }
+
+    for i in 0..num {
+        assert_eq!(tree.remove(&i), Ok(i));
+    }
+
+    for i in 0..num {
+        assert_eq!(tree.remove(&i), Err(()));
+    }
+
+    assert!(tree.is_empty());
+    assert_eq!(snapshot.len(), num as usize);
+    assert!(snapshot.iter().map(|(k, _)| *k).eq(0..num));
+}
+
+#[test]
+fn test_rcu_avl_tree_compute() {
+    let tree: RcuAVLTree<String, i32> = RcuAVLTree::new();
+
+    assert_eq!(tree.upsert("a", 1), None);
+    assert_eq!(tree.upsert("a", 2), Some(1));
+    assert_eq!(tree.compute("a", |v| v.map(|v| v + 1)), Some(2));
+    assert_eq!(tree.compute("a", |_| None), Some(3));
+    assert_eq!(tree.get("a"), None);
+
+    assert_eq!(tree.get_or_insert_with("b", || 10), 10);
+    assert_eq!(tree.get_or_insert_with("b", || 20), 10);
+    assert_eq!(tree.len(), 1);
+}
+
+#[test]
+fn test_rcu_avl_tree_snapshot_concurrent() {
+    let num = 256;
+    let tree: RcuAVLTree<i32, i32> = RcuAVLTree::new();
+
+    // every snapshot has the keys inserted in order without a gap
+    thread::scope(|s| {
+        s.spawn(|_| {
+            for i in 0..num {
+                assert_eq!(tree.insert(i, i), Ok(()));
+            }
+        });
+
+        for _ in 0..4 {
+            s.spawn(|_| loop {
+                let snapshot = tree.snapshot();
+                assert!(snapshot
+                    .iter()
+                    .map(|(k, _)| *k)
+                    .eq(0..snapshot.len() as i32));
+
+                if snapshot.len() == num as usize {
+                    break;
+                }
+            });
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn stress_rcu_avl_tree_sequential() {
+    stress_concurrent_as_sequential::<u8, RcuAVLTree<_, _>>(100_000);
+}
+
+#[test]
+fn stress_rcu_avl_tree_concurrent() {
+    stress_concurrent::<u32, RcuAVLTree<_, _>>(200_000, 16, false);
+}
+
+#[test]
+fn assert_rcu_avl_tree_concurrent() {
+    stress_concurrent::<u8, RcuAVLTree<_, _>>(100_000, 32, true);
+}
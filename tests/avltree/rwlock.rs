@@ -1,4 +1,12 @@
-use cds::{avltree::RwLockAVLTree, map::ConcurrentMap};
+use std::num::NonZeroU64;
+use std::sync::Arc;
+
+use cds::{
+    avltree::RwLockAVLTree,
+    map::{ConcurrentLookup, ConcurrentMap, ConcurrentOrderedMap},
+};
+use crossbeam_epoch::pin;
+use crossbeam_utils::thread;
 
 use crate::util::map::stress_concurrent_as_sequential;
 
@@ -8,11 +16,11 @@ fn test_rwlock_avl_tree() {
     let avl: RwLockAVLTree<i32, i32> = RwLockAVLTree::new();
 
     for i in 0..num {
-        assert_eq!(avl.insert(&i, i), Ok(()));
+        assert_eq!(avl.insert(i, i), Ok(()));
     }
 
     for i in 0..num {
-        assert_eq!(avl.insert(&i, i), Err(i));
+        assert_eq!(avl.insert(i, i), Err((i, i)));
     }
 
     assert_eq!(avl.get_height(), f32::log2(num as f32) as usize + 1);
@@ -30,6 +38,242 @@ fn test_rwlock_avl_tree() {
     }
 }
 
+#[test]
+fn test_rwlock_avl_tree_iter_range() {
+    let num = 1024;
+    let avl: RwLockAVLTree<i32, i32> = RwLockAVLTree::new();
+
+    for i in 0..num {
+        assert_eq!(avl.insert(i, i), Ok(()));
+    }
+
+    // the removed nodes may remain as logically removed ones
+    for i in (0..num).step_by(3) {
+        assert_eq!(avl.remove(&i), Ok(i));
+    }
+
+    let guard = pin();
+    let expected: Vec<_> = (0..num).filter(|i| i % 3 != 0).map(|i| (i, i)).collect();
+
+    assert!(avl.iter(&guard).eq(expected.iter().cloned()));
+    assert!(avl.range(100..200, &guard).eq(expected
+        .iter()
+        .cloned()
+        .filter(|(k, _)| (100..200).contains(k))));
+    assert!(avl
+        .range(..=30, &guard)
+        .eq(expected.iter().cloned().filter(|(k, _)| *k <= 30)));
+    assert_eq!(avl.range(num.., &guard).next(), None);
+}
+
+#[test]
+fn test_rwlock_avl_tree_compute() {
+    let avl: RwLockAVLTree<i32, i32> = RwLockAVLTree::new();
+
+    assert_eq!(avl.upsert(&1, 1), None);
+    assert_eq!(avl.upsert(&1, 2), Some(1));
+    assert_eq!(avl.get(&1), Some(2));
+
+    assert_eq!(avl.compute(&1, |v| v.map(|v| v + 1)), Some(2));
+    assert_eq!(avl.get(&1), Some(3));
+    assert_eq!(avl.compute(&1, |_| None), Some(3));
+    assert_eq!(avl.get(&1), None);
+    assert_eq!(avl.compute(&2, |v| Some(v.map_or(10, |v| v + 1))), None);
+    assert_eq!(avl.get(&2), Some(10));
+
+    assert_eq!(avl.get_or_insert_with(&2, || 20), 10);
+    assert_eq!(avl.get_or_insert_with(&3, || 30), 30);
+    assert_eq!(avl.get(&3), Some(30));
+
+    assert_eq!(avl.compare_and_swap_value(&3, &31, 32), Err(32));
+    assert_eq!(avl.compare_and_swap_value(&3, &30, 32), Ok(30));
+    assert_eq!(avl.compare_and_swap_value(&4, &0, 1), Err(1));
+    assert_eq!(avl.get(&3), Some(32));
+
+    assert_eq!(avl.insert(0, 0), Ok(()));
+    assert_eq!(avl.insert(1, 1), Ok(()));
+    assert_eq!(avl.insert(2, 2), Err((2, 2)));
+}
+
+#[test]
+fn test_rwlock_avl_tree_len() {
+    let avl: RwLockAVLTree<i32, i32> = RwLockAVLTree::new();
+
+    assert!(avl.is_empty());
+
+    for i in 0..64 {
+        assert_eq!(avl.insert(i, i), Ok(()));
+    }
+
+    assert_eq!(avl.insert(0, 0), Err((0, 0)));
+    assert_eq!(avl.len(), 64);
+
+    for i in (0..64).step_by(2) {
+        assert_eq!(avl.remove(&i), Ok(i));
+    }
+
+    assert_eq!(avl.remove(&0), Err(()));
+    assert_eq!(avl.len(), 32);
+
+    // the logically removed node is reused by upsert, and compute can remove the value
+    assert_eq!(avl.upsert(&0, 0), None);
+    assert_eq!(avl.upsert(&0, 1), Some(0));
+    assert_eq!(avl.upsert(&64, 64), None);
+    assert_eq!(avl.compute(&1, |_| None), Some(1));
+    assert_eq!(avl.compute(&1, |_| None), None);
+    assert_eq!(avl.len(), 33);
+
+    for i in 0..=64 {
+        let _ = avl.remove(&i);
+    }
+
+    assert_eq!(avl.len(), 0);
+    assert!(avl.is_empty());
+}
+
+#[test]
+fn test_rwlock_avl_tree_non_default_key() {
+    let value = Arc::new(0);
+    let avl: RwLockAVLTree<NonZeroU64, Arc<i32>> = RwLockAVLTree::new();
+
+    for i in 1..=64 {
+        let key = NonZeroU64::new(i).unwrap();
+        assert_eq!(avl.insert(key, value.clone()), Ok(()));
+    }
+
+    for i in (1..=64).step_by(2) {
+        assert!(avl.remove(&NonZeroU64::new(i).unwrap()).is_ok());
+    }
+
+    assert_eq!(Arc::strong_count(&value), 33);
+    assert_eq!(avl.get(&NonZeroU64::new(2).unwrap()), Some(value.clone()));
+    assert_eq!(avl.get(&NonZeroU64::new(1).unwrap()), None);
+
+    drop(avl);
+    assert_eq!(Arc::strong_count(&value), 1);
+}
+
+#[test]
+fn test_rwlock_avl_tree_ordered() {
+    let num = 1024;
+    let avl: RwLockAVLTree<i32, i32> = RwLockAVLTree::new();
+
+    assert_eq!(avl.first(), None);
+    assert_eq!(avl.pop_last(), None);
+    assert_eq!(avl.ceiling(&0), None);
+
+    for i in 0..num {
+        assert_eq!(avl.insert(i * 2, i * 2), Ok(()));
+    }
+
+    // the logically removed nodes are skipped
+    for i in (0..num).step_by(3) {
+        assert_eq!(avl.remove(&(i * 2)), Ok(i * 2));
+    }
+
+    let exists = |key: i32| key % 2 == 0 && (0..num * 2).contains(&key) && key % 6 != 0;
+
+    for key in -1..num * 2 + 1 {
+        let floor = (-1..=key).rev().find(|k| exists(*k));
+        let ceiling = (key..num * 2).find(|k| exists(*k));
+
+        assert_eq!(avl.floor(&key), floor.map(|k| (k, k)));
+        assert_eq!(avl.ceiling(&key), ceiling.map(|k| (k, k)));
+    }
+
+    assert_eq!(avl.first(), Some((2, 2)));
+    assert_eq!(avl.last(), Some((2044, 2044)));
+    assert_eq!(avl.pop_first(), Some((2, 2)));
+    assert_eq!(avl.pop_last(), Some((2044, 2044)));
+    assert_eq!(avl.first(), Some((4, 4)));
+    assert_eq!(avl.last(), Some((2042, 2042)));
+    assert_eq!(avl.get(&2), None);
+}
+
+#[test]
+fn test_rwlock_avl_tree_ordered_concurrent() {
+    let num = 1024;
+    let avl: RwLockAVLTree<i32, i32> = RwLockAVLTree::new();
+
+    for i in 0..num {
+        assert_eq!(avl.insert(i, i), Ok(()));
+    }
+
+    thread::scope(|s| {
+        let avl = &avl;
+
+        // writers remove and reinsert the odd keys, while the even keys always exist
+        for t in 0..2 {
+            s.spawn(move |_| {
+                for _ in 0..20 {
+                    for i in (2 * t + 1..num).step_by(4) {
+                        assert_eq!(avl.remove(&i), Ok(i));
+                    }
+
+                    for i in (2 * t + 1..num).step_by(4) {
+                        assert_eq!(avl.insert(i, i), Ok(()));
+                    }
+                }
+            });
+        }
+
+        for _ in 0..2 {
+            s.spawn(move |_| {
+                for _ in 0..20 {
+                    for i in (1..num - 1).step_by(2) {
+                        let floor = avl.floor(&i).unwrap().0;
+                        let ceiling = avl.ceiling(&i).unwrap().0;
+
+                        assert!(floor == i || floor == i - 1);
+                        assert!(ceiling == i || ceiling == i + 1);
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    // the keys are popped exactly once, and in the order on each thread
+    let popped: Vec<Vec<_>> = thread::scope(|s| {
+        let avl = &avl;
+
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                s.spawn(move |_| {
+                    let mut popped = Vec::new();
+
+                    while let Some((key, _)) = if t % 2 == 0 {
+                        avl.pop_first()
+                    } else {
+                        avl.pop_last()
+                    } {
+                        popped.push(key);
+                    }
+
+                    popped
+                })
+            })
+            .collect();
+
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    })
+    .unwrap();
+
+    for (t, keys) in popped.iter().enumerate() {
+        if t % 2 == 0 {
+            assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        } else {
+            assert!(keys.windows(2).all(|w| w[0] > w[1]));
+        }
+    }
+
+    let mut keys: Vec<_> = popped.into_iter().flatten().collect();
+    keys.sort_unstable();
+
+    assert_eq!(keys, (0..num).collect::<Vec<_>>());
+    assert!(avl.is_empty());
+}
+
 #[test]
 fn stress_rwlock_avl_tree_sequential() {
     stress_concurrent_as_sequential::<u8, RwLockAVLTree<_, _>>(100_000);
@@ -1,6 +1,17 @@
-use cds::{avltree::SeqLockAVLTree, map::ConcurrentMap};
+use std::num::NonZeroU64;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
 
-use crate::util::map::{stress_concurrent, stress_concurrent_as_sequential};
+use cds::{
+    avltree::SeqLockAVLTree,
+    map::{ConcurrentLookup, ConcurrentMap, ConcurrentOrderedMap},
+};
+use crossbeam_epoch::pin;
+use crossbeam_utils::thread;
+
+use crate::util::map::{
+    stress_concurrent, stress_concurrent_as_sequential, test_lookup_remove_concurrent,
+};
 
 #[test]
 fn test_seqlock_avl_tree() {
@@ -8,11 +19,11 @@ fn test_seqlock_avl_tree() {
     let avl: SeqLockAVLTree<i32, i32> = SeqLockAVLTree::new();
 
     for i in 0..num {
-        assert_eq!(avl.insert(&i, i), Ok(()));
+        assert_eq!(avl.insert(i, i), Ok(()));
     }
 
     for i in 0..num {
-        assert_eq!(avl.insert(&i, i), Err(i));
+        assert_eq!(avl.insert(i, i), Err((i, i)));
     }
 
     assert_eq!(avl.get_height(), f32::log2(num as f32) as usize + 1);
@@ -30,6 +41,494 @@ fn test_seqlock_avl_tree() {
     }
 }
 
+#[test]
+fn test_seqlock_avl_tree_lookup_optimistic() {
+    let num = 1024;
+    let avl: SeqLockAVLTree<i32, i32> = SeqLockAVLTree::new();
+
+    for i in 0..num {
+        assert_eq!(avl.insert(i, i), Ok(()));
+    }
+
+    // readers always see the value of the key or nothing while the writers remove and insert again
+    let avl = &avl;
+
+    thread::scope(|s| {
+        for t in 0..4 {
+            s.spawn(move |_| {
+                for i in (t..num).step_by(4) {
+                    assert_eq!(avl.remove(&i), Ok(i));
+                    assert_eq!(avl.insert(i, i), Ok(()));
+                }
+            });
+        }
+
+        for _ in 0..4 {
+            s.spawn(|_| {
+                for _ in 0..10 {
+                    for i in 0..num {
+                        avl.lookup_optimistic(&i, |value| {
+                            if let Some(value) = value {
+                                assert_eq!(*value, i);
+                            }
+                        });
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for i in 0..num {
+        assert_eq!(avl.lookup_optimistic(&i, |value| value.cloned()), Some(i));
+    }
+}
+
+/// lookup never locks the node, so the function may look up the same key again
+#[test]
+fn test_seqlock_avl_tree_lookup_nested() {
+    let avl: SeqLockAVLTree<i32, i32> = SeqLockAVLTree::new();
+    assert_eq!(avl.insert(1, 1), Ok(()));
+
+    let found = avl.lookup(&1, |outer| {
+        avl.lookup(&1, |inner| outer.zip(inner).map(|(outer, inner)| outer + inner))
+    });
+    assert_eq!(found, Some(2));
+}
+
+#[test]
+fn test_seqlock_avl_tree_lookup_remove_concurrent() {
+    test_lookup_remove_concurrent::<SeqLockAVLTree<_, _>>();
+}
+
+#[test]
+fn test_seqlock_avl_tree_get_remove_concurrent() {
+    let num = 64;
+    let avl: SeqLockAVLTree<i32, String> = SeqLockAVLTree::new();
+    let value = |key: i32| key.to_string().repeat(16);
+
+    // the optimistic readers clone the values which the writers remove or replace at the same time
+    thread::scope(|s| {
+        let avl = &avl;
+
+        for _ in 0..4 {
+            s.spawn(move |_| {
+                for _ in 0..1_000 {
+                    for i in 0..num {
+                        let _ = avl.insert(i, value(i));
+                        let _ = avl.upsert(&i, value(i));
+
+                        if let Ok(removed) = avl.remove(&i) {
+                            assert_eq!(removed, value(i));
+                        }
+                    }
+                }
+            });
+        }
+
+        for _ in 0..4 {
+            s.spawn(move |_| {
+                for _ in 0..1_000 {
+                    for i in 0..num {
+                        if let Some(found) = avl.get(&i) {
+                            assert_eq!(found, value(i));
+                        }
+
+                        avl.lookup_optimistic(&i, |found| {
+                            if let Some(found) = found {
+                                assert_eq!(*found, value(i));
+                            }
+                        });
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn test_seqlock_avl_tree_iter_range() {
+    let num = 1024;
+    let avl: SeqLockAVLTree<i32, i32> = SeqLockAVLTree::new();
+
+    for i in 0..num {
+        assert_eq!(avl.insert(i, i), Ok(()));
+    }
+
+    // the removed nodes may remain as logically removed ones
+    for i in (0..num).step_by(3) {
+        assert_eq!(avl.remove(&i), Ok(i));
+    }
+
+    let guard = pin();
+    let expected: Vec<_> = (0..num).filter(|i| i % 3 != 0).map(|i| (i, i)).collect();
+
+    assert!(avl.iter(&guard).eq(expected.iter().cloned()));
+    assert!(avl.range(100..200, &guard).eq(expected
+        .iter()
+        .cloned()
+        .filter(|(k, _)| (100..200).contains(k))));
+    assert!(avl
+        .range(..=30, &guard)
+        .eq(expected.iter().cloned().filter(|(k, _)| *k <= 30)));
+    assert_eq!(avl.range(num.., &guard).next(), None);
+}
+
+#[test]
+fn test_seqlock_avl_tree_iter_concurrent() {
+    let num = 1024;
+    let avl: SeqLockAVLTree<i32, i32> = SeqLockAVLTree::new();
+
+    for i in 0..num {
+        assert_eq!(avl.insert(i, i), Ok(()));
+    }
+
+    thread::scope(|s| {
+        let avl = &avl;
+
+        // writers remove and reinsert the odd keys, while the even keys always exist
+        for t in 0..4 {
+            s.spawn(move |_| {
+                for _ in 0..50 {
+                    for i in (2 * t + 1..num).step_by(8) {
+                        assert_eq!(avl.remove(&i), Ok(i));
+                    }
+
+                    for i in (2 * t + 1..num).step_by(8) {
+                        assert_eq!(avl.insert(i, i), Ok(()));
+                    }
+                }
+            });
+        }
+
+        for _ in 0..4 {
+            s.spawn(move |_| {
+                for _ in 0..50 {
+                    let guard = pin();
+                    let entries: Vec<_> = avl.iter(&guard).collect();
+
+                    assert!(entries.windows(2).all(|w| w[0].0 < w[1].0));
+                    assert!(entries.iter().all(|(k, v)| k == v));
+
+                    let evens: Vec<_> = entries
+                        .iter()
+                        .map(|(k, _)| *k)
+                        .filter(|k| k % 2 == 0)
+                        .collect();
+                    assert_eq!(evens, (0..num).step_by(2).collect::<Vec<_>>());
+                }
+            });
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn test_seqlock_avl_tree_iter_remove_concurrent() {
+    let num = 256;
+    let avl: SeqLockAVLTree<i32, String> = SeqLockAVLTree::new();
+    let value = |key: i32| key.to_string().repeat(16);
+
+    // the iterators clone the values which the writers remove at the same time
+    thread::scope(|s| {
+        let avl = &avl;
+
+        for t in 0..4 {
+            s.spawn(move |_| {
+                for _ in 0..100 {
+                    for i in (t..num).step_by(4) {
+                        assert_eq!(avl.insert(i, value(i)), Ok(()));
+                    }
+
+                    for i in (t..num).step_by(4) {
+                        assert_eq!(avl.remove(&i), Ok(value(i)));
+                    }
+                }
+            });
+        }
+
+        for _ in 0..4 {
+            s.spawn(move |_| {
+                for _ in 0..100 {
+                    let guard = pin();
+                    assert!(avl.iter(&guard).all(|(k, v)| v == value(k)));
+                }
+            });
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn test_seqlock_avl_tree_compute() {
+    let avl: SeqLockAVLTree<i32, i32> = SeqLockAVLTree::new();
+
+    assert_eq!(avl.upsert(&1, 1), None);
+    assert_eq!(avl.upsert(&1, 2), Some(1));
+    assert_eq!(avl.get(&1), Some(2));
+
+    assert_eq!(avl.compute(&1, |v| v.map(|v| v + 1)), Some(2));
+    assert_eq!(avl.get(&1), Some(3));
+    assert_eq!(avl.compute(&1, |_| None), Some(3));
+    assert_eq!(avl.get(&1), None);
+    assert_eq!(avl.compute(&2, |v| Some(v.map_or(10, |v| v + 1))), None);
+    assert_eq!(avl.get(&2), Some(10));
+
+    assert_eq!(avl.get_or_insert_with(&2, || 20), 10);
+    assert_eq!(avl.get_or_insert_with(&3, || 30), 30);
+    assert_eq!(avl.get(&3), Some(30));
+
+    assert_eq!(avl.compare_and_swap_value(&3, &31, 32), Err(32));
+    assert_eq!(avl.compare_and_swap_value(&3, &30, 32), Ok(30));
+    assert_eq!(avl.compare_and_swap_value(&4, &0, 1), Err(1));
+    assert_eq!(avl.get(&3), Some(32));
+
+    assert_eq!(avl.insert(0, 0), Ok(()));
+    assert_eq!(avl.insert(1, 1), Ok(()));
+    assert_eq!(avl.insert(2, 2), Err((2, 2)));
+}
+
+#[test]
+fn test_seqlock_avl_tree_compute_panic() {
+    let avl: SeqLockAVLTree<i32, String> = SeqLockAVLTree::new();
+    assert_eq!(avl.insert(1, "1".to_string()), Ok(()));
+
+    // the panic in the function neither drops the value nor keeps the node locked
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        avl.compute(&1, |_| panic!("compute"));
+    }));
+
+    assert!(result.is_err());
+    assert_eq!(avl.get(&1), Some("1".to_string()));
+    assert_eq!(avl.upsert(&1, "2".to_string()), Some("1".to_string()));
+    assert_eq!(avl.get(&1), Some("2".to_string()));
+}
+
+#[test]
+fn test_seqlock_avl_tree_str_key() {
+    let avl: SeqLockAVLTree<String, i32> = SeqLockAVLTree::new();
+
+    assert_eq!(avl.insert("b".to_string(), 2), Ok(()));
+    assert_eq!(avl.insert("b".to_string(), 3), Err(("b".to_string(), 3)));
+    assert_eq!(avl.upsert("a", 1), None);
+    assert_eq!(avl.compute("c", |_| Some(3)), None);
+
+    assert_eq!(avl.get("a"), Some(1));
+    assert_eq!(avl.lookup_optimistic("b", |v| v.cloned()), Some(2));
+    assert_eq!(avl.remove("c"), Ok(3));
+    assert_eq!(avl.get("c"), None);
+}
+
+#[test]
+fn test_seqlock_avl_tree_compute_concurrent() {
+    let keys = 16;
+    let threads = 8;
+    let iters = 1024;
+    let avl: SeqLockAVLTree<i32, i32> = SeqLockAVLTree::new();
+
+    thread::scope(|s| {
+        let avl = &avl;
+
+        for _ in 0..threads {
+            s.spawn(move |_| {
+                for i in 0..iters {
+                    let key = i % keys;
+                    avl.compute(&key, |v| Some(v.map_or(1, |v| v + 1)));
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for key in 0..keys {
+        assert_eq!(avl.get(&key), Some(threads * iters / keys));
+    }
+}
+
+#[test]
+fn test_seqlock_avl_tree_len_concurrent() {
+    let threads = 8;
+    let iters = 1024;
+    let avl: SeqLockAVLTree<i32, i32> = SeqLockAVLTree::new();
+
+    thread::scope(|s| {
+        let avl = &avl;
+
+        for t in 0..threads {
+            s.spawn(move |_| {
+                for i in 0..iters {
+                    let key = t * iters + i;
+                    assert_eq!(avl.insert(key, key), Ok(()));
+
+                    if i % 4 == 0 {
+                        assert_eq!(avl.remove(&key), Ok(key));
+                    } else if i % 4 == 1 {
+                        assert_eq!(avl.compute(&key, |_| None), Some(key));
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(avl.len(), (threads * iters / 2) as usize);
+
+    for key in 0..threads * iters {
+        let _ = avl.remove(&key);
+    }
+
+    assert!(avl.is_empty());
+}
+
+#[test]
+fn test_seqlock_avl_tree_non_default_key() {
+    let value = Arc::new(0);
+    let avl: SeqLockAVLTree<NonZeroU64, Arc<i32>> = SeqLockAVLTree::new();
+
+    for i in 1..=64 {
+        let key = NonZeroU64::new(i).unwrap();
+        assert_eq!(avl.insert(key, value.clone()), Ok(()));
+    }
+
+    for i in (1..=64).step_by(2) {
+        assert!(avl.remove(&NonZeroU64::new(i).unwrap()).is_ok());
+    }
+
+    // the removed values are dropped after the epoch of the optimistic readers
+    while Arc::strong_count(&value) > 33 {
+        pin().flush();
+    }
+
+    assert_eq!(Arc::strong_count(&value), 33);
+    assert_eq!(avl.get(&NonZeroU64::new(2).unwrap()), Some(value.clone()));
+    assert_eq!(avl.get(&NonZeroU64::new(1).unwrap()), None);
+
+    drop(avl);
+    assert_eq!(Arc::strong_count(&value), 1);
+}
+
+#[test]
+fn test_seqlock_avl_tree_ordered() {
+    let num = 1024;
+    let avl: SeqLockAVLTree<i32, i32> = SeqLockAVLTree::new();
+
+    assert_eq!(avl.first(), None);
+    assert_eq!(avl.pop_last(), None);
+    assert_eq!(avl.ceiling(&0), None);
+
+    for i in 0..num {
+        assert_eq!(avl.insert(i * 2, i * 2), Ok(()));
+    }
+
+    // the logically removed nodes are skipped
+    for i in (0..num).step_by(3) {
+        assert_eq!(avl.remove(&(i * 2)), Ok(i * 2));
+    }
+
+    let exists = |key: i32| key % 2 == 0 && (0..num * 2).contains(&key) && key % 6 != 0;
+
+    for key in -1..num * 2 + 1 {
+        let floor = (-1..=key).rev().find(|k| exists(*k));
+        let ceiling = (key..num * 2).find(|k| exists(*k));
+
+        assert_eq!(avl.floor(&key), floor.map(|k| (k, k)));
+        assert_eq!(avl.ceiling(&key), ceiling.map(|k| (k, k)));
+    }
+
+    assert_eq!(avl.first(), Some((2, 2)));
+    assert_eq!(avl.last(), Some((2044, 2044)));
+    assert_eq!(avl.pop_first(), Some((2, 2)));
+    assert_eq!(avl.pop_last(), Some((2044, 2044)));
+    assert_eq!(avl.first(), Some((4, 4)));
+    assert_eq!(avl.last(), Some((2042, 2042)));
+    assert_eq!(avl.get(&2), None);
+}
+
+#[test]
+fn test_seqlock_avl_tree_ordered_concurrent() {
+    let num = 1024;
+    let avl: SeqLockAVLTree<i32, i32> = SeqLockAVLTree::new();
+
+    for i in 0..num {
+        assert_eq!(avl.insert(i, i), Ok(()));
+    }
+
+    thread::scope(|s| {
+        let avl = &avl;
+
+        // writers remove and reinsert the odd keys, while the even keys always exist
+        for t in 0..2 {
+            s.spawn(move |_| {
+                for _ in 0..20 {
+                    for i in (2 * t + 1..num).step_by(4) {
+                        assert_eq!(avl.remove(&i), Ok(i));
+                    }
+
+                    for i in (2 * t + 1..num).step_by(4) {
+                        assert_eq!(avl.insert(i, i), Ok(()));
+                    }
+                }
+            });
+        }
+
+        for _ in 0..2 {
+            s.spawn(move |_| {
+                for _ in 0..20 {
+                    for i in (1..num - 1).step_by(2) {
+                        let floor = avl.floor(&i).unwrap().0;
+                        let ceiling = avl.ceiling(&i).unwrap().0;
+
+                        assert!(floor == i || floor == i - 1);
+                        assert!(ceiling == i || ceiling == i + 1);
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    // the keys are popped exactly once, and in the order on each thread
+    let popped: Vec<Vec<_>> = thread::scope(|s| {
+        let avl = &avl;
+
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                s.spawn(move |_| {
+                    let mut popped = Vec::new();
+
+                    while let Some((key, _)) = if t % 2 == 0 {
+                        avl.pop_first()
+                    } else {
+                        avl.pop_last()
+                    } {
+                        popped.push(key);
+                    }
+
+                    popped
+                })
+            })
+            .collect();
+
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    })
+    .unwrap();
+
+    for (t, keys) in popped.iter().enumerate() {
+        if t % 2 == 0 {
+            assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        } else {
+            assert!(keys.windows(2).all(|w| w[0] > w[1]));
+        }
+    }
+
+    let mut keys: Vec<_> = popped.into_iter().flatten().collect();
+    keys.sort_unstable();
+
+    assert_eq!(keys, (0..num).collect::<Vec<_>>());
+    assert!(avl.is_empty());
+}
+
 #[test]
 fn stress_seqlock_avl_tree_sequential() {
     stress_concurrent_as_sequential::<u8, SeqLockAVLTree<_, _>>(100_000);
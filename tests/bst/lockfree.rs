@@ -0,0 +1,119 @@
+use cds::{
+    bst::LockFreeBST,
+    map::{ConcurrentLookup, ConcurrentMap},
+};
+use crossbeam_utils::thread;
+
+use crate::util::map::{
+    stress_concurrent, stress_concurrent_as_sequential, test_compute_concurrent,
+    test_lookup_remove_concurrent,
+};
+
+#[test]
+fn test_lockfree_bst() {
+    let num = 4096;
+    let tree: LockFreeBST<i32, i32> = LockFreeBST::new();
+
+    // the reversed half makes long paths on both sides
+    let keys = (0..num / 2).chain((num / 2..num).rev());
+
+    for i in keys {
+        assert_eq!(tree.insert(i, i), Ok(()));
+    }
+
+    for i in 0..num {
+        assert_eq!(tree.insert(i, i), Err((i, i)));
+    }
+
+    for i in 0..num {
+        assert_eq!(tree.get(&i), Some(i));
+    }
+
+    for i in (0..num).step_by(2) {
+        assert_eq!(tree.remove(&i), Ok(i));
+    }
+
+    for i in 0..num {
+        let expected = if i % 2 == 0 { None } else { Some(i) };
+        assert_eq!(tree.get(&i), expected);
+    }
+
+    for i in 0..num {
+        let expected = if i % 2 == 0 { Err(()) } else { Ok(i) };
+        assert_eq!(tree.remove(&i), expected);
+    }
+}
+
+#[test]
+fn test_lockfree_bst_drop() {
+    let tree: LockFreeBST<String, String> = LockFreeBST::new();
+
+    for i in 0..100 {
+        assert_eq!(tree.insert(i.to_string(), i.to_string()), Ok(()));
+    }
+
+    for i in (0..100).step_by(3) {
+        assert_eq!(tree.remove(&i.to_string()), Ok(i.to_string()));
+    }
+
+    assert_eq!(tree.upsert("1", "one".to_string()), Some("1".to_string()));
+    assert_eq!(tree.get("1"), Some("one".to_string()));
+}
+
+#[test]
+fn test_lockfree_bst_lookup_remove_concurrent() {
+    test_lookup_remove_concurrent::<LockFreeBST<_, _>>();
+}
+
+#[test]
+fn test_lockfree_bst_remove_concurrent() {
+    let tree: LockFreeBST<i32, i32> = LockFreeBST::new();
+    let num = 1024;
+
+    for i in 0..num {
+        assert_eq!(tree.insert(i, i), Ok(()));
+    }
+
+    // every key is removed by exactly one thread
+    let removed: i32 = thread::scope(|s| {
+        let handles = (0..8)
+            .map(|_| {
+                s.spawn(|_| {
+                    (0..num)
+                        .filter(|i| tree.remove(i).is_ok())
+                        .map(|_| 1)
+                        .sum::<i32>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles.into_iter().map(|h| h.join().unwrap()).sum()
+    })
+    .unwrap();
+
+    assert_eq!(removed, num);
+
+    for i in 0..num {
+        assert_eq!(tree.get(&i), None);
+    }
+}
+
+#[test]
+fn test_lockfree_bst_compute_concurrent() {
+    test_compute_concurrent::<LockFreeBST<_, _>>();
+}
+
+#[test]
+fn stress_lockfree_bst_sequential() {
+    stress_concurrent_as_sequential::<u8, LockFreeBST<_, _>>(100_000);
+}
+
+#[test]
+fn stress_lockfree_bst_concurrent() {
+    stress_concurrent::<u32, LockFreeBST<_, _>>(200_000, 16, false);
+}
+
+#[test]
+fn assert_lockfree_bst_concurrent() {
+    stress_concurrent::<u8, LockFreeBST<_, _>>(100_000, 32, true);
+}
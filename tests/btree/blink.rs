@@ -0,0 +1,36 @@
+use cds::btree::BLinkTree;
+
+use crate::util::map::{
+    stress_concurrent, stress_concurrent_as_sequential, test_compute_concurrent,
+    test_compute_panic, test_insert_lookup_remove,
+};
+
+#[test]
+fn test_blink_tree() {
+    test_insert_lookup_remove::<BLinkTree<_, _>>(4096);
+}
+
+#[test]
+fn test_blink_tree_compute_concurrent() {
+    test_compute_concurrent::<BLinkTree<_, _>>();
+}
+
+#[test]
+fn test_blink_tree_compute_panic() {
+    test_compute_panic::<BLinkTree<_, _>>();
+}
+
+#[test]
+fn stress_blink_tree_sequential() {
+    stress_concurrent_as_sequential::<u8, BLinkTree<_, _>>(100_000);
+}
+
+#[test]
+fn stress_blink_tree_concurrent() {
+    stress_concurrent::<u32, BLinkTree<_, _>>(200_000, 16, false);
+}
+
+#[test]
+fn assert_blink_tree_concurrent() {
+    stress_concurrent::<u8, BLinkTree<_, _>>(100_000, 32, true);
+}
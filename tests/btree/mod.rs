@@ -1,6 +1,14 @@
-use cds::{btree::BTree, map::SequentialMap};
+mod blink;
 
-use crate::util::map::{stress_sequential};
+use std::collections::{BTreeMap, VecDeque};
+
+use cds::{
+    btree::BTree,
+    map::{OrderedMap, SequentialLookup, SequentialMap},
+};
+use rand::{prelude::SliceRandom, thread_rng};
+
+use crate::util::map::stress_sequential;
 
 #[test]
 fn test_insert_lookup_btree() {
@@ -8,7 +16,7 @@ fn test_insert_lookup_btree() {
     let mut tree: BTree<i32, i32> = BTree::new();
 
     for i in 0..num {
-        assert_eq!(tree.insert(&i, i), Ok(()));
+        assert_eq!(tree.insert(i, i), Ok(()));
         tree.assert();
         // tree.print();
     }
@@ -18,16 +26,37 @@ fn test_insert_lookup_btree() {
     }
 }
 
+#[test]
+fn test_str_key_btree() {
+    let mut tree: BTree<String, i32> = BTree::new();
+
+    for i in 0..64 {
+        assert_eq!(tree.insert(i.to_string(), i), Ok(()));
+    }
+
+    assert_eq!(tree.insert("0".to_string(), 1), Err(("0".to_string(), 1)));
+
+    for i in 0..64 {
+        assert_eq!(tree.lookup(i.to_string().as_str()), Some(&i));
+    }
+
+    assert_eq!(tree.len(), 64);
+    assert_eq!(tree.remove("63"), Ok(63));
+    assert_eq!(tree.lookup("63"), None);
+    assert_eq!(tree.len(), 63);
+}
+
 #[test]
 fn test_remove_btree() {
     // CASE 0-1: remove on leaf root
     {
         let mut tree: BTree<i32, i32> = BTree::new();
-        assert_eq!(tree.insert(&1, 1), Ok(()));
+        assert_eq!(tree.insert(1, 1), Ok(()));
         // tree.print();
         assert_eq!(tree.remove(&1), Ok(1));
         // tree.print();
         tree.assert();
+        assert!(tree.is_empty());
     }
 
     // CASE 0-2: remove on non-leaf root
@@ -36,12 +65,12 @@ fn test_remove_btree() {
 
         let mut tree: BTree<i32, i32> = BTree::new();
 
-        assert_eq!(tree.insert(&0, 0), Ok(()));
-        assert_eq!(tree.insert(&2, 2), Ok(()));
-        assert_eq!(tree.insert(&3, 3), Ok(()));
-        assert_eq!(tree.insert(&4, 4), Ok(()));
-        assert_eq!(tree.insert(&5, 5), Ok(()));
-        assert_eq!(tree.insert(&1, 1), Ok(()));
+        assert_eq!(tree.insert(0, 0), Ok(()));
+        assert_eq!(tree.insert(2, 2), Ok(()));
+        assert_eq!(tree.insert(3, 3), Ok(()));
+        assert_eq!(tree.insert(4, 4), Ok(()));
+        assert_eq!(tree.insert(5, 5), Ok(()));
+        assert_eq!(tree.insert(1, 1), Ok(()));
 
         // tree.print();
         assert_eq!(tree.remove(&target), Ok(target));
@@ -65,7 +94,7 @@ fn test_remove_btree() {
         let mut tree: BTree<i32, i32> = BTree::new();
 
         for i in 0..3 {
-            assert_eq!(tree.insert(&i, i), Ok(()));
+            assert_eq!(tree.insert(i, i), Ok(()));
         }
 
         // tree.print();
@@ -89,7 +118,7 @@ fn test_remove_btree() {
         let mut tree: BTree<i32, i32> = BTree::new();
 
         for i in 0..3 {
-            assert_eq!(tree.insert(&i, i), Ok(()));
+            assert_eq!(tree.insert(i, i), Ok(()));
         }
 
         // tree.print();
@@ -112,7 +141,7 @@ fn test_remove_btree() {
         let mut tree: BTree<i32, i32> = BTree::new();
 
         for i in 0..7 {
-            assert_eq!(tree.insert(&i, i), Ok(()));
+            assert_eq!(tree.insert(i, i), Ok(()));
         }
 
         // tree.print();
@@ -135,7 +164,7 @@ fn test_remove_btree() {
         let mut tree: BTree<i32, i32> = BTree::new();
 
         for i in 0..7 {
-            assert_eq!(tree.insert(&i, i), Ok(()));
+            assert_eq!(tree.insert(i, i), Ok(()));
         }
 
         // tree.print();
@@ -159,7 +188,7 @@ fn test_remove_btree() {
         let mut tree: BTree<i32, i32> = BTree::new();
 
         for i in 0..4 {
-            assert_eq!(tree.insert(&i, i), Ok(()));
+            assert_eq!(tree.insert(i, i), Ok(()));
         }
 
         // tree.print();
@@ -183,7 +212,7 @@ fn test_remove_btree() {
         let mut tree: BTree<i32, i32> = BTree::new();
 
         for i in 0..9 {
-            assert_eq!(tree.insert(&i, i), Ok(()));
+            assert_eq!(tree.insert(i, i), Ok(()));
         }
 
         // tree.print();
@@ -207,9 +236,9 @@ fn test_remove_btree() {
         let mut tree: BTree<i32, i32> = BTree::new();
 
         for i in 1..4 {
-            assert_eq!(tree.insert(&i, i), Ok(()));
+            assert_eq!(tree.insert(i, i), Ok(()));
         }
-        assert_eq!(tree.insert(&0, 0), Ok(()));
+        assert_eq!(tree.insert(0, 0), Ok(()));
 
         // tree.print();
         assert_eq!(tree.remove(&target), Ok(target));
@@ -232,10 +261,10 @@ fn test_remove_btree() {
         let mut tree: BTree<i32, i32> = BTree::new();
 
         for i in 2..9 {
-            assert_eq!(tree.insert(&i, i), Ok(()));
+            assert_eq!(tree.insert(i, i), Ok(()));
         }
-        assert_eq!(tree.insert(&1, 1), Ok(()));
-        assert_eq!(tree.insert(&0, 0), Ok(()));
+        assert_eq!(tree.insert(1, 1), Ok(()));
+        assert_eq!(tree.insert(0, 0), Ok(()));
 
         // tree.print();
         assert_eq!(tree.remove(&target), Ok(target));
@@ -258,7 +287,7 @@ fn test_remove_btree() {
         let mut tree: BTree<i32, i32> = BTree::new();
 
         for i in 0..5 {
-            assert_eq!(tree.insert(&i, i), Ok(()));
+            assert_eq!(tree.insert(i, i), Ok(()));
         }
 
         // tree.print();
@@ -282,7 +311,7 @@ fn test_remove_btree() {
         let mut tree: BTree<i32, i32> = BTree::new();
 
         for i in 0..11 {
-            assert_eq!(tree.insert(&i, i), Ok(()));
+            assert_eq!(tree.insert(i, i), Ok(()));
         }
 
         // tree.print();
@@ -306,7 +335,7 @@ fn test_remove_btree() {
         let mut tree: BTree<i32, i32> = BTree::new();
 
         for i in 0..5 {
-            assert_eq!(tree.insert(&i, i), Ok(()));
+            assert_eq!(tree.insert(i, i), Ok(()));
         }
 
         // tree.print();
@@ -330,7 +359,7 @@ fn test_remove_btree() {
         let mut tree: BTree<i32, i32> = BTree::new();
 
         for i in 0..11 {
-            assert_eq!(tree.insert(&i, i), Ok(()));
+            assert_eq!(tree.insert(i, i), Ok(()));
         }
 
         // tree.print();
@@ -353,12 +382,12 @@ fn test_remove_btree() {
 
         let mut tree: BTree<i32, i32> = BTree::new();
 
-        assert_eq!(tree.insert(&0, 0), Ok(()));
-        assert_eq!(tree.insert(&1, 1), Ok(()));
-        assert_eq!(tree.insert(&2, 2), Ok(()));
-        assert_eq!(tree.insert(&4, 4), Ok(()));
-        assert_eq!(tree.insert(&5, 5), Ok(()));
-        assert_eq!(tree.insert(&3, 3), Ok(()));
+        assert_eq!(tree.insert(0, 0), Ok(()));
+        assert_eq!(tree.insert(1, 1), Ok(()));
+        assert_eq!(tree.insert(2, 2), Ok(()));
+        assert_eq!(tree.insert(4, 4), Ok(()));
+        assert_eq!(tree.insert(5, 5), Ok(()));
+        assert_eq!(tree.insert(3, 3), Ok(()));
 
         // tree.print();
         assert_eq!(tree.remove(&target), Ok(target));
@@ -381,15 +410,15 @@ fn test_remove_btree() {
         let mut tree: BTree<i32, i32> = BTree::new();
 
         for i in 0..7 {
-            assert_eq!(tree.insert(&i, i), Ok(()));
+            assert_eq!(tree.insert(i, i), Ok(()));
         }
 
         for i in 9..13 {
-            assert_eq!(tree.insert(&i, i), Ok(()));
+            assert_eq!(tree.insert(i, i), Ok(()));
         }
 
-        assert_eq!(tree.insert(&7, 7), Ok(()));
-        assert_eq!(tree.insert(&8, 8), Ok(()));
+        assert_eq!(tree.insert(7, 7), Ok(()));
+        assert_eq!(tree.insert(8, 8), Ok(()));
 
         // tree.print();
         assert_eq!(tree.remove(&target), Ok(target));
@@ -411,12 +440,12 @@ fn test_remove_btree() {
 
         let mut tree: BTree<i32, i32> = BTree::new();
 
-        assert_eq!(tree.insert(&0, 0), Ok(()));
-        assert_eq!(tree.insert(&1, 1), Ok(()));
-        assert_eq!(tree.insert(&2, 2), Ok(()));
-        assert_eq!(tree.insert(&4, 4), Ok(()));
-        assert_eq!(tree.insert(&5, 5), Ok(()));
-        assert_eq!(tree.insert(&3, 3), Ok(()));
+        assert_eq!(tree.insert(0, 0), Ok(()));
+        assert_eq!(tree.insert(1, 1), Ok(()));
+        assert_eq!(tree.insert(2, 2), Ok(()));
+        assert_eq!(tree.insert(4, 4), Ok(()));
+        assert_eq!(tree.insert(5, 5), Ok(()));
+        assert_eq!(tree.insert(3, 3), Ok(()));
 
         // tree.print();
         assert_eq!(tree.remove(&target), Ok(target));
@@ -439,15 +468,15 @@ fn test_remove_btree() {
         let mut tree: BTree<i32, i32> = BTree::new();
 
         for i in 0..7 {
-            assert_eq!(tree.insert(&i, i), Ok(()));
+            assert_eq!(tree.insert(i, i), Ok(()));
         }
 
         for i in 9..13 {
-            assert_eq!(tree.insert(&i, i), Ok(()));
+            assert_eq!(tree.insert(i, i), Ok(()));
         }
 
-        assert_eq!(tree.insert(&7, 7), Ok(()));
-        assert_eq!(tree.insert(&8, 8), Ok(()));
+        assert_eq!(tree.insert(7, 7), Ok(()));
+        assert_eq!(tree.insert(8, 8), Ok(()));
 
         // tree.print();
         assert_eq!(tree.remove(&target), Ok(target));
@@ -468,3 +497,224 @@ fn test_remove_btree() {
 fn stress_btree() {
     stress_sequential::<String, BTree<_, _>>(100_000);
 }
+
+#[test]
+// the reversed range is intended to check that it is empty
+#[allow(clippy::reversed_empty_ranges)]
+fn test_iter_range_btree() {
+    let num = 4096;
+    let mut keys: Vec<i32> = (0..num).map(|i| i * 2).collect();
+    keys.shuffle(&mut thread_rng());
+
+    let mut tree: BTree<i32, i32> = BTree::new();
+    let mut reference = BTreeMap::new();
+
+    for key in keys {
+        assert_eq!(tree.insert(key, key), Ok(()));
+        reference.insert(key, key);
+    }
+
+    assert!(tree.iter().eq(reference.iter()));
+    assert!(tree.keys().eq(reference.keys()));
+    assert!(tree.values().eq(reference.values()));
+
+    for (start, end) in [(-1, 10), (0, 9), (1, 8), (3000, 5000), (8190, 9000)] {
+        assert!(tree.range(start..end).eq(reference.range(start..end)));
+        assert!(tree.range(start..=end).eq(reference.range(start..=end)));
+        assert!(tree.range(start..).eq(reference.range(start..)));
+        assert!(tree.range(..end).eq(reference.range(..end)));
+    }
+
+    assert_eq!(tree.range(100..50).next(), None);
+
+    for (_, value) in tree.iter_mut() {
+        *value += 1;
+    }
+
+    for (_, value) in &mut tree {
+        *value -= 2;
+    }
+
+    assert!(tree
+        .into_iter()
+        .eq(reference.into_iter().map(|(key, value)| (key, value - 1))));
+}
+
+#[test]
+fn test_from_sorted_iter_btree() {
+    for num in [0, 1, 11, 12, 143, 144, 1000, 20_000] {
+        let tree = BTree::from_sorted_iter((0..num).map(|i| (i, i)));
+        tree.assert();
+
+        assert_eq!(tree.len(), num as usize);
+        assert!(tree
+            .iter()
+            .map(|(k, v)| (*k, *v))
+            .eq((0..num).map(|i| (i, i))));
+
+        for i in 0..num {
+            assert_eq!(tree.lookup(&i), Some(&i));
+        }
+    }
+
+    let mut tree = BTree::from_sorted_iter((0..1000).map(|i| (i * 2, i)));
+
+    for i in 0..1000 {
+        assert_eq!(tree.insert(i * 2 + 1, i), Ok(()));
+        tree.assert();
+    }
+
+    for i in 0..2000 {
+        assert!(tree.remove(&i).is_ok());
+        tree.assert();
+    }
+
+    assert!(tree.is_empty());
+}
+
+#[test]
+#[should_panic]
+fn test_from_sorted_iter_unsorted_btree() {
+    let _ = BTree::from_sorted_iter(vec![(1, 1), (0, 0)]);
+}
+
+#[test]
+fn test_from_iter_btree() {
+    let mut entries: Vec<_> = (0..10_000).map(|i| (i % 5000, i)).collect();
+    entries.shuffle(&mut thread_rng());
+
+    let tree: BTree<i32, i32> = entries.iter().cloned().collect();
+    tree.assert();
+
+    let mut reference = BTreeMap::new();
+
+    for (key, value) in entries {
+        reference.entry(key).or_insert(value);
+    }
+
+    assert_eq!(tree.len(), 5000);
+    assert!(tree.iter().eq(reference.iter()));
+}
+
+#[test]
+fn test_append_btree() {
+    // the keys of the one are all less than the other, on various heights
+    for (left, right) in [(0, 10), (10, 0), (1, 1), (5, 5000), (5000, 5), (3000, 3000)] {
+        let mut tree = BTree::from_sorted_iter((0..left).map(|i| (i, i)));
+        let mut other = BTree::from_sorted_iter((left..left + right).map(|i| (i, i)));
+
+        tree.append(&mut other);
+        tree.assert();
+        other.assert();
+
+        assert!(other.is_empty());
+        assert_eq!(tree.len(), (left + right) as usize);
+        assert!(tree.keys().cloned().eq(0..left + right));
+
+        let mut tree = BTree::from_sorted_iter((0..left).map(|i| (i, i)));
+        let mut other = BTree::from_sorted_iter((left..left + right).map(|i| (i, i)));
+
+        other.append(&mut tree);
+        other.assert();
+
+        assert!(tree.is_empty());
+        assert!(other.keys().cloned().eq(0..left + right));
+    }
+
+    // the keys are interleaved, and the value of the tree is kept on the same key
+    let mut tree = BTree::from_sorted_iter((0..1000).map(|i| (i * 2, 0)));
+    let mut other = BTree::from_sorted_iter((0..1000).map(|i| (i * 3, 1)));
+
+    tree.append(&mut other);
+    tree.assert();
+
+    assert!(other.is_empty());
+    assert_eq!(tree.len(), 1000 + 1000 - 334);
+
+    for i in 0..1000 {
+        assert_eq!(tree.lookup(&(i * 2)), Some(&0));
+    }
+
+    assert_eq!(tree.lookup(&3), Some(&1));
+}
+
+#[test]
+fn test_split_off_btree() {
+    let num = 5000;
+    let mut keys: Vec<i32> = (0..num).map(|i| i * 2).collect();
+    keys.shuffle(&mut thread_rng());
+
+    let mut reference = BTree::new();
+
+    for key in keys {
+        assert_eq!(reference.insert(key, key), Ok(()));
+    }
+
+    for at in [-1, 0, 1, 2, 21, 22, 500, 4999, 5000, 9997, 9998, 10_000] {
+        let mut tree = BTree::from_sorted_iter(reference.iter().map(|(k, v)| (*k, *v)));
+        let right = tree.split_off(&at);
+
+        tree.assert();
+        right.assert();
+
+        assert!(tree.keys().all(|key| *key < at));
+        assert!(right.keys().all(|key| *key >= at));
+        assert_eq!(tree.len() + right.len(), num as usize);
+
+        // the splitted trees can be joined again
+        let mut tree = tree;
+        let mut right = right;
+        tree.append(&mut right);
+        tree.assert();
+
+        assert!(tree.iter().eq(reference.iter()));
+    }
+
+    // split off the tree which has been built by inserting
+    let right = reference.split_off(&7000);
+    reference.assert();
+    right.assert();
+
+    assert_eq!(reference.len(), 3500);
+    assert_eq!(right.len(), 1500);
+}
+
+#[test]
+fn test_ordered_btree() {
+    let num = 5000;
+    let mut keys: Vec<i32> = (0..num).map(|i| i * 2).collect();
+    keys.shuffle(&mut thread_rng());
+
+    let mut tree = BTree::new();
+    let mut reference = BTreeMap::new();
+
+    assert_eq!(tree.first(), None);
+    assert_eq!(tree.last(), None);
+    assert_eq!(tree.pop_first(), None);
+    assert_eq!(tree.floor(&0), None);
+
+    for key in keys {
+        assert_eq!(tree.insert(key, key), Ok(()));
+        reference.insert(key, key);
+    }
+
+    for key in -1..num * 2 + 1 {
+        assert_eq!(tree.floor(&key), reference.range(..=key).next_back());
+        assert_eq!(tree.ceiling(&key), reference.range(key..).next());
+    }
+
+    // pop from both ends alternately until the tree is empty
+    let mut reference: VecDeque<_> = reference.into_iter().collect();
+
+    while !reference.is_empty() {
+        assert_eq!(tree.first(), reference.front().map(|(k, v)| (k, v)));
+        assert_eq!(tree.last(), reference.back().map(|(k, v)| (k, v)));
+
+        assert_eq!(tree.pop_first(), reference.pop_front());
+        assert_eq!(tree.pop_last(), reference.pop_back());
+        assert_eq!(tree.len(), reference.len());
+    }
+
+    tree.assert();
+    assert_eq!(tree.pop_last(), None);
+}
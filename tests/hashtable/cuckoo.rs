@@ -0,0 +1,77 @@
+use std::hash::{Hash, Hasher};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use cds::{
+    hashtable::CuckooHashMap,
+    map::{ConcurrentLookup, ConcurrentMap},
+};
+
+use crate::util::map::{
+    stress_concurrent, stress_concurrent_as_sequential, test_compute_concurrent,
+    test_compute_panic, test_insert_lookup_remove, test_lookup_remove_concurrent,
+};
+
+#[test]
+fn test_cuckoo_hashmap() {
+    test_insert_lookup_remove::<CuckooHashMap<_, _>>(10_000);
+}
+
+#[test]
+fn test_cuckoo_hashmap_lookup_remove_concurrent() {
+    test_lookup_remove_concurrent::<CuckooHashMap<_, _>>();
+}
+
+/// the key whose hashes always collide, and whose comparison with 0 panics
+#[derive(Debug)]
+struct PanicKey(u64);
+
+impl Hash for PanicKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        0.hash(state);
+    }
+}
+
+impl PartialEq for PanicKey {
+    fn eq(&self, other: &Self) -> bool {
+        assert!(self.0 != 0 && other.0 != 0, "compare with 0");
+        self.0 == other.0
+    }
+}
+
+impl Eq for PanicKey {}
+
+#[test]
+fn test_cuckoo_hashmap_lookup_panic() {
+    let map: CuckooHashMap<PanicKey, u64> = CuckooHashMap::new();
+    assert_eq!(map.insert(PanicKey(1), 1), Ok(()));
+
+    // the panic of the comparison is propagated without aborting, and the map is still usable
+    assert!(catch_unwind(AssertUnwindSafe(|| map.get(&PanicKey(0)))).is_err());
+    assert_eq!(map.get(&PanicKey(1)), Some(1));
+    assert_eq!(map.remove(&PanicKey(1)), Ok(1));
+}
+
+#[test]
+fn test_cuckoo_hashmap_compute_concurrent() {
+    test_compute_concurrent::<CuckooHashMap<_, _>>();
+}
+
+#[test]
+fn test_cuckoo_hashmap_compute_panic() {
+    test_compute_panic::<CuckooHashMap<_, _>>();
+}
+
+#[test]
+fn stress_cuckoo_hashmap_sequential() {
+    stress_concurrent_as_sequential::<u8, CuckooHashMap<_, _>>(100_000);
+}
+
+#[test]
+fn stress_cuckoo_hashmap_concurrent() {
+    stress_concurrent::<u32, CuckooHashMap<_, _>>(200_000, 16, false);
+}
+
+#[test]
+fn assert_cuckoo_hashmap_concurrent() {
+    stress_concurrent::<u8, CuckooHashMap<_, _>>(100_000, 32, true);
+}
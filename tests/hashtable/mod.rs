@@ -0,0 +1,4 @@
+mod cuckoo;
+mod robinhood;
+mod splitordered;
+mod striped;
@@ -0,0 +1,46 @@
+use cds::{
+    hashtable::RobinHoodHashMap,
+    map::{SequentialLookup, SequentialMap},
+};
+
+use crate::util::map::stress_sequential;
+
+#[test]
+fn test_robin_hood_hashmap() {
+    let num = 4096;
+    let mut map: RobinHoodHashMap<i32, i32> = RobinHoodHashMap::new();
+
+    for i in 0..num {
+        assert_eq!(map.insert(i, i), Ok(()));
+    }
+
+    for i in 0..num {
+        assert_eq!(map.insert(i, i), Err((i, i)));
+    }
+
+    for i in 0..num {
+        assert_eq!(map.lookup(&i), Some(&i));
+    }
+
+    // remove the half, so that the others are shifted backward
+    for i in (0..num).step_by(2) {
+        assert_eq!(map.remove(&i), Ok(i));
+    }
+
+    for i in 0..num {
+        if i % 2 == 0 {
+            assert_eq!(map.lookup(&i), None);
+        } else {
+            assert_eq!(map.lookup(&i), Some(&i));
+        }
+    }
+
+    for i in 0..num {
+        assert_eq!(map.remove(&i), if i % 2 == 0 { Err(()) } else { Ok(i) });
+    }
+}
+
+#[test]
+fn stress_robin_hood_hashmap() {
+    stress_sequential::<String, RobinHoodHashMap<_, _>>(100_000);
+}
@@ -0,0 +1,57 @@
+use cds::{
+    hashtable::SplitOrderedHashMap,
+    map::{ConcurrentLookup, ConcurrentMap},
+};
+
+use crate::util::map::{
+    stress_concurrent, stress_concurrent_as_sequential, test_compute_concurrent,
+    test_insert_lookup_remove, test_lookup_remove_concurrent,
+};
+
+#[test]
+fn test_split_ordered_hashmap() {
+    test_insert_lookup_remove::<SplitOrderedHashMap<_, _>>(4096);
+}
+
+#[test]
+fn test_split_ordered_hashmap_compute() {
+    let map: SplitOrderedHashMap<i32, i32> = SplitOrderedHashMap::new();
+
+    assert_eq!(map.upsert(&1, 1), None);
+    assert_eq!(map.upsert(&1, 2), Some(1));
+    assert_eq!(map.compute(&1, |v| v.map(|v| v + 1)), Some(2));
+    assert_eq!(map.compute(&1, |_| None), Some(3));
+    assert_eq!(map.get(&1), None);
+
+    assert_eq!(map.get_or_insert_with(&2, || 20), 20);
+    assert_eq!(map.get_or_insert_with(&2, || 30), 20);
+
+    assert_eq!(map.compare_and_swap_value(&2, &30, 40), Err(40));
+    assert_eq!(map.compare_and_swap_value(&2, &20, 40), Ok(20));
+    assert_eq!(map.get(&2), Some(40));
+}
+
+#[test]
+fn test_split_ordered_hashmap_lookup_remove_concurrent() {
+    test_lookup_remove_concurrent::<SplitOrderedHashMap<_, _>>();
+}
+
+#[test]
+fn test_split_ordered_hashmap_compute_concurrent() {
+    test_compute_concurrent::<SplitOrderedHashMap<_, _>>();
+}
+
+#[test]
+fn stress_split_ordered_hashmap_sequential() {
+    stress_concurrent_as_sequential::<u8, SplitOrderedHashMap<_, _>>(100_000);
+}
+
+#[test]
+fn stress_split_ordered_hashmap_concurrent() {
+    stress_concurrent::<u32, SplitOrderedHashMap<_, _>>(200_000, 16, false);
+}
+
+#[test]
+fn assert_split_ordered_hashmap_concurrent() {
+    stress_concurrent::<u8, SplitOrderedHashMap<_, _>>(100_000, 32, true);
+}
@@ -0,0 +1,51 @@
+use cds::{
+    hashtable::StripedHashMap,
+    lock::{RawMutex, RawSpinLock},
+};
+
+use crate::util::map::{
+    stress_concurrent, stress_concurrent_as_sequential, test_compute_concurrent,
+    test_compute_panic, test_insert_lookup_remove, test_lookup_panic,
+};
+
+#[test]
+fn test_striped_hashmap_simple() {
+    test_insert_lookup_remove::<StripedHashMap<_, _, RawSpinLock>>(4096);
+    test_insert_lookup_remove::<StripedHashMap<_, _, RawMutex>>(4096);
+}
+
+#[test]
+fn test_striped_hashmap_lookup_panic() {
+    test_lookup_panic::<StripedHashMap<_, _, RawSpinLock>>();
+    test_lookup_panic::<StripedHashMap<_, _, RawMutex>>();
+}
+
+#[test]
+fn test_striped_hashmap_compute_concurrent() {
+    test_compute_concurrent::<StripedHashMap<_, _, RawSpinLock>>();
+    test_compute_concurrent::<StripedHashMap<_, _, RawMutex>>();
+}
+
+#[test]
+fn test_striped_hashmap_compute_panic() {
+    test_compute_panic::<StripedHashMap<_, _, RawSpinLock>>();
+    test_compute_panic::<StripedHashMap<_, _, RawMutex>>();
+}
+
+#[test]
+fn stress_striped_hashmap_sequential() {
+    stress_concurrent_as_sequential::<u8, StripedHashMap<_, _, RawSpinLock>>(100_000);
+    stress_concurrent_as_sequential::<u8, StripedHashMap<_, _, RawMutex>>(100_000);
+}
+
+#[test]
+fn stress_striped_hashmap_concurrent() {
+    stress_concurrent::<u32, StripedHashMap<_, _, RawSpinLock>>(200_000, 16, false);
+    stress_concurrent::<u32, StripedHashMap<_, _, RawMutex>>(200_000, 16, false);
+}
+
+#[test]
+fn assert_striped_hashmap_concurrent() {
+    stress_concurrent::<u8, StripedHashMap<_, _, RawSpinLock>>(100_000, 32, true);
+    stress_concurrent::<u8, StripedHashMap<_, _, RawMutex>>(100_000, 32, true);
+}
@@ -0,0 +1,58 @@
+use cds::{
+    linkedlist::LazyList,
+    lock::{RawMutex, RawSpinLock},
+};
+
+use crate::util::map::{
+    stress_concurrent, stress_concurrent_as_sequential, test_compute_concurrent,
+    test_compute_panic, test_insert_lookup_remove, test_lookup_panic,
+    test_lookup_remove_concurrent,
+};
+
+#[test]
+fn test_lazy_list_simple() {
+    test_insert_lookup_remove::<LazyList<_, _, RawSpinLock>>(64);
+    test_insert_lookup_remove::<LazyList<_, _, RawMutex>>(64);
+}
+
+#[test]
+fn test_lazy_list_lookup_remove_concurrent() {
+    test_lookup_remove_concurrent::<LazyList<_, _, RawSpinLock>>();
+    test_lookup_remove_concurrent::<LazyList<_, _, RawMutex>>();
+}
+
+#[test]
+fn test_lazy_list_lookup_panic() {
+    test_lookup_panic::<LazyList<_, _, RawSpinLock>>();
+    test_lookup_panic::<LazyList<_, _, RawMutex>>();
+}
+
+#[test]
+fn test_lazy_list_compute_concurrent() {
+    test_compute_concurrent::<LazyList<_, _, RawSpinLock>>();
+    test_compute_concurrent::<LazyList<_, _, RawMutex>>();
+}
+
+#[test]
+fn test_lazy_list_compute_panic() {
+    test_compute_panic::<LazyList<_, _, RawSpinLock>>();
+    test_compute_panic::<LazyList<_, _, RawMutex>>();
+}
+
+#[test]
+fn stress_lazy_list_sequential() {
+    stress_concurrent_as_sequential::<u8, LazyList<_, _, RawSpinLock>>(100_000);
+    stress_concurrent_as_sequential::<u8, LazyList<_, _, RawMutex>>(100_000);
+}
+
+#[test]
+fn stress_lazy_list_concurrent() {
+    stress_concurrent::<u8, LazyList<_, _, RawSpinLock>>(100_000, 16, false);
+    stress_concurrent::<u8, LazyList<_, _, RawMutex>>(100_000, 16, false);
+}
+
+#[test]
+fn assert_lazy_list_concurrent() {
+    stress_concurrent::<u8, LazyList<_, _, RawSpinLock>>(100_000, 32, true);
+    stress_concurrent::<u8, LazyList<_, _, RawMutex>>(100_000, 32, true);
+}
@@ -1,6 +1,6 @@
 use crate::util::map::stress_sequential;
 use cds::linkedlist::LinkedList;
-use cds::map::SequentialMap;
+use cds::map::{SequentialLookup, SequentialMap};
 
 #[test]
 fn test_linkedlist() {
@@ -8,11 +8,11 @@ fn test_linkedlist() {
 
     assert_eq!(list.lookup(&1), None);
 
-    assert_eq!(list.insert(&1, 1), Ok(()));
-    assert_eq!(list.insert(&2, 2), Ok(()));
-    assert_eq!(list.insert(&3, 3), Ok(()));
-    assert_eq!(list.insert(&4, 4), Ok(()));
-    assert_eq!(list.insert(&5, 5), Ok(()));
+    assert_eq!(list.insert(1, 1), Ok(()));
+    assert_eq!(list.insert(2, 2), Ok(()));
+    assert_eq!(list.insert(3, 3), Ok(()));
+    assert_eq!(list.insert(4, 4), Ok(()));
+    assert_eq!(list.insert(5, 5), Ok(()));
 
     assert_eq!(list.lookup(&1), Some(&1));
     assert_eq!(list.lookup(&2), Some(&2));
@@ -33,7 +33,7 @@ fn test_linkedlist() {
     assert_eq!(list.remove(&4), Ok(4));
     assert_eq!(list.remove(&2), Ok(2));
 
-    assert_eq!(list.insert(&0, 0), Ok(()));
+    assert_eq!(list.insert(0, 0), Ok(()));
     assert_eq!(list.lookup(&0), Some(&0));
     assert_eq!(list.remove(&0), Ok(0));
     assert_eq!(list.lookup(&0), None);
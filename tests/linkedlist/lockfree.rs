@@ -0,0 +1,36 @@
+use cds::linkedlist::HarrisList;
+
+use crate::util::map::{
+    stress_concurrent, stress_concurrent_as_sequential, test_compute_concurrent,
+    test_insert_lookup_remove, test_lookup_remove_concurrent,
+};
+
+#[test]
+fn test_harris_list() {
+    test_insert_lookup_remove::<HarrisList<_, _>>(64);
+}
+
+#[test]
+fn test_harris_list_lookup_remove_concurrent() {
+    test_lookup_remove_concurrent::<HarrisList<_, _>>();
+}
+
+#[test]
+fn test_harris_list_compute_concurrent() {
+    test_compute_concurrent::<HarrisList<_, _>>();
+}
+
+#[test]
+fn stress_harris_list_sequential() {
+    stress_concurrent_as_sequential::<u8, HarrisList<_, _>>(100_000);
+}
+
+#[test]
+fn stress_harris_list_concurrent() {
+    stress_concurrent::<u8, HarrisList<_, _>>(100_000, 16, false);
+}
+
+#[test]
+fn assert_harris_list_concurrent() {
+    stress_concurrent::<u8, HarrisList<_, _>>(100_000, 32, true);
+}
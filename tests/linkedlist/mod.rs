@@ -1 +1,4 @@
+mod handoverhand;
+mod lazy;
 mod linkedlist;
+mod lockfree;
@@ -0,0 +1,139 @@
+use std::{cell::Cell, rc::Rc};
+
+use cds::{
+    avltree::AVLTree,
+    btree::BTree,
+    linkedlist::LinkedList,
+    lock::{RawMutex, RawSpinLock},
+    map::{ConcurrentLookup, ConcurrentMap, FCMap},
+};
+use crossbeam_utils::thread;
+
+use crate::util::map::{
+    stress_concurrent, stress_concurrent_as_sequential, test_compute_concurrent,
+    test_compute_panic, test_insert_lookup_remove,
+};
+
+#[test]
+fn test_fc_map_simple() {
+    test_insert_lookup_remove::<FCMap<_, _, RawSpinLock, BTree<_, _>>>(4096);
+    test_insert_lookup_remove::<FCMap<_, _, RawSpinLock, AVLTree<_, _>>>(4096);
+    test_insert_lookup_remove::<FCMap<_, _, RawMutex, BTree<_, _>>>(4096);
+    test_insert_lookup_remove::<FCMap<_, _, RawMutex, AVLTree<_, _>>>(4096);
+}
+
+#[test]
+fn test_fc_map_compute() {
+    let map: FCMap<String, i32, RawSpinLock, BTree<_, _>> = FCMap::new();
+
+    assert_eq!(map.upsert("a", 1), None);
+    assert_eq!(map.upsert("a", 2), Some(1));
+    assert_eq!(map.compute("a", |v| v.map(|v| v + 1)), Some(2));
+    assert_eq!(map.compute("a", |_| None), Some(3));
+    assert_eq!(map.get("a"), None);
+
+    assert_eq!(map.get_or_insert_with("b", || 10), 10);
+    assert_eq!(map.get_or_insert_with("b", || 20), 10);
+    assert_eq!(map.compare_and_swap_value("b", &11, 12), Err(12));
+    assert_eq!(map.compare_and_swap_value("b", &10, 12), Ok(10));
+    assert_eq!(map.len(), 1);
+}
+
+#[test]
+fn test_fc_map_compute_panic() {
+    test_compute_panic::<FCMap<_, _, RawSpinLock, BTree<_, _>>>();
+    test_compute_panic::<FCMap<_, _, RawMutex, AVLTree<_, _>>>();
+}
+
+/// the functions of the caller run on the calling thread, so they need not be sendable
+#[test]
+fn test_fc_map_function_on_caller() {
+    let map: FCMap<i32, i32, RawSpinLock, BTree<_, _>> = FCMap::new();
+    let num = 1024;
+
+    thread::scope(|s| {
+        for t in 0..4 {
+            let map = &map;
+
+            s.spawn(move |_| {
+                let caller = std::thread::current().id();
+                let calls = Rc::new(Cell::new(0));
+
+                for i in (t..num).step_by(4) {
+                    assert_eq!(map.insert(i, i), Ok(()));
+
+                    let found = map.lookup(&i, |value| {
+                        assert_eq!(std::thread::current().id(), caller);
+                        calls.set(calls.get() + 1);
+                        value.copied()
+                    });
+                    assert_eq!(found, Some(i));
+
+                    let old = map.compute(&i, |value| {
+                        assert_eq!(std::thread::current().id(), caller);
+                        calls.set(calls.get() + 1);
+                        value.map(|value| value + 1)
+                    });
+                    assert_eq!(old, Some(i));
+                }
+
+                assert_eq!(calls.get(), num / 2);
+            });
+        }
+    })
+    .unwrap();
+
+    for i in 0..num {
+        assert_eq!(map.get(&i), Some(i + 1));
+    }
+}
+
+#[test]
+fn test_fc_map_len() {
+    let map: FCMap<i32, i32, RawMutex, AVLTree<_, _>> = FCMap::new();
+    let num = 1024;
+
+    thread::scope(|s| {
+        for t in 0..4 {
+            let map = &map;
+
+            s.spawn(move |_| {
+                for i in (t..num).step_by(4) {
+                    assert_eq!(map.insert(i, i), Ok(()));
+                }
+
+                for i in (t..num).step_by(8) {
+                    assert_eq!(map.remove(&i), Ok(i));
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(map.len(), num as usize / 2);
+}
+
+#[test]
+fn test_fc_map_compute_concurrent() {
+    test_compute_concurrent::<FCMap<_, _, RawSpinLock, BTree<_, _>>>();
+    test_compute_concurrent::<FCMap<_, _, RawMutex, AVLTree<_, _>>>();
+}
+
+#[test]
+fn stress_fc_map_sequential() {
+    stress_concurrent_as_sequential::<u8, FCMap<_, _, RawSpinLock, BTree<_, _>>>(100_000);
+    stress_concurrent_as_sequential::<u8, FCMap<_, _, RawSpinLock, AVLTree<_, _>>>(100_000);
+    stress_concurrent_as_sequential::<u8, FCMap<_, _, RawMutex, LinkedList<_, _>>>(100_000);
+}
+
+#[test]
+fn stress_fc_map_concurrent() {
+    stress_concurrent::<u32, FCMap<_, _, RawSpinLock, BTree<_, _>>>(200_000, 16, false);
+    stress_concurrent::<u32, FCMap<_, _, RawMutex, AVLTree<_, _>>>(200_000, 16, false);
+}
+
+#[test]
+fn assert_fc_map_concurrent() {
+    stress_concurrent::<u8, FCMap<_, _, RawSpinLock, BTree<_, _>>>(100_000, 32, true);
+    stress_concurrent::<u8, FCMap<_, _, RawMutex, LinkedList<_, _>>>(100_000, 32, true);
+}
@@ -0,0 +1,95 @@
+use cds::{
+    avltree::AVLTree,
+    btree::BTree,
+    hashtable::RobinHoodHashMap,
+    lock::{RawMutex, RawSpinLock},
+    map::{ConcurrentLookup, ConcurrentMap, Locked, RwLocked},
+};
+use crossbeam_utils::thread;
+
+use crate::util::map::{
+    stress_concurrent, stress_concurrent_as_sequential, test_compute_concurrent,
+    test_compute_panic, test_insert_lookup_remove,
+};
+
+#[test]
+fn test_locked_map_simple() {
+    test_insert_lookup_remove::<Locked<RawSpinLock, BTree<_, _>>>(4096);
+    test_insert_lookup_remove::<Locked<RawMutex, AVLTree<_, _>>>(4096);
+    test_insert_lookup_remove::<RwLocked<AVLTree<_, _>>>(4096);
+    test_insert_lookup_remove::<RwLocked<RobinHoodHashMap<_, _>>>(4096);
+}
+
+#[test]
+fn test_locked_map_compute() {
+    let map: RwLocked<AVLTree<String, i32>> = RwLocked::new();
+
+    assert_eq!(map.upsert("a", 1), None);
+    assert_eq!(map.upsert("a", 2), Some(1));
+    assert_eq!(map.compute("a", |v| v.map(|v| v + 1)), Some(2));
+    assert_eq!(map.compute("a", |_| None), Some(3));
+    assert_eq!(map.get("a"), None);
+
+    assert_eq!(map.get_or_insert_with("b", || 10), 10);
+    assert_eq!(map.get_or_insert_with("b", || 20), 10);
+    assert_eq!(map.compare_and_swap_value("b", &11, 12), Err(12));
+    assert_eq!(map.compare_and_swap_value("b", &10, 12), Ok(10));
+    assert_eq!(map.len(), 1);
+}
+
+#[test]
+fn test_locked_map_compute_panic() {
+    test_compute_panic::<Locked<RawSpinLock, BTree<_, _>>>();
+    test_compute_panic::<Locked<RawMutex, AVLTree<_, _>>>();
+    test_compute_panic::<RwLocked<RobinHoodHashMap<_, _>>>();
+}
+
+#[test]
+fn test_locked_map_len() {
+    let map: Locked<RawSpinLock, AVLTree<i32, i32>> = Locked::new();
+    let num = 1024;
+
+    thread::scope(|s| {
+        for t in 0..4 {
+            let map = &map;
+
+            s.spawn(move |_| {
+                for i in (t..num).step_by(4) {
+                    assert_eq!(map.insert(i, i), Ok(()));
+                }
+
+                for i in (t..num).step_by(8) {
+                    assert_eq!(map.remove(&i), Ok(i));
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(map.len(), num as usize / 2);
+}
+
+#[test]
+fn test_locked_map_compute_concurrent() {
+    test_compute_concurrent::<Locked<RawSpinLock, BTree<_, _>>>();
+    test_compute_concurrent::<Locked<RawMutex, AVLTree<_, _>>>();
+    test_compute_concurrent::<RwLocked<RobinHoodHashMap<_, _>>>();
+}
+
+#[test]
+fn stress_locked_map_sequential() {
+    stress_concurrent_as_sequential::<u8, Locked<RawSpinLock, BTree<_, _>>>(100_000);
+    stress_concurrent_as_sequential::<u8, RwLocked<AVLTree<_, _>>>(100_000);
+}
+
+#[test]
+fn stress_locked_map_concurrent() {
+    stress_concurrent::<u32, Locked<RawMutex, BTree<_, _>>>(200_000, 16, false);
+    stress_concurrent::<u32, RwLocked<RobinHoodHashMap<_, _>>>(200_000, 16, false);
+}
+
+#[test]
+fn assert_locked_map_concurrent() {
+    stress_concurrent::<u8, Locked<RawSpinLock, AVLTree<_, _>>>(100_000, 32, true);
+    stress_concurrent::<u8, RwLocked<AVLTree<_, _>>>(100_000, 32, true);
+}
@@ -0,0 +1,2 @@
+mod fclock;
+mod locked;
@@ -0,0 +1,41 @@
+use cds::{
+    lock::{spinlock::RawSpinLock, RawMutex},
+    priority_queue::{ConcurrentPriorityQueue, FCPriorityQueue, PairingHeap},
+};
+
+use super::*;
+
+type SpinLockFCPQ = FCPriorityQueue<u64, u64, RawSpinLock, PairingHeap<u64, u64>>;
+type MutexFCPQ = FCPriorityQueue<u64, u64, RawMutex, PairingHeap<u64, u64>>;
+
+#[test]
+fn test_fc_priority_queue_sequential() {
+    test_sequential_concurrent_priority_queue::<SpinLockFCPQ>();
+    test_sequential_concurrent_priority_queue::<MutexFCPQ>();
+}
+
+#[test]
+fn test_fc_priority_queue_quiescent() {
+    test_quiescent_concurrent_priority_queue::<SpinLockFCPQ>();
+    test_quiescent_concurrent_priority_queue::<MutexFCPQ>();
+}
+
+#[test]
+fn test_fc_priority_queue_mpmc() {
+    test_mpmc_concurrent_priority_queue::<SpinLockFCPQ>();
+    test_mpmc_concurrent_priority_queue::<MutexFCPQ>();
+}
+
+#[test]
+fn test_fc_priority_queue_len() {
+    let queue = SpinLockFCPQ::new();
+    assert!(queue.is_empty());
+
+    for i in (0..10).rev() {
+        queue.push(i, i);
+    }
+
+    assert_eq!(queue.len(), 10);
+    assert_eq!(queue.try_pop_min(), Some((0, 0)));
+    assert_eq!(queue.len(), 9);
+}
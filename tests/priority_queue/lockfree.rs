@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use cds::priority_queue::{ConcurrentPriorityQueue, SkipListPriorityQueue};
+
+use super::*;
+
+#[test]
+fn test_skiplist_priority_queue_sequential() {
+    test_sequential_concurrent_priority_queue::<SkipListPriorityQueue<_, _>>();
+}
+
+#[test]
+fn test_skiplist_priority_queue_quiescent() {
+    test_quiescent_concurrent_priority_queue::<SkipListPriorityQueue<_, _>>();
+}
+
+#[test]
+fn test_skiplist_priority_queue_mpmc() {
+    test_mpmc_concurrent_priority_queue::<SkipListPriorityQueue<_, _>>();
+}
+
+#[test]
+fn test_skiplist_priority_queue_ties() {
+    let queue = SkipListPriorityQueue::new();
+
+    // the same keys are popped in the push order
+    for i in 0..100 {
+        queue.push(i % 3, i);
+    }
+
+    for key in 0..3 {
+        for value in (key..100).step_by(3) {
+            assert_eq!(queue.try_pop_min(), Some((key, value)));
+        }
+    }
+
+    assert!(queue.try_pop_min().is_none());
+}
+
+#[test]
+fn test_skiplist_priority_queue_len() {
+    let queue = SkipListPriorityQueue::new();
+    assert!(queue.is_empty());
+
+    for i in 0..10 {
+        queue.push(i, i);
+    }
+
+    assert_eq!(queue.len(), 10);
+    assert!(!queue.is_empty());
+
+    while queue.try_pop_min().is_some() {}
+
+    assert_eq!(queue.len(), 0);
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn test_skiplist_priority_queue_drop() {
+    let value = Arc::new(0);
+
+    {
+        let queue = SkipListPriorityQueue::new();
+
+        for i in 0..100 {
+            queue.push(i.to_string(), value.clone());
+        }
+
+        for _ in 0..50 {
+            assert!(queue.try_pop_min().is_some());
+        }
+
+        assert_eq!(Arc::strong_count(&value), 51);
+    }
+
+    assert_eq!(Arc::strong_count(&value), 1);
+}
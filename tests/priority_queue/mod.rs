@@ -0,0 +1,35 @@
+mod fclock;
+mod lockfree;
+
+use cds::priority_queue::{PairingHeap, SequentialPriorityQueue};
+
+use crate::util::priority_queue::*;
+
+#[test]
+fn test_pairing_heap() {
+    test_simple_sequential_priority_queue::<PairingHeap<_, _>>();
+}
+
+#[test]
+fn test_pairing_heap_len() {
+    let mut heap = PairingHeap::new();
+    assert!(heap.is_empty());
+    assert_eq!(heap.peek_min(), None);
+
+    // the decreasing keys make the deepest heap
+    for i in (0..100_000).rev() {
+        heap.push(i, i);
+    }
+
+    assert_eq!(heap.len(), 100_000);
+    assert_eq!(heap.peek_min(), Some((&0, &0)));
+
+    assert_eq!(heap.pop_min(), Some((0, 0)));
+    assert_eq!(heap.len(), 99_999);
+    assert_eq!(heap.peek_min(), Some((&1, &1)));
+}
+
+#[test]
+fn stress_pairing_heap() {
+    stress_sequential_priority_queue::<PairingHeap<_, _>>(100_000);
+}
@@ -54,3 +54,9 @@ fn test_fc_queue_mpmc() {
     test_mpmc_concurrent_queue::<FCQueue<_, RawMutex, Queue<_>>>();
     test_mpmc_concurrent_queue::<FCQueue<_, RawMutex, FatNodeQueue<_>>>();
 }
+
+#[test]
+fn test_fc_queue_len() {
+    test_len_concurrent_queue::<FCQueue<_, RawSpinLock, Queue<_>>>(FCQueue::len);
+    test_len_concurrent_queue::<FCQueue<_, RawMutex, FatNodeQueue<_>>>(FCQueue::len);
+}
@@ -1,4 +1,4 @@
-use cds::queue::MSQueue;
+use cds::queue::{ConcurrentQueue, MSQueue};
 
 use super::*;
 
@@ -31,3 +31,18 @@ fn test_ms_queue_mpsc() {
 fn test_ms_queue_mpmc() {
     test_mpmc_concurrent_queue::<MSQueue<_>>();
 }
+
+#[test]
+fn test_ms_queue_len() {
+    test_len_concurrent_queue::<MSQueue<_>>(MSQueue::len);
+
+    let queue = MSQueue::new();
+    assert!(queue.is_empty());
+
+    queue.push(1);
+    assert_eq!(queue.len(), 1);
+    assert!(!queue.is_empty());
+
+    assert_eq!(queue.try_pop(), Some(1));
+    assert!(queue.is_empty());
+}
@@ -0,0 +1,113 @@
+use cds::{
+    map::{ConcurrentLookup, ConcurrentMap},
+    skiplist::LockFreeSkipList,
+};
+use crossbeam_epoch::pin;
+
+use crate::util::map::{
+    stress_concurrent, stress_concurrent_as_sequential, test_compute_concurrent,
+    test_insert_lookup_remove, test_lookup_remove_concurrent,
+};
+
+#[test]
+fn test_lockfree_skiplist() {
+    test_insert_lookup_remove::<LockFreeSkipList<_, _>>(64);
+}
+
+#[test]
+fn test_lockfree_skiplist_ordered() {
+    let list: LockFreeSkipList<i32, i32> = LockFreeSkipList::new();
+    let guard = pin();
+
+    assert_eq!(list.min(&guard), None);
+    assert_eq!(list.max(&guard), None);
+    assert_eq!(list.successor(&0, &guard), None);
+    assert_eq!(list.iter(&guard).next(), None);
+
+    for i in (0..100).map(|i| i * 2) {
+        assert_eq!(list.insert(i, i), Ok(()));
+    }
+
+    assert_eq!(list.min(&guard), Some((&0, &0)));
+    assert_eq!(list.max(&guard), Some((&198, &198)));
+    assert_eq!(list.successor(&10, &guard), Some((&12, &12)));
+    assert_eq!(list.successor(&11, &guard), Some((&12, &12)));
+    assert_eq!(list.successor(&198, &guard), None);
+
+    assert!(list
+        .iter(&guard)
+        .map(|(k, _)| *k)
+        .eq((0..100).map(|i| i * 2)));
+    assert!(list
+        .range(10..20, &guard)
+        .map(|(k, _)| *k)
+        .eq([10, 12, 14, 16, 18]));
+    assert!(list
+        .range(11..=20, &guard)
+        .map(|(k, _)| *k)
+        .eq([12, 14, 16, 18, 20]));
+    assert!(list.range(..4, &guard).map(|(k, _)| *k).eq([0, 2]));
+    assert!(list.range(195.., &guard).map(|(k, _)| *k).eq([196, 198]));
+
+    assert_eq!(list.remove(&0), Ok(0));
+    assert_eq!(list.remove(&198), Ok(198));
+    assert_eq!(list.remove(&12), Ok(12));
+
+    assert_eq!(list.min(&guard), Some((&2, &2)));
+    assert_eq!(list.max(&guard), Some((&196, &196)));
+    assert_eq!(list.successor(&10, &guard), Some((&14, &14)));
+    assert!(list
+        .range(10..20, &guard)
+        .map(|(k, _)| *k)
+        .eq([10, 14, 16, 18]));
+}
+
+#[test]
+fn test_lockfree_skiplist_removed_entry() {
+    let list: LockFreeSkipList<String, String> = LockFreeSkipList::new();
+    let guard = pin();
+
+    for key in ["a", "b", "c"] {
+        assert_eq!(list.insert(key.to_string(), key.repeat(16)), Ok(()));
+    }
+
+    assert_eq!(
+        list.successor("a", &guard).map(|(k, _)| k.as_str()),
+        Some("b")
+    );
+
+    // the entry read under the guard is still alive after it is removed
+    let (key, value) = list.min(&guard).unwrap();
+    assert_eq!(list.remove("a"), Ok("a".repeat(16)));
+    assert_eq!(
+        (key.as_str(), value.as_str()),
+        ("a", "a".repeat(16).as_str())
+    );
+
+    assert_eq!(list.min(&guard).map(|(k, _)| k.as_str()), Some("b"));
+}
+
+#[test]
+fn test_lockfree_skiplist_lookup_remove_concurrent() {
+    test_lookup_remove_concurrent::<LockFreeSkipList<_, _>>();
+}
+
+#[test]
+fn test_lockfree_skiplist_compute_concurrent() {
+    test_compute_concurrent::<LockFreeSkipList<_, _>>();
+}
+
+#[test]
+fn stress_lockfree_skiplist_sequential() {
+    stress_concurrent_as_sequential::<u8, LockFreeSkipList<_, _>>(100_000);
+}
+
+#[test]
+fn stress_lockfree_skiplist_concurrent() {
+    stress_concurrent::<u32, LockFreeSkipList<_, _>>(200_000, 16, false);
+}
+
+#[test]
+fn assert_lockfree_skiplist_concurrent() {
+    stress_concurrent::<u8, LockFreeSkipList<_, _>>(100_000, 32, true);
+}
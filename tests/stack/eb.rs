@@ -19,3 +19,30 @@ fn test_ebstack() {
 
     assert!(stack.try_pop().is_none());
 }
+
+#[test]
+fn test_ebstack_len() {
+    let stack = EBStack::new();
+
+    scope(|scope| {
+        for _ in 0..8 {
+            scope.spawn(|_| {
+                for i in 0..1_000 {
+                    stack.push(i);
+
+                    if i % 2 == 0 {
+                        assert!(stack.try_pop().is_some());
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(stack.len(), 4_000);
+
+    while stack.try_pop().is_some() {}
+
+    assert_eq!(stack.len(), 0);
+    assert!(stack.is_empty());
+}
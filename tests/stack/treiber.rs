@@ -1,4 +1,5 @@
 use cds::stack::{ConcurrentStack, TreiberStack};
+use crossbeam_utils::thread::scope;
 
 #[test]
 fn test_treiber_stack() {
@@ -24,3 +25,30 @@ fn test_treiber_stack() {
     assert_eq!(stack.is_empty(), true);
     assert_eq!(stack.try_pop(), None);
 }
+
+#[test]
+fn test_treiber_stack_len() {
+    let stack = TreiberStack::new();
+
+    scope(|scope| {
+        for _ in 0..8 {
+            scope.spawn(|_| {
+                for i in 0..1_000 {
+                    stack.push(i);
+                }
+
+                for _ in 0..500 {
+                    assert!(stack.try_pop().is_some());
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(stack.len(), 4_000);
+
+    while stack.try_pop().is_some() {}
+
+    assert_eq!(stack.len(), 0);
+    assert_eq!(stack.is_empty(), true);
+}
@@ -1,7 +1,13 @@
+mod art;
 mod avltree;
+mod bst;
 mod btree;
+mod hashtable;
 mod linkedlist;
 mod lock;
+mod map;
+mod priority_queue;
 mod queue;
+mod skiplist;
 mod stack;
 mod util;
@@ -1,4 +1,6 @@
+use cds::map::ConcurrentLookup;
 use cds::map::ConcurrentMap;
+use cds::map::SequentialLookup;
 use cds::map::SequentialMap;
 use cds::util::random::Random;
 use crossbeam_utils::thread;
@@ -13,6 +15,7 @@ use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::marker::PhantomData;
+use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::time::Duration;
 use std::time::Instant;
 
@@ -31,7 +34,7 @@ enum OperationType {
 
 pub fn stress_sequential<K, M>(iter: u64)
 where
-    K: Ord + Clone + Random + Debug,
+    K: Ord + Clone + Random + Debug + Hash,
     M: SequentialMap<K, u64>,
 {
     // 10 times try to get not existing key, or return if failing
@@ -79,7 +82,7 @@ where
                     //     i, not_existing_key, value
                     // );
                     assert_eq!(ref_map.insert(not_existing_key.clone(), value), None);
-                    assert_eq!(map.insert(&not_existing_key, value), Ok(()));
+                    assert_eq!(map.insert(not_existing_key, value), Ok(()));
                 }
                 Operation::Lookup => {
                     // should fail
@@ -104,7 +107,10 @@ where
                     let value: u64 = rng.gen();
 
                     // println!("[{:0>10}] InsertSome: ({:?}, {})", i, existing_key, value);
-                    assert_eq!(map.insert(&existing_key, value), Err(value));
+                    assert_eq!(
+                        map.insert(existing_key.clone(), value),
+                        Err((existing_key, value))
+                    );
                 }
                 Operation::Lookup => {
                     // should success
@@ -166,11 +172,19 @@ where
         }
     }
 
-    fn insert(&mut self, key: &K, value: V) -> Result<(), V> {
+    fn insert(&mut self, key: K, value: V) -> Result<(), (K, V)> {
         self.inner.insert(key, value)
     }
+}
 
-    fn lookup(&self, key: &K) -> Option<&V> {
+impl<K, V, M, Q> SequentialLookup<K, V, Q> for Sequentialized<K, V, M>
+where
+    K: Eq,
+    V: Clone,
+    M: ConcurrentMap<K, V> + ConcurrentLookup<K, V, Q>,
+    Q: ?Sized,
+{
+    fn lookup(&self, key: &Q) -> Option<&V> {
         let value = self.inner.get(key);
 
         // HACK: temporarily save the value, and get its reference safely
@@ -180,14 +194,14 @@ where
         }
     }
 
-    fn remove(&mut self, key: &K) -> Result<V, ()> {
+    fn remove(&mut self, key: &Q) -> Result<V, ()> {
         self.inner.remove(key)
     }
 }
 
 pub fn stress_concurrent_as_sequential<K, M>(iter: u64)
 where
-    K: Ord + Clone + Random + Debug,
+    K: Ord + Clone + Random + Debug + Hash,
     M: ConcurrentMap<K, u64>,
 {
     stress_sequential::<K, Sequentialized<K, u64, M>>(iter)
@@ -237,7 +251,7 @@ where
                         Operation::Insert => {
                             let value = u64::gen(&mut rng);
                             let start = Instant::now();
-                            let result = match map.insert(&key, value) {
+                            let result = match map.insert(key.clone(), value) {
                                 Ok(()) => Ok(value),
                                 Err(_) => Err(()),
                             };
@@ -725,3 +739,210 @@ fn verify_state_log<K, V: Clone + PartialEq>(
         }
     }
 }
+
+/// insert the keys, fail to insert them again, find them, remove them, and fail to remove them again
+pub fn test_insert_lookup_remove<M>(num: i32)
+where
+    M: ConcurrentMap<i32, i32>,
+{
+    let map = M::new();
+
+    for i in 0..num {
+        assert_eq!(map.insert(i, i), Ok(()));
+    }
+
+    for i in 0..num {
+        assert_eq!(map.insert(i, i), Err((i, i)));
+    }
+
+    for i in 0..num {
+        assert_eq!(map.get(&i), Some(i));
+        assert_eq!(
+            map.lookup(&i, |value| value.map(|value| value + 1)),
+            Some(i + 1)
+        );
+    }
+
+    for i in 0..num {
+        assert_eq!(map.remove(&i), Ok(i));
+    }
+
+    for i in 0..num {
+        assert_eq!(map.remove(&i), Err(()));
+        assert_eq!(map.get(&i), None);
+    }
+}
+
+/// remove the values while other threads read them, so the values which own the heap memory should be alive on reading
+pub fn test_lookup_remove_concurrent<M>()
+where
+    M: Sync + ConcurrentMap<u64, String>,
+{
+    let map = M::new();
+    let num = 64;
+    let value = |key: u64| key.to_string().repeat(16);
+
+    thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|_| {
+                for _ in 0..2_000 {
+                    for key in 0..num {
+                        let _ = map.insert(key, value(key));
+
+                        if let Ok(removed) = map.remove(&key) {
+                            assert_eq!(removed, value(key));
+                        }
+                    }
+                }
+            });
+
+            s.spawn(|_| {
+                for _ in 0..2_000 {
+                    for key in 0..num {
+                        map.lookup(&key, |found| {
+                            if let Some(found) = found {
+                                assert_eq!(*found, value(key));
+                            }
+                        });
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+}
+
+/// panic in the functions of lookup while other threads operate, so the panic should not keep any lock held
+pub fn test_lookup_panic<M>()
+where
+    M: Sync + ConcurrentMap<u64, String>,
+{
+    let map = M::new();
+    let num = 64;
+    let value = |key: u64| key.to_string();
+
+    for key in 0..num {
+        assert_eq!(map.insert(key, value(key)), Ok(()));
+    }
+
+    thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|_| {
+                for key in 0..num {
+                    let result = catch_unwind(AssertUnwindSafe(|| {
+                        map.lookup(&key, |_| panic!("lookup"));
+                    }));
+                    assert!(result.is_err());
+                }
+            });
+
+            s.spawn(|_| {
+                for key in 0..num {
+                    assert_eq!(map.get(&key), Some(value(key)));
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for key in 0..num {
+        assert_eq!(map.remove(&key), Ok(value(key)));
+    }
+}
+
+/// panic in the functions of compute while other threads operate, so the panic should neither change the value nor block them
+pub fn test_compute_panic<M>()
+where
+    M: Sync + ConcurrentMap<u64, String>,
+{
+    let map = M::new();
+    let num = 64;
+    let value = |key: u64| key.to_string();
+
+    for key in 0..num {
+        assert_eq!(map.insert(key, value(key)), Ok(()));
+    }
+
+    thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|_| {
+                for key in 0..num {
+                    let result = catch_unwind(AssertUnwindSafe(|| {
+                        map.compute(&key, |_| panic!("compute"));
+                    }));
+                    assert!(result.is_err());
+                }
+            });
+
+            s.spawn(|_| {
+                for key in 0..num {
+                    assert_eq!(map.get(&key), Some(value(key)));
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for key in 0..num {
+        assert_eq!(map.remove(&key), Ok(value(key)));
+    }
+}
+
+/// count the keys by compute on many threads, and then remove them by compute on many threads, so no update should be lost or repeated
+pub fn test_compute_concurrent<M>()
+where
+    M: Sync + ConcurrentMap<u64, u64>,
+{
+    let map = M::new();
+    let num = 64;
+    let thread_num = 4;
+    let iter = 500;
+
+    assert_eq!(map.upsert(&num, 0), None);
+    assert_eq!(map.upsert(&num, 1), Some(0));
+    assert_eq!(map.compare_and_swap_value(&num, &0, 2), Err(2));
+    assert_eq!(map.compare_and_swap_value(&num, &1, 2), Ok(1));
+    assert_eq!(map.compute(&num, |_| None), Some(2));
+    assert_eq!(map.compute(&num, |_| None), None);
+
+    thread::scope(|s| {
+        for _ in 0..thread_num {
+            s.spawn(|_| {
+                for _ in 0..iter {
+                    for key in 0..num {
+                        map.compute(&key, |count| Some(count.map_or(1, |count| count + 1)));
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for key in 0..num {
+        assert_eq!(map.get(&key), Some(thread_num * iter));
+    }
+
+    let removed = thread::scope(|s| {
+        let handles = (0..thread_num)
+            .map(|_| {
+                s.spawn(|_| {
+                    (0..num)
+                        .filter_map(|key| map.compute(&key, |_| None))
+                        .sum::<u64>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .sum::<u64>()
+    })
+    .unwrap();
+
+    assert_eq!(removed, num * thread_num * iter);
+
+    for key in 0..num {
+        assert_eq!(map.get(&key), None);
+    }
+}
@@ -1,2 +1,3 @@
 pub mod map;
+pub mod priority_queue;
 pub mod queue;
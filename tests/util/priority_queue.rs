@@ -0,0 +1,129 @@
+use std::{cmp::Reverse, collections::BinaryHeap, thread};
+
+use cds::priority_queue::{ConcurrentPriorityQueue, SequentialPriorityQueue};
+use rand::{prelude::SliceRandom, thread_rng, Rng};
+
+pub fn test_simple_sequential_priority_queue<Q: SequentialPriorityQueue<u64, u64>>() {
+    let mut queue = Q::new();
+
+    queue.push(3, 30);
+    queue.push(1, 10);
+    queue.push(4, 40);
+    queue.push(1, 11);
+    queue.push(5, 50);
+
+    assert_eq!(queue.pop_min().map(|(key, _)| key), Some(1));
+    assert_eq!(queue.pop_min().map(|(key, _)| key), Some(1));
+    assert_eq!(queue.pop_min(), Some((3, 30)));
+    assert_eq!(queue.pop_min(), Some((4, 40)));
+    assert_eq!(queue.pop_min(), Some((5, 50)));
+
+    assert_eq!(queue.pop_min(), None);
+}
+
+/// compare with std::collections::BinaryHeap on random pushes and pops
+pub fn stress_sequential_priority_queue<Q: SequentialPriorityQueue<u64, u64>>(iter: usize) {
+    let mut rng = thread_rng();
+    let mut queue = Q::new();
+    let mut reference = BinaryHeap::new();
+
+    for i in 0..iter {
+        if rng.gen_bool(0.6) {
+            // the key is unique by the index, so the popped value is deterministic
+            let key = rng.gen_range(0..1_000) * iter as u64 + i as u64;
+            queue.push(key, i as u64);
+            reference.push(Reverse((key, i as u64)));
+        } else {
+            assert_eq!(queue.pop_min(), reference.pop().map(|Reverse(entry)| entry));
+        }
+    }
+
+    while let Some(Reverse(entry)) = reference.pop() {
+        assert_eq!(queue.pop_min(), Some(entry));
+    }
+
+    assert_eq!(queue.pop_min(), None);
+}
+
+pub fn test_sequential_concurrent_priority_queue<Q: ConcurrentPriorityQueue<u64, u64>>() {
+    let queue = Q::new();
+
+    // every key is pushed twice
+    let mut keys: Vec<u64> = (0..1_000).chain(0..1_000).collect();
+    keys.shuffle(&mut thread_rng());
+
+    for key in keys.iter() {
+        queue.push(*key, key + 1);
+    }
+
+    for key in 0..1_000 {
+        assert_eq!(queue.pop_min(), (key, key + 1));
+        assert_eq!(queue.try_pop_min(), Some((key, key + 1)));
+    }
+
+    assert!(queue.try_pop_min().is_none());
+}
+
+/// push concurrently, then pop sequentially, so the order must be exact after the quiescence
+pub fn test_quiescent_concurrent_priority_queue<Q: Sync + ConcurrentPriorityQueue<u64, u64>>() {
+    let queue = Q::new();
+
+    thread::scope(|scope| {
+        for t in 0..10 {
+            let queue = &queue;
+
+            scope.spawn(move || {
+                let mut keys: Vec<u64> = (0..10_000).map(|i| i * 10 + t).collect();
+                keys.shuffle(&mut thread_rng());
+
+                for key in keys {
+                    queue.push(key, key);
+                }
+            });
+        }
+    });
+
+    for key in 0..100_000 {
+        assert_eq!(queue.try_pop_min(), Some((key, key)));
+    }
+
+    assert!(queue.try_pop_min().is_none());
+}
+
+pub fn test_mpmc_concurrent_priority_queue<Q: Sync + ConcurrentPriorityQueue<u64, u64>>() {
+    let queue = Q::new();
+
+    let mut popped: Vec<u64> = thread::scope(|scope| {
+        let mut consumers = Vec::new();
+
+        for t in 0..10 {
+            let queue = &queue;
+
+            scope.spawn(move || {
+                for i in 0..100_000 {
+                    queue.push(i % 1_000, i * 10 + t);
+                }
+            });
+
+            consumers.push(scope.spawn(move || {
+                (0..100_000)
+                    .map(|_| {
+                        let (key, value) = queue.pop_min();
+                        assert_eq!(key, value / 10 % 1_000);
+                        value
+                    })
+                    .collect::<Vec<_>>()
+            }));
+        }
+
+        consumers
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    });
+
+    // every value is popped exactly once
+    popped.sort_unstable();
+    assert!(popped.into_iter().eq(0..1_000_000));
+    assert!(queue.try_pop_min().is_none());
+}
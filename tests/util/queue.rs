@@ -154,3 +154,28 @@ pub fn test_mpmc_concurrent_queue<Q: Sync + ConcurrentQueue<u64>>() {
 
     assert!(queue.try_pop().is_none());
 }
+
+/// the size API is not on the trait, so the test gets it as a function
+pub fn test_len_concurrent_queue<Q: Sync + ConcurrentQueue<u64>>(len: fn(&Q) -> usize) {
+    let queue = Q::new();
+
+    thread::scope(|scope| {
+        for _ in 0..8 {
+            scope.spawn(|| {
+                for i in 0..1_000 {
+                    queue.push(i);
+                }
+
+                for _ in 0..500 {
+                    assert!(queue.try_pop().is_some());
+                }
+            });
+        }
+    });
+
+    assert_eq!(len(&queue), 4_000);
+
+    while queue.try_pop().is_some() {}
+
+    assert_eq!(len(&queue), 0);
+}